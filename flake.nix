@@ -124,7 +124,7 @@
                           XC=0
 
                           cargo fmt --check || XC=$(( XC + 0x01 ))
-                          (cargo clippy --release && cargo clippy --features _gen) || XC=$(( XC + 0x02 ))
+                          (cargo clippy --release && cargo clippy --features _gen && cargo clippy --features serde) || XC=$(( XC + 0x02 ))
 
                           deadnix . || XC=$(( XC + 0x04 ))
                           statix check . || XC=$(( XC + 0x08 ))
@@ -145,7 +145,7 @@
                             import sys
                             from subprocess import run
                             run(
-                              ["cargo", "test", "--no-fail-fast"]
+                              ["cargo", "test", "--no-fail-fast", "--features", "serde"]
                               + (["--", "--include-ignored"] if sys.stdout.isatty() else []),
                               check=True,
                             )
@@ -172,7 +172,7 @@
                             import sys
                             from subprocess import run
                             run(
-                              ["cargo", "test", "--no-fail-fast"]
+                              ["cargo", "test", "--no-fail-fast", "--features", "serde"]
                               + (["--", "--include-ignored"] if sys.stdout.isatty() else []),
                               check=True,
                             )
@@ -4,7 +4,7 @@ use std::{cmp::Ordering, env::args};
 
 use cp437_tools::{
     internal::{process, ExitCode, Input, Output},
-    prelude::meta,
+    prelude::meta::{self, Severity},
 };
 
 #[allow(dead_code)]
@@ -20,10 +20,15 @@ pub fn main() -> ExitCode {
 #[allow(missing_docs, reason = "Just an entry point")]
 #[allow(clippy::missing_docs_in_private_items, reason = "Just an entry point")]
 pub fn exec(args: &[String]) -> ExitCode {
-    let exit_code = match args.len().cmp(&2) {
+    let (strict, args) = match args {
+        [_, option, rest @ ..] if option == "--strict" => (true, rest),
+        [_, rest @ ..] => (false, rest),
+        [] => (false, args),
+    };
+    let exit_code = match args.len().cmp(&1) {
         Ordering::Less => ExitCode::USAGE(String::from("Missing input file")),
         Ordering::Greater => ExitCode::USAGE(String::from("Too many arguments")),
-        Ordering::Equal => process(&args[1], run),
+        Ordering::Equal => process(&args[0], |i, o| return run(i, o, strict)),
     };
 
     exit_code.print();
@@ -32,13 +37,20 @@ pub fn exec(args: &[String]) -> ExitCode {
 
 #[allow(missing_docs, reason = "Just an entry point")]
 #[allow(clippy::missing_docs_in_private_items, reason = "Just an entry point")]
-pub fn run(input: &mut Input, output: &mut Output) -> ExitCode {
-    if let Err(msg) = meta::check(input.meta.as_ref()) {
-        output.write(format!("\x1B[3;31m{msg}\x1B[0m\n").as_bytes())?;
-        return ExitCode::FAIL(msg);
+pub fn run(input: &mut Input, output: &mut Output, strict: bool) -> ExitCode {
+    let bytes = input.bytes()?;
+    let mut report = meta::validate(input.meta.as_ref());
+    report.issues.extend(meta::validate_content(input.meta.as_ref(), &bytes).issues);
+    for issue in &report.issues {
+        let colour = match issue.severity {
+            Severity::Error => "3;31",
+            Severity::Warning => "3;33",
+            Severity::Info => "3;34",
+        };
+        output.write(format!("\x1B[{colour}m{issue}\x1B[0m\n").as_bytes())?;
     }
 
-    return ExitCode::OK;
+    return report.failure(strict).map_or(ExitCode::OK, |issue| return ExitCode::from(issue.error.clone()));
 }
 
 #[path = "."]
@@ -65,23 +77,35 @@ mod tests {
         );
     }
 
+    #[test]
+    fn strict() {
+        assert_eq!(
+            exec(&[
+                String::from("cp437-check-meta"),
+                String::from("--strict"),
+                String::from("res/test/amiga_font.ans")
+            ]),
+            ExitCode::FAIL(String::from("Font is unsupported (Amiga Topaz 1+)")),
+        );
+    }
+
     #[test]
     fn ok() -> Result<(), String> {
-        return test::ok(run, "res/test/meta.ans", indoc! {""});
+        return test::ok(|i, o| return run(i, o, false), "res/test/meta.ans", indoc! {""});
     }
 
     #[test]
     fn no_meta() -> Result<(), String> {
-        return test::ok(run, "res/test/simple.ans", indoc! {""});
+        return test::ok(|i, o| return run(i, o, false), "res/test/simple.ans", indoc! {""});
     }
 
     #[test]
     fn title() -> Result<(), String> {
         return test::file_err(
-            run,
+            |i, o| return run(i, o, false),
             "res/test/bad_title.ans",
             indoc! {"
-                \x1B[3;31mTitle contains illegal characters (0x00 is a control character)\x1B[0m
+                \x1B[3;31merror[control-character]: Title contains illegal characters (0x00 is a control character)\x1B[0m
             "},
         );
     }
@@ -89,10 +113,10 @@ mod tests {
     #[test]
     fn author() -> Result<(), String> {
         return test::file_err(
-            run,
+            |i, o| return run(i, o, false),
             "res/test/bad_author.ans",
             indoc! {"
-                \x1B[3;31mAuthor contains illegal characters (0x00 is a control character)\x1B[0m
+                \x1B[3;31merror[control-character]: Author contains illegal characters (0x00 is a control character)\x1B[0m
             "},
         );
     }
@@ -100,10 +124,10 @@ mod tests {
     #[test]
     fn group() -> Result<(), String> {
         return test::file_err(
-            run,
+            |i, o| return run(i, o, false),
             "res/test/bad_group.ans",
             indoc! {"
-                \x1B[3;31mGroup contains illegal characters (0x00 is a control character)\x1B[0m
+                \x1B[3;31merror[control-character]: Group contains illegal characters (0x00 is a control character)\x1B[0m
             "},
         );
     }
@@ -111,10 +135,10 @@ mod tests {
     #[test]
     fn date() -> Result<(), String> {
         return test::file_err(
-            run,
+            |i, o| return run(i, o, false),
             "res/test/bad_date.ans",
             indoc! {"
-                \x1B[3;31mDate format is wrong (input contains invalid characters)\x1B[0m
+                \x1B[3;31merror[wrong-format]: Date format is wrong (input contains invalid characters)\x1B[0m
             "},
         );
     }
@@ -122,10 +146,21 @@ mod tests {
     #[test]
     fn r#type() -> Result<(), String> {
         return test::file_err(
-            run,
+            |i, o| return run(i, o, false),
+            "res/test/bad_type.ans",
+            indoc! {"
+                \x1B[3;31merror[unknown]: Type is unknown (Unknown 255/Unknown 255)\x1B[0m
+            "},
+        );
+    }
+
+    #[test]
+    fn type_strict() -> Result<(), String> {
+        return test::file_err(
+            |i, o| return run(i, o, true),
             "res/test/bad_type.ans",
             indoc! {"
-                \x1B[3;31mType is unsupported (Unknown 255/Unknown 255)\x1B[0m
+                \x1B[3;31merror[unknown]: Type is unknown (Unknown 255/Unknown 255)\x1B[0m
             "},
         );
     }
@@ -133,21 +168,56 @@ mod tests {
     #[test]
     fn flags() -> Result<(), String> {
         return test::file_err(
-            run,
+            |i, o| return run(i, o, false),
             "res/test/bad_flags.ans",
             indoc! {"
-                \x1B[3;31mInvalid letter spacing\x1B[0m
+                \x1B[3;31merror[letter-spacing]: Invalid letter spacing\x1B[0m
+                \x1B[3;31merror[aspect-ratio]: Invalid aspect ratio\x1B[0m
+                \x1B[3;31merror[invalid]: Invalid flags\x1B[0m
+            "},
+        );
+    }
+
+    #[test]
+    fn blink() -> Result<(), String> {
+        return test::ok(
+            |i, o| return run(i, o, true),
+            "res/test/blink.ans",
+            indoc! {"
+                \x1B[3;34minfo[blink]: Flags enable blinking text instead of iCE colours\x1B[0m
             "},
         );
     }
 
     #[test]
     fn font() -> Result<(), String> {
+        return test::ok(
+            |i, o| return run(i, o, false),
+            "res/test/amiga_font.ans",
+            indoc! {"
+                \x1B[3;33mwarning[unsupported]: Font is unsupported (Amiga Topaz 1+)\x1B[0m
+            "},
+        );
+    }
+
+    #[test]
+    fn font_strict() -> Result<(), String> {
+        return test::file_err(
+            |i, o| return run(i, o, true),
+            "res/test/amiga_font.ans",
+            indoc! {"
+                \x1B[3;33mwarning[unsupported]: Font is unsupported (Amiga Topaz 1+)\x1B[0m
+            "},
+        );
+    }
+
+    #[test]
+    fn unknown_font() -> Result<(), String> {
         return test::file_err(
-            run,
+            |i, o| return run(i, o, false),
             "res/test/bad_font.ans",
             indoc! {"
-                \x1B[3;31mFont is unsupported (IBM FOO)\x1B[0m
+                \x1B[3;31merror[unknown]: Font is unknown (IBM FOO)\x1B[0m
             "},
         );
     }
@@ -155,10 +225,26 @@ mod tests {
     #[test]
     fn notes() -> Result<(), String> {
         return test::file_err(
-            run,
+            |i, o| return run(i, o, false),
             "res/test/bad_comment.ans",
             indoc! {"
-                \x1B[3;31mNotes[0] contains illegal characters (0x00 is a control character)\x1B[0m
+                \x1B[3;31merror[control-character]: Notes[0] contains illegal characters (0x00 is a control character)\x1B[0m
+                \x1B[3;31merror[mismatch]: Size doesn't match the contents (declared 404, actual 0)\x1B[0m
+            "},
+        );
+    }
+
+    #[test]
+    fn content() -> Result<(), String> {
+        return test::file_err(
+            |i, o| return run(i, o, false),
+            "res/test/bad_content.ans",
+            indoc! {"
+                \x1B[3;31merror[mismatch]: Size doesn't match the contents (declared 23, actual 152)\x1B[0m
+                \x1B[3;33mwarning[mismatch]: Width doesn't match the contents (declared 80, actual 10)\x1B[0m
+                \x1B[3;33mwarning[escape-sequence]: Data contains escape sequences, but the type is plain text\x1B[0m
+                \x1B[3;33mwarning[early-eof]: Data contains an EOF marker before its end (at 0x10)\x1B[0m
+                \x1B[3;31merror[duplicate]: Data ends with another SAUCE record\x1B[0m
             "},
         );
     }
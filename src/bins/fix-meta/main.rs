@@ -0,0 +1,234 @@
+//! Repair a file's metadata.
+
+use std::{
+    env::args,
+    fs::OpenOptions,
+    io::{stdout, IsTerminal as _, Read as _},
+};
+
+use cp437_tools::{
+    internal::{process, ExitCode, Input, Output},
+    prelude::meta::{self, Meta},
+};
+
+#[allow(dead_code)]
+#[must_use]
+#[allow(missing_docs, reason = "Just an entry point")]
+#[allow(clippy::missing_docs_in_private_items, reason = "Just an entry point")]
+pub fn main() -> ExitCode {
+    return exec(&args().collect::<Vec<String>>());
+}
+
+#[inline]
+#[must_use]
+#[allow(missing_docs, reason = "Just an entry point")]
+#[allow(clippy::missing_docs_in_private_items, reason = "Just an entry point")]
+pub fn exec(args: &[String]) -> ExitCode {
+    let exit_code = match args {
+        [] | [_] => ExitCode::USAGE(String::from("Missing input file")),
+        [_, option] if option == "-i" || option == "--in-place" => ExitCode::USAGE(String::from("Missing input file")),
+        [_, option, file] if option == "-i" || option == "--in-place" => {
+            if file == "-" {
+                ExitCode::USAGE(String::from("Can't modify stdin in place"))
+            } else {
+                run_in_place(file)
+            }
+        },
+        [_, _] if stdout().is_terminal() => ExitCode::USAGE(String::from("Refusing to write to terminal")),
+        [_, file] => process(file, run),
+        _ => ExitCode::USAGE(String::from("Too many arguments")),
+    };
+
+    exit_code.print();
+    return exit_code;
+}
+
+#[allow(missing_docs, reason = "Just an entry point")]
+#[allow(clippy::missing_docs_in_private_items, reason = "Just an entry point")]
+pub fn run(input: &mut Input, output: &mut Output) -> ExitCode {
+    let bytes = input.bytes()?;
+    let (old, range) = meta::parse(&bytes)?;
+    output.write(&bytes[range.clone()])?;
+
+    if let Some(old) = old {
+        let new = meta::fix(&old, u32::try_from(range.len())?);
+        eprint!("{}", diff(&old, &new));
+        meta::write(output, &new)?;
+    }
+
+    return ExitCode::OK;
+}
+
+/// Repair a file's metadata without copying its contents.
+#[must_use]
+pub fn run_in_place(path: &str) -> ExitCode {
+    let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+    let mut bytes = vec![];
+    file.read_to_end(&mut bytes)?;
+
+    let (old, range) = meta::parse(&bytes)?;
+    if let Some(old) = old {
+        let new = meta::fix(&old, u32::try_from(range.len())?);
+        eprint!("{}", diff(&old, &new));
+        new.replace_in(&mut file)?;
+    }
+
+    return ExitCode::OK;
+}
+
+/// How to show a single field, for [`diff`].
+type Show = fn(&Meta) -> String;
+
+/// Describe every field that changed, as a pair of `-`/`+` lines each.
+fn diff(old: &Meta, new: &Meta) -> String {
+    let fields: [(&str, Show); 9] = [
+        ("Title", |m| return format!("{:?}", m.title)),
+        ("Author", |m| return format!("{:?}", m.author)),
+        ("Group", |m| return format!("{:?}", m.group)),
+        ("Date", |m| return format!("{:?}", m.date)),
+        ("Size", |m| return m.size.to_string()),
+        ("Type", |m| return m.r#type.to_string()),
+        ("Flags", |m| return format!("0x{:02X} ({})", m.flags, m.flags())),
+        ("Font", |m| return format!("{:?}", m.font)),
+        ("Notes", |m| return format!("{:?}", m.notes)),
+    ];
+
+    return fields
+        .iter()
+        .map(|(name, show)| return (name, show(old), show(new)))
+        .filter(|(_, old, new)| return old != new)
+        .map(|(name, old, new)| return format!("\x1B[31m- {name}: {old}\x1B[0m\n\x1B[32m+ {name}: {new}\x1B[0m\n"))
+        .collect();
+}
+
+#[path = "."]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+    use std::fs::copy;
+    use tempfile::tempdir;
+
+    #[path = "../../libs/internal/test_utils.rs"]
+    mod test;
+
+    #[test]
+    fn no_input() {
+        assert_eq!(exec(&[String::from("cp437-fix-meta")]), ExitCode::USAGE(String::from("Missing input file")));
+    }
+
+    #[test]
+    fn in_place_no_input() {
+        assert_eq!(
+            exec(&[String::from("cp437-fix-meta"), String::from("-i")]),
+            ExitCode::USAGE(String::from("Missing input file")),
+        );
+    }
+
+    #[test]
+    fn too_many_args() {
+        assert_eq!(
+            exec(&[String::from("cp437-fix-meta"), String::from("a"), String::from("b")]),
+            ExitCode::USAGE(String::from("Too many arguments")),
+        );
+    }
+
+    #[test]
+    fn in_place_stdin() {
+        assert_eq!(
+            exec(&[String::from("cp437-fix-meta"), String::from("--in-place"), String::from("-")]),
+            ExitCode::USAGE(String::from("Can't modify stdin in place")),
+        );
+    }
+
+    #[ignore = "Needs stdout to be a terminal"]
+    #[test]
+    fn stdout() {
+        assert_eq!(
+            exec(&[String::from("cp437-fix-meta"), String::from("a")]),
+            ExitCode::USAGE(String::from("Refusing to write to terminal")),
+        );
+    }
+
+    #[test]
+    fn no_meta() -> Result<(), String> {
+        return test::file(run, "res/test/simple.ans", "res/test/simple.ans");
+    }
+
+    #[test]
+    fn ok() -> Result<(), String> {
+        return test::file(run, "res/test/meta.ans", "res/test/meta.ans");
+    }
+
+    #[test]
+    fn title() -> Result<(), String> {
+        return check("res/test/bad_title.ans", |meta| return Meta { title: String::new(), ..meta });
+    }
+
+    #[test]
+    fn date() -> Result<(), String> {
+        return check("res/test/bad_date.ans", |meta| return Meta { date: String::new(), ..meta });
+    }
+
+    #[test]
+    fn flags() -> Result<(), String> {
+        return check("res/test/bad_flags.ans", |meta| return Meta { flags: 0x01, ..meta });
+    }
+
+    #[test]
+    fn font() -> Result<(), String> {
+        return check("res/test/bad_font.ans", |meta| return Meta { font: String::new(), ..meta });
+    }
+
+    #[test]
+    fn notes() -> Result<(), String> {
+        return check("res/test/bad_comment.ans", |meta| {
+            return Meta { size: 0, notes: vec![String::new()], ..meta };
+        });
+    }
+
+    #[test]
+    fn size() -> Result<(), String> {
+        return check("res/test/bad_content.ans", |meta| return Meta { size: 152, ..meta });
+    }
+
+    #[test]
+    fn in_place() -> Result<(), String> {
+        let tmp_dir = tempdir().map_err(|err| return err.to_string())?;
+        let target = tmp_dir.path().join("output.ans").to_string_lossy().to_string();
+        copy("res/test/bad_flags.ans", &target).map_err(|err| return err.to_string())?;
+
+        assert_eq!(run_in_place(&target), ExitCode::OK);
+        let meta = meta::get("res/test/bad_flags.ans").map_err(|err| return err.to_string())?.unwrap_or_default();
+        assert_eq!(meta::get(&target).map_err(|err| return err.to_string())?, Some(Meta { flags: 0x01, ..meta }));
+
+        tmp_dir.close().map_err(|err| return err.to_string())?;
+
+        return Ok(());
+    }
+
+    #[test]
+    fn diff() {
+        let old = Meta { title: String::from("\0"), flags: 0xFF, ..Default::default() };
+        let new = meta::fix(&old, old.size);
+        assert_eq!(
+            super::diff(&old, &new),
+            indoc! {"
+                \x1B[31m- Title: \"\\0\"\x1B[0m
+                \x1B[32m+ Title: \" \"\x1B[0m
+                \x1B[31m- Flags: 0xFF (ice,invalid-ls,invalid-ar)\x1B[0m
+                \x1B[32m+ Flags: 0x01 (ice,legacy-ls,legacy-ar)\x1B[0m
+            "},
+        );
+        assert_eq!(super::diff(&new, &new), "");
+    }
+
+    /// Fix a file, and compare the result against its original metadata with
+    /// some changes applied.
+    fn check(input: &str, expected: fn(Meta) -> Meta) -> Result<(), String> {
+        let meta = meta::get(input).map_err(|err| return err.to_string())?.unwrap_or_default();
+        return test::file_meta(run, input, Some(expected(meta)));
+    }
+}
@@ -93,7 +93,7 @@ fn generate_png(scheme: &ColourScheme) -> ExitCode {
     }
     let mut output = Output::file(&path)?;
 
-    cmd_to_png::run(&mut input, &mut output, &scheme.name());
+    cmd_to_png::run(&mut input, &mut output, &scheme.name(), "on", None);
 
     assert!(Command::new("magick")
         .arg(&path)
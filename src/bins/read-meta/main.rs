@@ -1,11 +1,12 @@
 //! Read a file's metadata.
 
 use humansize::{format_size, BINARY};
+use serde_json::{json, Map, Value};
 use std::{cmp::Ordering, env::args};
 
 use cp437_tools::{
     internal::{process, ExitCode, Input, Output},
-    prelude::meta::{self, Meta},
+    prelude::meta::{self, AspectRatio, CheckError, FileType, LetterSpacing, Meta, SauceFlags, Severity},
 };
 
 #[allow(dead_code)]
@@ -21,33 +22,65 @@ pub fn main() -> ExitCode {
 #[allow(missing_docs, reason = "Just an entry point")]
 #[allow(clippy::missing_docs_in_private_items, reason = "Just an entry point")]
 pub fn exec(args: &[String]) -> ExitCode {
-    let exit_code = match args.len().cmp(&2) {
-        Ordering::Less => ExitCode::USAGE(String::from("Missing input file")),
-        Ordering::Greater => ExitCode::USAGE(String::from("Too many arguments")),
-        Ordering::Equal => process(&args[1], run),
+    let mut format = Some("text");
+    let mut positional = vec![];
+    let mut iter = args.iter().skip(1);
+    while let Some(arg) = iter.next() {
+        if arg == "--format" {
+            format = iter.next().map(String::as_str);
+        } else {
+            positional.push(arg.clone());
+        }
+    }
+    let exit_code = match (format, positional.len().cmp(&1)) {
+        (None, _) => ExitCode::USAGE(String::from("Missing format")),
+        (_, Ordering::Less) => ExitCode::USAGE(String::from("Missing input file")),
+        (_, Ordering::Greater) => ExitCode::USAGE(String::from("Too many arguments")),
+        (Some(format), Ordering::Equal) => match Format::get(format) {
+            Ok(format) => process(&positional[0], |i, o| return run(i, o, format)),
+            Err(exit_code) => exit_code,
+        },
     };
 
     exit_code.print();
     return exit_code;
 }
 
+/// How to print the metadata.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Format {
+    /// Human readable, highlighted text.
+    Text,
+    /// A JSON object.
+    Json,
+    /// A TOML document.
+    Toml,
+    /// A YAML document.
+    Yaml,
+    /// Shell variable assignments, prefixed with `SAUCE_`.
+    Env,
+}
+
+impl Format {
+    /// Parse a format name.
+    fn get(name: &str) -> Result<Format, ExitCode> {
+        return match name.to_lowercase().as_str() {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "toml" => Ok(Format::Toml),
+            "yaml" => Ok(Format::Yaml),
+            "env" => Ok(Format::Env),
+            _ => Err(ExitCode::USAGE(format!("Unknown format: {name}"))),
+        };
+    }
+}
+
 #[allow(missing_docs, reason = "Just an entry point")]
 #[allow(clippy::missing_docs_in_private_items, reason = "Just an entry point")]
-pub fn run(input: &mut Input, output: &mut Output) -> ExitCode {
-    output.write(
-        format!(
-            "\x1B[{}mMetadata\x1B[0m:\n",
-            match input.meta {
-                Some(_) => "4",
-                None => "4;33",
-            }
-        )
-        .as_bytes(),
-    )?;
-
+pub fn run(input: &mut Input, output: &mut Output, format: Format) -> ExitCode {
     let meta = input.meta.clone().unwrap_or(Meta {
         size: input.size,
-        r#type: (0, 0),
+        r#type: FileType::None,
         width: 0,
         height: 0,
         flags: 0x01,
@@ -55,21 +88,168 @@ pub fn run(input: &mut Input, output: &mut Output) -> ExitCode {
         ..Default::default()
     });
 
-    print_title(output, &meta)?;
-    print_author(output, &meta)?;
-    print_group(output, &meta)?;
-    print_date(output, &meta)?;
-    print_size(output, &meta)?;
-    print_type(output, &meta)?;
-    print_width(output, &meta)?;
-    print_height(output, &meta)?;
-    print_flags(output, &meta)?;
-    print_font(output, &meta)?;
-    print_notes(output, &meta)?;
+    return match format {
+        Format::Text => print_text(output, &meta, input.meta.is_some()),
+        Format::Json => {
+            let mut json = serde_json::to_string_pretty(&document(&meta, input.meta.is_some()))
+                .map_err(|err| return err.to_string())?;
+            json.push('\n');
+            output.write(json.as_bytes()).into()
+        },
+        Format::Toml => output
+            .write(
+                toml::to_string(&document(&meta, input.meta.is_some()))
+                    .map_err(|err| return err.to_string())?
+                    .as_bytes(),
+            )
+            .into(),
+        Format::Yaml => output.write(yaml(&document(&meta, input.meta.is_some()), 0).as_bytes()).into(),
+        Format::Env => output.write(shell("SAUCE", &document(&meta, input.meta.is_some())).as_bytes()).into(),
+    };
+}
+
+/// Print every field, highlighting problems.
+#[inline]
+fn print_text(output: &mut Output, meta: &Meta, has_meta: bool) -> ExitCode {
+    output.write(format!("\x1B[{}mMetadata\x1B[0m:\n", if has_meta { "4" } else { "4;33" }).as_bytes())?;
+
+    print_title(output, meta)?;
+    print_author(output, meta)?;
+    print_group(output, meta)?;
+    print_date(output, meta)?;
+    print_size(output, meta)?;
+    print_type(output, meta)?;
+    print_width(output, meta)?;
+    print_height(output, meta)?;
+    print_flags(output, meta)?;
+    print_font(output, meta)?;
+    print_notes(output, meta)?;
 
     return ExitCode::OK;
 }
 
+/// One of the `check_*` functions.
+type Check = fn(Option<&Meta>) -> Result<(), CheckError>;
+
+/// Gather the raw fields, their effective values, and any problems with them.
+///
+/// The schema is stable, and never uses nulls (so that it can be written as
+/// TOML): unset values are empty strings instead.
+///
+fn document(meta: &Meta, has_meta: bool) -> Value {
+    let flags = effective_flags(meta);
+    let checks: [(&str, Check); 8] = [
+        ("title", meta::check_title),
+        ("author", meta::check_author),
+        ("group", meta::check_group),
+        ("date", meta::check_date),
+        ("type", meta::check_type),
+        ("flags", meta::check_flags),
+        ("font", |meta| return meta::check_font(meta).and_then(|()| return meta::check_font_support(meta))),
+        ("notes", meta::check_notes),
+    ];
+    let errors = checks
+        .iter()
+        .filter_map(|(key, check)| {
+            return check(Some(meta)).err().map(|err| return (String::from(*key), Value::String(err.to_string())));
+        })
+        .collect::<Map<String, Value>>();
+
+    return json!({
+        "meta": has_meta,
+        "raw": {
+            "title": meta.title,
+            "author": meta.author,
+            "group": meta.group,
+            "date": meta.date,
+            "size": meta.size,
+            "type": meta.r#type.to_string(),
+            "width": meta.width,
+            "height": meta.height,
+            "tinfo3": meta.tinfo3,
+            "tinfo4": meta.tinfo4,
+            "flags": meta.flags,
+            "font": meta.font,
+            "notes": meta.notes,
+        },
+        "effective": {
+            "date": meta.date().map_or(String::new(), |date| return date.format("%Y-%m-%d").to_string()),
+            "type": meta.r#type().to_string(),
+            "width": meta.width(),
+            "height": meta.height(),
+            "ice_colours": flags.ice_colours,
+            "letter_spacing": flags.letter_spacing.to_string(),
+            "aspect_ratio": flags.aspect_ratio.to_string(),
+            "flags": u8::from(flags),
+            "font": meta.font().unwrap_or(&Meta::default().font),
+            "font_size": [meta.font_width(), meta.font_height()],
+            "pixel_ratio": [meta.aspect_ratio().0, meta.aspect_ratio().1],
+        },
+        "valid": errors.is_empty(),
+        "errors": errors,
+    });
+}
+
+/// Write a document as YAML, in block style.
+///
+/// Strings are always double quoted, which is where YAML and JSON agree.
+///
+fn yaml(value: &Value, indent: usize) -> String {
+    let padding = " ".repeat(indent);
+    return match value {
+        Value::Object(object) if !object.is_empty() => object
+            .iter()
+            .map(|(key, value)| {
+                return match value {
+                    Value::Object(inner) if !inner.is_empty() => {
+                        format!("{padding}{key}:\n{}", yaml(value, indent + 2))
+                    },
+                    Value::Array(inner) if !inner.is_empty() => format!("{padding}{key}:\n{}", yaml(value, indent + 2)),
+                    _ => format!("{padding}{key}: {}", yaml(value, 0)),
+                };
+            })
+            .collect(),
+        Value::Array(array) if !array.is_empty() => {
+            array.iter().map(|value| return format!("{padding}- {}", yaml(value, 0))).collect()
+        },
+        Value::Object(_) => String::from("{}\n"),
+        Value::Array(_) => String::from("[]\n"),
+        _ => format!("{value}\n"),
+    };
+}
+
+/// Write a document as shell variable assignments, flattening nested keys.
+///
+/// Arrays are flattened by index, and strings are single quoted.
+///
+fn shell(prefix: &str, value: &Value) -> String {
+    return match value {
+        Value::Object(object) => {
+            object.iter().map(|(key, value)| return shell(&format!("{prefix}_{}", key.to_uppercase()), value)).collect()
+        },
+        Value::Array(array) => {
+            array.iter().enumerate().map(|(i, value)| return shell(&format!("{prefix}_{i}"), value)).collect()
+        },
+        Value::String(string) => format!("{prefix}='{}'\n", string.replace('\'', "'\\''")),
+        _ => format!("{prefix}={value}\n"),
+    };
+}
+
+/// The flags, with legacy values replaced by what they effectively mean.
+#[inline]
+fn effective_flags(meta: &Meta) -> SauceFlags {
+    let flags = meta.flags();
+    return SauceFlags {
+        aspect_ratio: if flags.aspect_ratio == AspectRatio::Legacy { AspectRatio::Stretch } else { flags.aspect_ratio },
+        letter_spacing: if flags.letter_spacing == LetterSpacing::Legacy {
+            LetterSpacing::NinePixel
+        } else {
+            flags.letter_spacing
+        },
+        ..flags
+    };
+}
+
 /// Show the file's title if present.
 #[inline]
 fn print_title(output: &mut Output, meta: &Meta) -> ExitCode {
@@ -124,15 +304,12 @@ fn print_group(output: &mut Output, meta: &Meta) -> ExitCode {
 /// Show the file's date if present.
 #[inline]
 fn print_date(output: &mut Output, meta: &Meta) -> ExitCode {
-    if meta.date().is_some() {
+    if !meta.date.is_empty() {
         output.write(
-            format!(
-                "* \x1B[1mDate\x1B[0m: \x1B[{}m{}/{}/{}\x1B[0m\n",
-                if meta::check_date(Some(meta)).is_err() { "1;3;31" } else { "3;32" },
-                &meta.date[0..4],
-                &meta.date[4..6],
-                &meta.date[6..8],
-            )
+            match meta.date() {
+                Some(date) => format!("* \x1B[1mDate\x1B[0m: \x1B[3;32m{}\x1B[0m\n", date.format("%Y/%m/%d")),
+                None => format!("* \x1B[1mDate\x1B[0m: \x1B[1;3;31m{:?}\x1B[0m\n", meta.date),
+            }
             .as_bytes(),
         )?;
     }
@@ -155,9 +332,9 @@ fn print_type(output: &mut Output, meta: &Meta) -> ExitCode {
         format!(
             "* \x1B[1mType\x1B[0m: {}\x1B[0m\n",
             match meta.r#type {
-                (0, _) => format!("\x1B[1;3;33mNone ({})", meta::type_name(Meta::default().r#type)),
-                (1, 0 | 1) => format!("\x1B[3;32m{}", meta::type_name(meta.r#type)),
-                _ => format!("\x1B[1;3;31m{}", meta::type_name(meta.r#type)),
+                FileType::None => format!("\x1B[1;3;33mNone ({})", Meta::default().r#type),
+                FileType::Ascii | FileType::Ansi => format!("\x1B[3;32m{}", meta.r#type),
+                _ => format!("\x1B[1;3;31m{}", meta.r#type),
             },
         )
         .as_bytes(),
@@ -199,19 +376,14 @@ fn print_height(output: &mut Output, meta: &Meta) -> ExitCode {
 /// Show the file's flags.
 #[inline]
 fn print_flags(output: &mut Output, meta: &Meta) -> ExitCode {
+    let flags = meta.flags();
     output.write(
         format!(
             "* \x1B[1mFlags\x1B[0m: {}\x1B[0m\n",
-            if meta.flags().0 == 0b11 || meta.flags().1 == 0b11 || meta.flags().2 == 0b0 {
+            if flags.aspect_ratio == AspectRatio::Invalid || flags.letter_spacing == LetterSpacing::Invalid {
                 format!("\x1B[3;31m{:02X}h", meta.flags)
-            } else if meta.flags().0 == 0b00 || meta.flags().1 == 0b00 {
-                format!(
-                    "\x1B[3;33m{:02X}h ({:02X}h)",
-                    meta.flags,
-                    meta.flags
-                        | (if meta.flags().0 == 0b00 { 0x08 } else { 0x00 })
-                        | (if meta.flags().1 == 0b00 { 0x04 } else { 0x00 }),
-                )
+            } else if flags.aspect_ratio == AspectRatio::Legacy || flags.letter_spacing == LetterSpacing::Legacy {
+                format!("\x1B[3;33m{:02X}h ({:02X}h)", meta.flags, u8::from(effective_flags(meta)))
             } else {
                 format!("\x1B[3;32m{:02X}h", meta.flags)
             },
@@ -229,10 +401,13 @@ fn print_font(output: &mut Output, meta: &Meta) -> ExitCode {
         format!(
             "* \x1B[1mFont\x1B[0m: {}\x1B[0m\n",
             if let Some(font) = meta.font() {
-                if ["IBM VGA", "IBM VGA 437"].contains(&font.as_str()) {
-                    format!("\x1B[3;32m{font:?}")
-                } else {
-                    format!("\x1B[1;3;31m{font:?}")
+                match meta::check_font(Some(meta))
+                    .and_then(|()| return meta::check_font_support(Some(meta)))
+                    .map_err(|err| return err.severity())
+                {
+                    Ok(()) => format!("\x1B[3;32m{font:?}"),
+                    Err(Severity::Warning) => format!("\x1B[1;3;33m{font:?}"),
+                    Err(_) => format!("\x1B[1;3;31m{font:?}"),
                 }
             } else {
                 format!("\x1B[1;3;33m<N/A> ({})", Meta::default().font)
@@ -293,7 +468,7 @@ mod tests {
     #[test]
     fn simple() -> Result<(), String> {
         return test::ok(
-            run,
+            |i, o| return run(i, o, Format::Text),
             "res/test/simple.ans",
             indoc! {"
                 \x1B[4;33mMetadata\x1B[0m:
@@ -310,7 +485,7 @@ mod tests {
     #[test]
     fn meta() -> Result<(), String> {
         return test::ok(
-            run,
+            |i, o| return run(i, o, Format::Text),
             "res/test/meta.ans",
             indoc! {"
                 \x1B[4mMetadata\x1B[0m:
@@ -331,7 +506,7 @@ mod tests {
     #[test]
     fn notes() -> Result<(), String> {
         return test::ok(
-            run,
+            |i, o| return run(i, o, Format::Text),
             "res/test/comments.ans",
             indoc! {"
                 \x1B[4mMetadata\x1B[0m:
@@ -353,4 +528,210 @@ mod tests {
             "},
         );
     }
+
+    #[test]
+    fn shell_quoting() {
+        assert_eq!(shell("X", &json!({ "a": "it's", "b": [] })), "X_A='it'\\''s'\n");
+    }
+
+    #[test]
+    fn unknown_format() {
+        assert_eq!(
+            exec(&[String::from("cp437-read-meta"), String::from("--format"), String::from("xml"), String::from("a")]),
+            ExitCode::USAGE(String::from("Unknown format: xml")),
+        );
+    }
+
+    #[test]
+    fn format_last() {
+        assert_eq!(
+            exec(&[String::from("cp437-read-meta"), String::from("a"), String::from("--format"), String::from("xml")]),
+            ExitCode::USAGE(String::from("Unknown format: xml")),
+        );
+    }
+
+    #[test]
+    fn no_format() {
+        assert_eq!(
+            exec(&[String::from("cp437-read-meta"), String::from("--format")]),
+            ExitCode::USAGE(String::from("Missing format")),
+        );
+    }
+
+    #[test]
+    fn json() -> Result<(), String> {
+        return test::ok(
+            |i, o| return run(i, o, Format::Json),
+            "res/test/meta.ans",
+            indoc! {"
+                {
+                  \"effective\": {
+                    \"aspect_ratio\": \"stretch-ar\",
+                    \"date\": \"1970-01-01\",
+                    \"flags\": 13,
+                    \"font\": \"IBM VGA\",
+                    \"font_size\": [
+                      9,
+                      16
+                    ],
+                    \"height\": 8,
+                    \"ice_colours\": true,
+                    \"letter_spacing\": \"9px\",
+                    \"pixel_ratio\": [
+                      20,
+                      27
+                    ],
+                    \"type\": \"Character/ANSi\",
+                    \"width\": 32
+                  },
+                  \"errors\": {},
+                  \"meta\": true,
+                  \"raw\": {
+                    \"author\": \"AUTHOR\",
+                    \"date\": \"19700101\",
+                    \"flags\": 1,
+                    \"font\": \"IBM VGA\",
+                    \"group\": \"GROUP\",
+                    \"height\": 8,
+                    \"notes\": [],
+                    \"size\": 416,
+                    \"tinfo3\": 0,
+                    \"tinfo4\": 0,
+                    \"title\": \"TITLE\",
+                    \"type\": \"Character/ANSi\",
+                    \"width\": 32
+                  },
+                  \"valid\": true
+                }
+            "},
+        );
+    }
+
+    #[test]
+    fn toml() -> Result<(), String> {
+        return test::ok(
+            |i, o| return run(i, o, Format::Toml),
+            "res/test/simple.ans",
+            indoc! {"
+                meta = false
+                valid = true
+
+                [effective]
+                aspect_ratio = \"stretch-ar\"
+                date = \"\"
+                flags = 13
+                font = \"IBM VGA\"
+                font_size = [9, 16]
+                height = 25
+                ice_colours = true
+                letter_spacing = \"9px\"
+                pixel_ratio = [20, 27]
+                type = \"Character/ANSi\"
+                width = 80
+
+                [errors]
+
+                [raw]
+                author = \"\"
+                date = \"\"
+                flags = 1
+                font = \"\"
+                group = \"\"
+                height = 0
+                notes = []
+                size = 416
+                tinfo3 = 0
+                tinfo4 = 0
+                title = \"\"
+                type = \"None\"
+                width = 0
+            "},
+        );
+    }
+
+    #[test]
+    fn yaml() -> Result<(), String> {
+        return test::ok(
+            |i, o| return run(i, o, Format::Yaml),
+            "res/test/bad_date.ans",
+            indoc! {"
+                effective:
+                  aspect_ratio: \"stretch-ar\"
+                  date: \"\"
+                  flags: 13
+                  font: \"IBM VGA\"
+                  font_size:
+                    - 9
+                    - 16
+                  height: 8
+                  ice_colours: true
+                  letter_spacing: \"9px\"
+                  pixel_ratio:
+                    - 20
+                    - 27
+                  type: \"Character/ANSi\"
+                  width: 32
+                errors:
+                  date: \"Date format is wrong (input contains invalid characters)\"
+                meta: true
+                raw:
+                  author: \"AUTHOR\"
+                  date: \"XXXXXXXX\"
+                  flags: 1
+                  font: \"IBM VGA\"
+                  group: \"GROUP\"
+                  height: 8
+                  notes: []
+                  size: 0
+                  tinfo3: 0
+                  tinfo4: 0
+                  title: \"TITLE\"
+                  type: \"Character/ANSi\"
+                  width: 32
+                valid: false
+            "},
+        );
+    }
+
+    #[test]
+    fn env() -> Result<(), String> {
+        return test::ok(
+            |i, o| return run(i, o, Format::Env),
+            "res/test/comments.ans",
+            indoc! {"
+                SAUCE_EFFECTIVE_ASPECT_RATIO='stretch-ar'
+                SAUCE_EFFECTIVE_DATE='1970-01-01'
+                SAUCE_EFFECTIVE_FLAGS=13
+                SAUCE_EFFECTIVE_FONT='IBM VGA'
+                SAUCE_EFFECTIVE_FONT_SIZE_0=9
+                SAUCE_EFFECTIVE_FONT_SIZE_1=16
+                SAUCE_EFFECTIVE_HEIGHT=8
+                SAUCE_EFFECTIVE_ICE_COLOURS=true
+                SAUCE_EFFECTIVE_LETTER_SPACING='9px'
+                SAUCE_EFFECTIVE_PIXEL_RATIO_0=20
+                SAUCE_EFFECTIVE_PIXEL_RATIO_1=27
+                SAUCE_EFFECTIVE_TYPE='Character/ANSi'
+                SAUCE_EFFECTIVE_WIDTH=32
+                SAUCE_META=true
+                SAUCE_RAW_AUTHOR='AUTHOR'
+                SAUCE_RAW_DATE='19700101'
+                SAUCE_RAW_FLAGS=1
+                SAUCE_RAW_FONT='IBM VGA'
+                SAUCE_RAW_GROUP='GROUP'
+                SAUCE_RAW_HEIGHT=8
+                SAUCE_RAW_NOTES_0='Lorem'
+                SAUCE_RAW_NOTES_1='ipsum'
+                SAUCE_RAW_NOTES_2='dolor'
+                SAUCE_RAW_NOTES_3='sit'
+                SAUCE_RAW_NOTES_4='amet'
+                SAUCE_RAW_SIZE=416
+                SAUCE_RAW_TINFO3=0
+                SAUCE_RAW_TINFO4=0
+                SAUCE_RAW_TITLE='TITLE'
+                SAUCE_RAW_TYPE='Character/ANSi'
+                SAUCE_RAW_WIDTH=32
+                SAUCE_VALID=true
+            "},
+        );
+    }
 }
@@ -1,15 +1,20 @@
-//! Set one field of a file's metadata.
+//! Set some fields of a file's metadata.
 
+use chrono::NaiveDate;
 use std::{
     env::args,
-    io::{stdout, IsTerminal as _},
+    fs::{read_to_string, OpenOptions},
+    io::{stdout, IsTerminal as _, Read as _, Seek as _},
+    path::Path,
 };
 
 use cp437_tools::{
+    fonts::FontName,
     internal::{escape, process, ExitCode, Input, Output},
+    lossy::Policy,
     prelude::{
-        meta::{self, Meta},
-        to_cp437,
+        meta::{self, FileType, Meta, SauceFlags},
+        CodePage,
     },
 };
 
@@ -26,97 +31,323 @@ pub fn main() -> ExitCode {
 #[allow(missing_docs, reason = "Just an entry point")]
 #[allow(clippy::missing_docs_in_private_items, reason = "Just an entry point")]
 pub fn exec(args: &[String]) -> ExitCode {
-    let exit_code = if args.len() < 2 {
-        ExitCode::USAGE(String::from("Missing input file"))
-    } else if args.len() < 3 {
-        ExitCode::USAGE(String::from("Missing key"))
-    } else if args.len() < 4 {
-        ExitCode::USAGE(String::from("Missing value"))
-    } else if args.len() > 5 {
-        ExitCode::USAGE(String::from("Too many arguments"))
-    } else if stdout().is_terminal() {
-        ExitCode::USAGE(String::from("Refusing to write to terminal"))
-    } else {
-        process(&args[1], |i, o| return run(i, o, &args[2], &args[3]))
+    let exit_code = match Args::parse(args) {
+        Err(exit_code) => exit_code,
+        Ok(args) if args.in_place => run_in_place(&args.file, args.policy, args.from.as_deref(), &args.assignments),
+        Ok(_) if stdout().is_terminal() => ExitCode::USAGE(String::from("Refusing to write to terminal")),
+        Ok(args) => process(&args.file, |i, o| return run(i, o, args.policy, args.from.as_deref(), &args.assignments)),
     };
 
     exit_code.print();
     return exit_code;
 }
 
+/// The parsed command line.
+struct Args {
+    /// The file to modify.
+    file: String,
+    /// A metadata document to apply, if any.
+    from: Option<String>,
+    /// Whether to modify the file instead of piping it to stdout.
+    in_place: bool,
+    /// What to do with characters that don't exist in CP437.
+    policy: Policy,
+    /// The `(key, value)` pairs to set.
+    assignments: Vec<(String, String)>,
+}
+
+impl Args {
+    /// Split the arguments into options, the file, and the assignments.
+    ///
+    /// Besides `key=value` pairs, a single `key value` pair is also accepted.
+    ///
+    fn parse(args: &[String]) -> Result<Args, ExitCode> {
+        let mut from = None;
+        let mut in_place = false;
+        let mut policy = Policy::Strict;
+        let mut positional = vec![];
+        let mut iter = args.iter().skip(1);
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "-i" | "--in-place" => in_place = true,
+                "--from" => {
+                    from = Some(
+                        iter.next()
+                            .ok_or_else(|| return ExitCode::USAGE(String::from("Missing metadata document")))?
+                            .clone(),
+                    );
+                },
+                "--encoding" => {
+                    let name =
+                        iter.next().ok_or_else(|| return ExitCode::USAGE(String::from("Missing encoding policy")))?;
+                    policy = Policy::get(name)
+                        .ok_or_else(|| return ExitCode::USAGE(format!("Unknown encoding policy: {name}")))?;
+                },
+                _ if arg.starts_with("--") => return Err(ExitCode::USAGE(format!("Unknown option: {arg}"))),
+                _ => positional.push(arg.clone()),
+            }
+        }
+
+        let Some((file, pairs)) = positional.split_first() else {
+            return Err(ExitCode::USAGE(String::from("Missing input file")));
+        };
+        let assignments = match pairs {
+            [key, value] if !key.contains('=') => vec![(key.clone(), value.clone())],
+            _ => pairs
+                .iter()
+                .map(|pair| {
+                    let (key, value) =
+                        pair.split_once('=').ok_or_else(|| return ExitCode::USAGE(String::from("Missing value")))?;
+                    return Ok((key.to_string(), value.to_string()));
+                })
+                .collect::<Result<Vec<_>, ExitCode>>()?,
+        };
+
+        if assignments.is_empty() && from.is_none() {
+            return Err(ExitCode::USAGE(String::from("Missing key")));
+        } else if in_place && file.as_str() == "-" {
+            return Err(ExitCode::USAGE(String::from("Can't modify stdin in place")));
+        }
+
+        return Ok(Args { file: file.clone(), from, in_place, policy, assignments });
+    }
+}
+
 #[allow(missing_docs, reason = "Just an entry point")]
 #[allow(clippy::missing_docs_in_private_items, reason = "Just an entry point")]
-pub fn run(input: &mut Input, output: &mut Output, key: &str, value: &str) -> ExitCode {
+pub fn run(
+    input: &mut Input,
+    output: &mut Output,
+    policy: Policy,
+    from: Option<&str>,
+    assignments: &[(String, String)],
+) -> ExitCode {
     let mut meta = input.meta.clone().unwrap_or(Meta { size: input.size, ..Default::default() });
-
-    set_meta(&mut meta, key, &escape(value)?)?;
-    meta::check(Some(&meta))?;
+    let size = input.size as usize;
+    edit(
+        &mut meta,
+        || {
+            let mut data = Vec::with_capacity(size);
+            input.read_by_chunks(|chunk| {
+                data.extend_from_slice(chunk);
+                return Ok(());
+            })?;
+            return Ok(data);
+        },
+        policy,
+        from,
+        assignments,
+    )?;
 
     input.read_by_chunks(|chunk| {
         return output.write(chunk);
     })?;
 
-    return write_meta(output, &meta);
+    return meta::write(output, &meta).map(|_| return ExitCode::OK)?;
+}
+
+/// Modify a file's metadata without copying its contents.
+#[must_use]
+pub fn run_in_place(path: &str, policy: Policy, from: Option<&str>, assignments: &[(String, String)]) -> ExitCode {
+    let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+    let mut meta = match meta::read(&mut file)? {
+        Some(meta) => meta,
+        None => Meta { size: u32::try_from(file.metadata()?.len())?, ..Default::default() },
+    };
+    let size = meta.size;
+    edit(
+        &mut meta,
+        || {
+            let mut data = vec![];
+            file.rewind()?;
+            file.by_ref().take(u64::from(size)).read_to_end(&mut data)?;
+            return Ok(data);
+        },
+        policy,
+        from,
+        assignments,
+    )?;
+
+    return meta.replace_in(&mut file).map(|_| return ExitCode::OK)?;
+}
+
+/// Apply a metadata document (if any), then every assignment, and check the
+/// result.
+///
+/// The contents are only needed to resolve `auto` values, so they're only
+/// read when there's any.
+///
+#[inline]
+fn edit<F: FnOnce() -> Result<Vec<u8>, ExitCode>>(
+    meta: &mut Meta,
+    data: F,
+    policy: Policy,
+    from: Option<&str>,
+    assignments: &[(String, String)],
+) -> ExitCode {
+    let mut pairs = match from {
+        Some(path) => read_document(path)?,
+        None => vec![],
+    };
+    for (key, value) in assignments {
+        pairs.push((key.clone(), escape(value)?));
+    }
+
+    let auto = pairs.iter().any(|(key, value)| {
+        return matches!(key.as_str(), "type" | "width" | "height") && value.trim() == "auto";
+    });
+    let estimate = if auto { meta::estimate(&data()?) } else { Meta::default() };
+    for (key, value) in pairs {
+        set_meta(meta, &estimate, policy, &key, &value)?;
+    }
+
+    return meta::check(Some(meta)).into();
+}
+
+/// Read a JSON or TOML document into a list of `(key, value)` pairs.
+///
+/// Lists (i.e., notes) are joined into lines, and `size` is skipped since it's
+/// derived from the file itself. The output of `cp437-read-meta` is accepted
+/// too, in which case only its raw fields are used.
+///
+fn read_document(path: &str) -> Result<Vec<(String, String)>, ExitCode> {
+    let format = Path::new(path).extension().and_then(|extension| return extension.to_str());
+    if !matches!(format, Some("json" | "toml")) {
+        return Err(ExitCode::USAGE(format!("Unknown metadata document format: {path}")));
+    }
+
+    let text = read_to_string(path)?;
+    let pairs = match format {
+        Some("json") => match serde_json::from_str(&text).map_err(|err| return invalid_document(&err.to_string()))? {
+            serde_json::Value::Object(mut object) => match object.remove("raw") {
+                Some(serde_json::Value::Object(raw)) => raw,
+                _ => object,
+            }
+            .into_iter()
+            .map(|(key, value)| {
+                let value = match value {
+                    serde_json::Value::Null => Some(String::new()),
+                    serde_json::Value::Number(number) => Some(number.to_string()),
+                    serde_json::Value::String(string) => Some(string),
+                    serde_json::Value::Array(list) => lines(list.iter().map(serde_json::Value::as_str)),
+                    serde_json::Value::Bool(_) | serde_json::Value::Object(_) => None,
+                };
+                return Ok((
+                    key.clone(),
+                    value.ok_or_else(|| return invalid_document(&format!("bad value for {key}")))?,
+                ));
+            })
+            .collect::<Result<Vec<_>, ExitCode>>()?,
+            _ => return Err(invalid_document("expected an object")),
+        },
+        _ => {
+            let mut table = text.parse::<toml::Table>().map_err(|err| return invalid_document(err.message()))?;
+            match table.remove("raw") {
+                Some(toml::Value::Table(raw)) => raw,
+                _ => table,
+            }
+        }
+        .into_iter()
+        .map(|(key, value)| {
+            let value = match value {
+                toml::Value::Integer(number) => Some(number.to_string()),
+                toml::Value::String(string) => Some(string),
+                toml::Value::Datetime(datetime) => datetime.date.map(|date| return date.to_string()),
+                toml::Value::Array(list) => lines(list.iter().map(toml::Value::as_str)),
+                toml::Value::Float(_) | toml::Value::Boolean(_) | toml::Value::Table(_) => None,
+            };
+            return Ok((key.clone(), value.ok_or_else(|| return invalid_document(&format!("bad value for {key}")))?));
+        })
+        .collect::<Result<Vec<_>, ExitCode>>()?,
+    };
+
+    return Ok(pairs.into_iter().filter(|(key, _)| return key != "size").collect());
+}
+
+/// Join a list of strings into lines, if they're all strings.
+#[inline]
+fn lines<'a, I: Iterator<Item = Option<&'a str>>>(list: I) -> Option<String> {
+    return list.collect::<Option<Vec<_>>>().map(|lines| return lines.join("\n"));
+}
+
+/// Wrap a document parsing error.
+#[inline]
+fn invalid_document(reason: &str) -> ExitCode {
+    return ExitCode::USAGE(format!("Invalid metadata document ({reason})"));
 }
 
 /// Modify a single meta field.
+///
+/// The type, width, and height can also be set to `auto`, which takes them
+/// from an estimate based on the contents. Text fields are made to fit in
+/// CP437 as the policy says.
+///
 #[inline]
-fn set_meta(meta: &mut Meta, key: &str, value: &str) -> ExitCode {
+fn set_meta(meta: &mut Meta, estimate: &Meta, policy: Policy, key: &str, value: &str) -> ExitCode {
     match key {
         "title" => {
-            meta.title = value.trim().to_string();
+            meta.title = encode(value.trim(), "Title", policy);
         },
         "author" => {
-            meta.author = value.trim().to_string();
+            meta.author = encode(value.trim(), "Author", policy);
         },
         "group" => {
-            meta.group = value.trim().to_string();
+            meta.group = encode(value.trim(), "Group", policy);
         },
         "date" => {
-            meta.date = value.trim().to_string();
+            meta.date = match NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d") {
+                Ok(date) => date.format("%Y%m%d").to_string(),
+                Err(_) => value.trim().to_string(),
+            };
         },
         "size" => {
             return ExitCode::USAGE(String::from("Size can't be changed"));
         },
-        "type" => match value.to_lowercase().as_str() {
-            "none" => {
-                meta.r#type = (0, 0);
-            },
-            "character/ascii" => {
-                meta.r#type = (1, 0);
-            },
-            "character/ansi" => {
-                meta.r#type = (1, 1);
-            },
-            _ => {
-                return ExitCode::USAGE(format!("Type is unsupported ({value})"));
-            },
+        "type" if value.trim() == "auto" => {
+            meta.r#type = estimate.r#type;
+        },
+        "type" => {
+            meta.r#type = value.trim().parse::<FileType>()?;
+        },
+        "width" if value.trim() == "auto" => {
+            meta.width = estimate.width;
         },
         "width" => {
             meta.width =
                 value.parse::<u16>().map_err(|err| return ExitCode::USAGE(format!("Invalid width ({err})")))?;
         },
+        "height" if value.trim() == "auto" => {
+            meta.height = estimate.height;
+        },
         "height" => {
             meta.height =
                 value.parse::<u16>().map_err(|err| return ExitCode::USAGE(format!("Invalid height ({err})")))?;
         },
+        "tinfo3" => {
+            meta.tinfo3 =
+                value.parse::<u16>().map_err(|err| return ExitCode::USAGE(format!("Invalid tinfo3 ({err})")))?;
+        },
+        "tinfo4" => {
+            meta.tinfo4 =
+                value.parse::<u16>().map_err(|err| return ExitCode::USAGE(format!("Invalid tinfo4 ({err})")))?;
+        },
         "flags" => {
             meta.flags = (if let Some(hex) = value.strip_prefix("0x") {
                 u8::from_str_radix(hex, 16)
             } else if let Some(bin) = value.strip_prefix("0b") {
                 u8::from_str_radix(bin, 2)
-            } else {
+            } else if value.bytes().all(|byte| return byte.is_ascii_digit()) {
                 value.parse::<u8>()
+            } else {
+                Ok(u8::from(value.parse::<SauceFlags>()?))
             })
             .map_err(|err| return ExitCode::USAGE(format!("Invalid flags ({err})")))?;
         },
-        "font" => match value {
-            "" | "IBM VGA" | "IBM VGA 437" => {
-                meta.font = value.trim().to_string();
-            },
-            _ => {
-                return ExitCode::USAGE(format!("Font is unsupported ({value})"));
-            },
+        "font" => {
+            if !value.trim().is_empty() && FontName::parse(value.trim()).is_none() {
+                return ExitCode::USAGE(format!("Font is unknown ({value})"));
+            }
+            meta.font = value.trim().to_string();
         },
         "notes" => {
             if value.is_empty() {
@@ -124,7 +355,7 @@ fn set_meta(meta: &mut Meta, key: &str, value: &str) -> ExitCode {
             } else {
                 meta.notes = value
                     .split('\n')
-                    .map(|note| return note.trim().to_string())
+                    .map(|note| return encode(note.trim(), "Notes", policy))
                     .filter(|note| return !note.is_empty())
                     .collect();
             }
@@ -137,32 +368,21 @@ fn set_meta(meta: &mut Meta, key: &str, value: &str) -> ExitCode {
     return ExitCode::OK;
 }
 
-/// Write the metadata part of the file.
-#[inline]
-fn write_meta(output: &mut Output, meta: &Meta) -> ExitCode {
-    output.write(b"\x1A")?;
-    if !meta.notes().is_empty() {
-        output.write(b"COMNT")?;
-        for note in meta.notes() {
-            output.write(&to_cp437(&format!("{note:<64}"))?)?;
-        }
-    }
-
-    output.write(b"SAUCE00")?;
-    output.write(&to_cp437(&format!("{:<35}", meta.title))?)?;
-    output.write(&to_cp437(&format!("{:<20}", meta.author))?)?;
-    output.write(&to_cp437(&format!("{:<20}", meta.group))?)?;
-    output.write(&to_cp437(&format!("{:<8}", meta.date))?)?;
-    output.write(&meta.size.to_le_bytes())?;
-    output.write(&[meta.r#type.0, meta.r#type.1])?;
-    output.write(&meta.width.to_le_bytes())?;
-    output.write(&meta.height.to_le_bytes())?;
-    output.write(&0_u32.to_le_bytes())?;
-    output.write(&[u8::try_from(meta.notes().len())?])?;
-    output.write(&[meta.flags])?;
-    output.write(&to_cp437(&format!("{:\0<22}", meta.font))?)?;
+/// Replace the characters that don't exist in CP437 as the policy says,
+/// warning about each one of them.
+///
+/// Strings that can't be encoded are kept as they are, so that checking the
+/// metadata reports them.
+///
+fn encode(value: &str, field: &str, policy: Policy) -> String {
+    let Ok(encoded) = CodePage::Cp437.encode_lossy(value, policy) else {
+        return value.to_owned();
+    };
+    for substitution in &encoded.substitutions {
+        eprintln!("\x1B[33mWARN: {field}: {substitution}\x1B[0m");
+    }
 
-    return ExitCode::OK;
+    return CodePage::Cp437.decode(&encoded.bytes);
 }
 
 #[path = "."]
@@ -197,17 +417,34 @@ mod tests {
     }
 
     #[test]
-    fn too_many_args() {
+    fn no_assignment() {
+        assert_eq!(
+            exec(&[String::from("cp437-set-meta"), String::from("a"), String::from("b=c"), String::from("d")]),
+            ExitCode::USAGE(String::from("Missing value")),
+        );
+    }
+
+    #[test]
+    fn no_document() {
         assert_eq!(
-            exec(&[
-                String::from("cp437-set-meta"),
-                String::from("a"),
-                String::from("b"),
-                String::from("c"),
-                String::from("d"),
-                String::from("e"),
-            ]),
-            ExitCode::USAGE(String::from("Too many arguments")),
+            exec(&[String::from("cp437-set-meta"), String::from("a"), String::from("--from")]),
+            ExitCode::USAGE(String::from("Missing metadata document")),
+        );
+    }
+
+    #[test]
+    fn unknown_option() {
+        assert_eq!(
+            exec(&[String::from("cp437-set-meta"), String::from("--foo"), String::from("a"), String::from("b=c")]),
+            ExitCode::USAGE(String::from("Unknown option: --foo")),
+        );
+    }
+
+    #[test]
+    fn in_place_stdin() {
+        assert_eq!(
+            exec(&[String::from("cp437-set-meta"), String::from("-i"), String::from("-"), String::from("b=c")]),
+            ExitCode::USAGE(String::from("Can't modify stdin in place")),
         );
     }
 
@@ -220,10 +457,203 @@ mod tests {
         );
     }
 
+    #[test]
+    fn multiple() -> Result<(), String> {
+        return test::file_meta(
+            |i, o| {
+                return run(
+                    i,
+                    o,
+                    Policy::Strict,
+                    None,
+                    &[(String::from("title"), String::from("TITLE")), (String::from("author"), String::from("AUTHOR"))],
+                );
+            },
+            "res/test/simple.ans",
+            Some(Meta {
+                title: String::from("TITLE"),
+                author: String::from("AUTHOR"),
+                size: 416,
+                ..Default::default()
+            }),
+        );
+    }
+
+    mod from {
+        use super::*;
+
+        #[test]
+        fn json() -> Result<(), String> {
+            return test::file_meta(
+                |i, o| return run(i, o, Policy::Strict, Some("res/test/meta.json"), &[]),
+                "res/test/simple.ans",
+                Some(document()),
+            );
+        }
+
+        #[test]
+        fn toml() -> Result<(), String> {
+            return test::file_meta(
+                |i, o| return run(i, o, Policy::Strict, Some("res/test/meta.toml"), &[]),
+                "res/test/simple.ans",
+                Some(document()),
+            );
+        }
+
+        #[test]
+        fn overridden() -> Result<(), String> {
+            return test::file_meta(
+                |i, o| {
+                    return run(
+                        i,
+                        o,
+                        Policy::Strict,
+                        Some("res/test/meta.json"),
+                        &[(String::from("title"), String::from("FOO"))],
+                    );
+                },
+                "res/test/simple.ans",
+                Some(Meta { title: String::from("FOO"), ..document() }),
+            );
+        }
+
+        #[test]
+        fn serde() -> Result<(), String> {
+            return test::file_meta(
+                |i, o| return run(i, o, Policy::Strict, Some("res/test/serde_meta.json"), &[]),
+                "res/test/simple.ans",
+                Some(document()),
+            );
+        }
+
+        #[test]
+        fn read_meta_json() -> Result<(), String> {
+            return test::file_meta(
+                |i, o| return run(i, o, Policy::Strict, Some("res/test/read_meta.json"), &[]),
+                "res/test/simple.ans",
+                meta::get("res/test/comments.ans").map_err(|err| return err.to_string())?,
+            );
+        }
+
+        #[test]
+        fn read_meta_toml() -> Result<(), String> {
+            return test::file_meta(
+                |i, o| return run(i, o, Policy::Strict, Some("res/test/read_meta.toml"), &[]),
+                "res/test/simple.ans",
+                meta::get("res/test/comments.ans").map_err(|err| return err.to_string())?,
+            );
+        }
+
+        #[test]
+        fn unknown_format() -> Result<(), String> {
+            return test::err(
+                |i, o| return run(i, o, Policy::Strict, Some("res/test/simple.ans"), &[]),
+                "res/test/simple.ans",
+                "Unknown metadata document format: res/test/simple.ans",
+            );
+        }
+
+        #[test]
+        fn invalid() -> Result<(), String> {
+            return test::err(
+                |i, o| return run(i, o, Policy::Strict, Some("res/test/bad_meta.json"), &[]),
+                "res/test/simple.ans",
+                "Invalid metadata document (expected an object)",
+            );
+        }
+
+        fn document() -> Meta {
+            return Meta {
+                title: String::from("TITLE"),
+                author: String::from("AUTHOR"),
+                group: String::from("GROUP"),
+                date: String::from("19700101"),
+                size: 416,
+                r#type: FileType::Ansi,
+                width: 80,
+                height: 25,
+                flags: 0x0D,
+                font: String::from("IBM VGA"),
+                notes: vec![String::from("NOTE 1"), String::from("NOTE 2")],
+                ..Default::default()
+            };
+        }
+    }
+
+    mod in_place {
+        use super::*;
+
+        use pretty_assertions::assert_eq;
+        use std::fs::{copy, read};
+        use tempfile::tempdir;
+
+        #[test]
+        fn no_meta() -> Result<(), String> {
+            return check(
+                "res/test/simple.ans",
+                &Meta { title: String::from("TITLE"), size: 416, ..Default::default() },
+            );
+        }
+
+        #[test]
+        fn meta() -> Result<(), String> {
+            let meta = meta::get("res/test/meta.ans").map_err(|err| return err.to_string())?;
+            return check("res/test/meta.ans", &Meta { title: String::from("TITLE"), ..meta.unwrap_or_default() });
+        }
+
+        #[test]
+        fn auto() -> Result<(), String> {
+            let tmp_dir = tempdir().map_err(|err| return err.to_string())?;
+            let target = tmp_dir.path().join("output.ans").to_string_lossy().to_string();
+            copy("res/test/meta.ans", &target).map_err(|err| return err.to_string())?;
+
+            assert_eq!(
+                run_in_place(
+                    &target,
+                    Policy::Strict,
+                    None,
+                    &[(String::from("width"), String::from("auto")), (String::from("height"), String::from("auto"))],
+                ),
+                ExitCode::OK
+            );
+            let meta = meta::get("res/test/meta.ans").map_err(|err| return err.to_string())?.unwrap_or_default();
+            assert_eq!(
+                meta::get(&target).map_err(|err| return err.to_string())?,
+                Some(Meta { width: 80, height: 4, ..meta })
+            );
+
+            tmp_dir.close().map_err(|err| return err.to_string())?;
+
+            return Ok(());
+        }
+
+        fn check(input: &str, expected: &Meta) -> Result<(), String> {
+            let tmp_dir = tempdir().map_err(|err| return err.to_string())?;
+            let target = tmp_dir.path().join("output.ans").to_string_lossy().to_string();
+            copy(input, &target).map_err(|err| return err.to_string())?;
+
+            assert_eq!(
+                run_in_place(&target, Policy::Strict, None, &[(String::from("title"), String::from("TITLE"))]),
+                ExitCode::OK
+            );
+            assert_eq!(meta::get(&target).map_err(|err| return err.to_string())?, Some(expected.clone()));
+
+            let before = read(input).map_err(|err| return err.to_string())?;
+            let after = read(&target).map_err(|err| return err.to_string())?;
+            let size = expected.size as usize;
+            assert_eq!(after[..size], before[..size]);
+            assert_eq!(after[size..], expected.to_bytes().map_err(|err| return err.to_string())?);
+
+            tmp_dir.close().map_err(|err| return err.to_string())?;
+
+            return Ok(());
+        }
+    }
+
     #[test]
     fn unknown_key() -> Result<(), String> {
         return test::err(
-            |i, o| return run(i, o, &String::from("foo"), &String::from("bar")),
+            |i, o| return run(i, o, Policy::Strict, None, &[(String::from("foo"), String::from("bar"))]),
             "res/test/simple.ans",
             "Unknown key: foo",
         );
@@ -232,16 +662,92 @@ mod tests {
     #[test]
     fn illegal() -> Result<(), String> {
         return test::err(
-            |i, o| return run(i, o, &String::from("title"), &String::from("🚫")),
+            |i, o| return run(i, o, Policy::Strict, None, &[(String::from("title"), String::from("🚫"))]),
             "res/test/simple.ans",
             "Title contains illegal characters (🚫 (U+1F6AB) is not a valid CP437 character)",
         );
     }
 
+    mod encoding {
+        use super::*;
+
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn replace() -> Result<(), String> {
+            return test::file_meta(
+                |i, o| return run(i, o, Policy::Replace, None, &[(String::from("title"), String::from("“OK” 🚫"))]),
+                "res/test/simple.ans",
+                Some(Meta { title: String::from("?OK? ?"), size: 416, ..Default::default() }),
+            );
+        }
+
+        #[test]
+        fn transliterate() -> Result<(), String> {
+            return test::file_meta(
+                |i, o| {
+                    return run(
+                        i,
+                        o,
+                        Policy::Transliterate,
+                        None,
+                        &[
+                            (String::from("title"), String::from("“Cafe\\u301” — ✓")),
+                            (String::from("notes"), String::from("It’s…\\n╭━╮")),
+                        ],
+                    );
+                },
+                "res/test/simple.ans",
+                Some(Meta {
+                    title: String::from("\"Café\" - √"),
+                    notes: vec![String::from("It's..."), String::from("┌═┐")],
+                    size: 416,
+                    ..Default::default()
+                }),
+            );
+        }
+
+        #[test]
+        fn strict() -> Result<(), String> {
+            return test::err(
+                |i, o| return run(i, o, Policy::Strict, None, &[(String::from("author"), String::from("“OK”"))]),
+                "res/test/simple.ans",
+                "Author contains illegal characters (“ (U+201C) is not a valid CP437 character)",
+            );
+        }
+
+        #[test]
+        fn missing() {
+            assert_eq!(
+                exec(&[
+                    String::from("cp437-set-meta"),
+                    String::from("a"),
+                    String::from("b=c"),
+                    String::from("--encoding")
+                ]),
+                ExitCode::USAGE(String::from("Missing encoding policy")),
+            );
+        }
+
+        #[test]
+        fn unknown() {
+            assert_eq!(
+                exec(&[
+                    String::from("cp437-set-meta"),
+                    String::from("--encoding"),
+                    String::from("foo"),
+                    String::from("a"),
+                    String::from("b=c"),
+                ]),
+                ExitCode::USAGE(String::from("Unknown encoding policy: foo")),
+            );
+        }
+    }
+
     #[test]
     fn hex() -> Result<(), String> {
         return test::file_meta(
-            |i, o| return run(i, o, &String::from("title"), &String::from("\\x40")),
+            |i, o| return run(i, o, Policy::Strict, None, &[(String::from("title"), String::from("\\x40"))]),
             "res/test/simple.ans",
             Some(Meta { title: String::from("@"), size: 416, ..Default::default() }),
         );
@@ -250,7 +756,7 @@ mod tests {
     #[test]
     fn unicode() -> Result<(), String> {
         return test::file_meta(
-            |i, o| return run(i, o, &String::from("title"), &String::from("\\u3B1")),
+            |i, o| return run(i, o, Policy::Strict, None, &[(String::from("title"), String::from("\\u3B1"))]),
             "res/test/simple.ans",
             Some(Meta { title: String::from("α"), size: 416, ..Default::default() }),
         );
@@ -259,16 +765,16 @@ mod tests {
     #[test]
     fn lf() -> Result<(), String> {
         return test::file_meta(
-            |i, o| return run(i, o, &String::from("title"), &String::from("\\n")),
+            |i, o| return run(i, o, Policy::Strict, None, &[(String::from("title"), String::from("\\n"))]),
             "res/test/simple.ans",
-            Some(Meta { title: String::from(""), size: 416, ..Default::default() }),
+            Some(Meta { title: String::new(), size: 416, ..Default::default() }),
         );
     }
 
     #[test]
     fn title() -> Result<(), String> {
         return test::file_meta(
-            |i, o| return run(i, o, &String::from("title"), &String::from("TITLE")),
+            |i, o| return run(i, o, Policy::Strict, None, &[(String::from("title"), String::from("TITLE"))]),
             "res/test/simple.ans",
             Some(Meta { title: String::from("TITLE"), size: 416, ..Default::default() }),
         );
@@ -277,7 +783,7 @@ mod tests {
     #[test]
     fn author() -> Result<(), String> {
         return test::file_meta(
-            |i, o| return run(i, o, &String::from("author"), &String::from("AUTHOR")),
+            |i, o| return run(i, o, Policy::Strict, None, &[(String::from("author"), String::from("AUTHOR"))]),
             "res/test/simple.ans",
             Some(Meta { author: String::from("AUTHOR"), size: 416, ..Default::default() }),
         );
@@ -286,19 +792,49 @@ mod tests {
     #[test]
     fn group() -> Result<(), String> {
         return test::file_meta(
-            |i, o| return run(i, o, &String::from("group"), &String::from("GROUP")),
+            |i, o| return run(i, o, Policy::Strict, None, &[(String::from("group"), String::from("GROUP"))]),
             "res/test/simple.ans",
             Some(Meta { group: String::from("GROUP"), size: 416, ..Default::default() }),
         );
     }
 
+    #[test]
+    fn auto() -> Result<(), String> {
+        return test::file_meta(
+            |i, o| {
+                return run(
+                    i,
+                    o,
+                    Policy::Strict,
+                    None,
+                    &[
+                        (String::from("type"), String::from("auto")),
+                        (String::from("width"), String::from("auto")),
+                        (String::from("height"), String::from("auto")),
+                    ],
+                );
+            },
+            "res/test/128_bytes.ans",
+            Some(Meta { r#type: FileType::Ascii, width: 80, height: 2, size: 128, ..Default::default() }),
+        );
+    }
+
     mod date {
         use super::*;
 
         #[test]
         fn valid() -> Result<(), String> {
             return test::file_meta(
-                |i, o| return run(i, o, &String::from("date"), &String::from("19700101")),
+                |i, o| return run(i, o, Policy::Strict, None, &[(String::from("date"), String::from("19700101"))]),
+                "res/test/simple.ans",
+                Some(Meta { date: String::from("19700101"), size: 416, ..Default::default() }),
+            );
+        }
+
+        #[test]
+        fn iso() -> Result<(), String> {
+            return test::file_meta(
+                |i, o| return run(i, o, Policy::Strict, None, &[(String::from("date"), String::from("1970-01-01"))]),
                 "res/test/simple.ans",
                 Some(Meta { date: String::from("19700101"), size: 416, ..Default::default() }),
             );
@@ -307,7 +843,7 @@ mod tests {
         #[test]
         fn invalid() -> Result<(), String> {
             return test::err(
-                |i, o| return run(i, o, &String::from("date"), &String::from("YYYYMMDD")),
+                |i, o| return run(i, o, Policy::Strict, None, &[(String::from("date"), String::from("YYYYMMDD"))]),
                 "res/test/simple.ans",
                 "Date format is wrong (input contains invalid characters)",
             );
@@ -317,7 +853,7 @@ mod tests {
     #[test]
     fn size() -> Result<(), String> {
         return test::err(
-            |i, o| return run(i, o, &String::from("size"), &String::from("1")),
+            |i, o| return run(i, o, Policy::Strict, None, &[(String::from("size"), String::from("1"))]),
             "res/test/simple.ans",
             "Size can't be changed",
         );
@@ -329,9 +865,9 @@ mod tests {
         #[test]
         fn none() -> Result<(), String> {
             return test::file_meta(
-                |i, o| return run(i, o, &String::from("type"), &String::from("None")),
+                |i, o| return run(i, o, Policy::Strict, None, &[(String::from("type"), String::from("None"))]),
                 "res/test/simple.ans",
-                Some(Meta { r#type: (0, 0), size: 416, ..Default::default() }),
+                Some(Meta { r#type: FileType::None, size: 416, ..Default::default() }),
             );
         }
 
@@ -339,10 +875,10 @@ mod tests {
         fn ascii() -> Result<(), String> {
             return test::file_meta(
                 |i, o| {
-                    return run(i, o, &String::from("type"), &String::from("Character/ASCII"));
+                    return run(i, o, Policy::Strict, None, &[(String::from("type"), String::from("Character/ASCII"))]);
                 },
                 "res/test/simple.ans",
-                Some(Meta { r#type: (1, 0), size: 416, ..Default::default() }),
+                Some(Meta { r#type: FileType::Ascii, size: 416, ..Default::default() }),
             );
         }
 
@@ -350,19 +886,28 @@ mod tests {
         fn ansi() -> Result<(), String> {
             return test::file_meta(
                 |i, o| {
-                    return run(i, o, &String::from("type"), &String::from("Character/ANSI"));
+                    return run(i, o, Policy::Strict, None, &[(String::from("type"), String::from("Character/ANSI"))]);
                 },
                 "res/test/simple.ans",
-                Some(Meta { r#type: (1, 1), size: 416, ..Default::default() }),
+                Some(Meta { r#type: FileType::Ansi, size: 416, ..Default::default() }),
             );
         }
 
         #[test]
         fn unsupported() -> Result<(), String> {
             return test::err(
-                |i, o| return run(i, o, &String::from("type"), &String::from("foo")),
+                |i, o| return run(i, o, Policy::Strict, None, &[(String::from("type"), String::from("Bitmap/GIF"))]),
+                "res/test/simple.ans",
+                "Type is unsupported (Bitmap/GIF)",
+            );
+        }
+
+        #[test]
+        fn unknown() -> Result<(), String> {
+            return test::err(
+                |i, o| return run(i, o, Policy::Strict, None, &[(String::from("type"), String::from("foo"))]),
                 "res/test/simple.ans",
-                "Type is unsupported (foo)",
+                "Unknown type: foo",
             );
         }
     }
@@ -371,7 +916,7 @@ mod tests {
     fn width() -> Result<(), String> {
         return test::file_meta(
             |i, o| {
-                return run(i, o, &String::from("width"), &String::from("1"));
+                return run(i, o, Policy::Strict, None, &[(String::from("width"), String::from("1"))]);
             },
             "res/test/simple.ans",
             Some(Meta { width: 1, size: 416, ..Default::default() }),
@@ -382,13 +927,30 @@ mod tests {
     fn height() -> Result<(), String> {
         return test::file_meta(
             |i, o| {
-                return run(i, o, &String::from("height"), &String::from("1"));
+                return run(i, o, Policy::Strict, None, &[(String::from("height"), String::from("1"))]);
             },
             "res/test/simple.ans",
             Some(Meta { height: 1, size: 416, ..Default::default() }),
         );
     }
 
+    #[test]
+    fn tinfo() -> Result<(), String> {
+        return test::file_meta(
+            |i, o| {
+                return run(
+                    i,
+                    o,
+                    Policy::Strict,
+                    None,
+                    &[(String::from("tinfo3"), String::from("1")), (String::from("tinfo4"), String::from("2"))],
+                );
+            },
+            "res/test/simple.ans",
+            Some(Meta { tinfo3: 1, tinfo4: 2, size: 416, ..Default::default() }),
+        );
+    }
+
     mod flags {
         use super::*;
 
@@ -396,7 +958,7 @@ mod tests {
         fn valid() -> Result<(), String> {
             return test::file_meta(
                 |i, o| {
-                    return run(i, o, &String::from("flags"), &String::from("0x01"));
+                    return run(i, o, Policy::Strict, None, &[(String::from("flags"), String::from("0x01"))]);
                 },
                 "res/test/simple.ans",
                 Some(Meta { flags: 0x01, size: 416, ..Default::default() }),
@@ -407,7 +969,7 @@ mod tests {
         fn binary() -> Result<(), String> {
             return test::file_meta(
                 |i, o| {
-                    return run(i, o, &String::from("flags"), &String::from("0b00011"));
+                    return run(i, o, Policy::Strict, None, &[(String::from("flags"), String::from("0b00011"))]);
                 },
                 "res/test/simple.ans",
                 Some(Meta { flags: 0x03, size: 416, ..Default::default() }),
@@ -418,7 +980,7 @@ mod tests {
         fn hex() -> Result<(), String> {
             return test::file_meta(
                 |i, o| {
-                    return run(i, o, &String::from("flags"), &String::from("0x03"));
+                    return run(i, o, Policy::Strict, None, &[(String::from("flags"), String::from("0x03"))]);
                 },
                 "res/test/simple.ans",
                 Some(Meta { flags: 0x03, size: 416, ..Default::default() }),
@@ -429,30 +991,67 @@ mod tests {
         fn decimal() -> Result<(), String> {
             return test::file_meta(
                 |i, o| {
-                    return run(i, o, &String::from("flags"), &String::from("3"));
+                    return run(i, o, Policy::Strict, None, &[(String::from("flags"), String::from("3"))]);
                 },
                 "res/test/simple.ans",
                 Some(Meta { flags: 0x03, size: 416, ..Default::default() }),
             );
         }
 
+        #[test]
+        fn blink() -> Result<(), String> {
+            return test::file_meta(
+                |i, o| {
+                    return run(i, o, Policy::Strict, None, &[(String::from("flags"), String::from("0x00"))]);
+                },
+                "res/test/simple.ans",
+                Some(Meta { flags: 0x00, size: 416, ..Default::default() }),
+            );
+        }
+
         #[test]
         fn unsupported() -> Result<(), String> {
             return test::err(
-                |i, o| return run(i, o, &String::from("flags"), &String::from("0x00")),
+                |i, o| return run(i, o, Policy::Strict, None, &[(String::from("flags"), String::from("0x07"))]),
+                "res/test/simple.ans",
+                "Invalid letter spacing",
+            );
+        }
+
+        #[test]
+        fn symbolic() -> Result<(), String> {
+            return test::file_meta(
+                |i, o| {
+                    return run(
+                        i,
+                        o,
+                        Policy::Strict,
+                        None,
+                        &[(String::from("flags"), String::from("ice,9px,legacy-ar"))],
+                    );
+                },
                 "res/test/simple.ans",
-                "Blink mode is unsupported",
+                Some(Meta { flags: 0x05, size: 416, ..Default::default() }),
             );
         }
 
         #[test]
         fn illegal() -> Result<(), String> {
             return test::err(
-                |i, o| return run(i, o, &String::from("flags"), &String::from("x")),
+                |i, o| return run(i, o, Policy::Strict, None, &[(String::from("flags"), String::from("0xZZ"))]),
                 "res/test/simple.ans",
                 "Invalid flags (invalid digit found in string)",
             );
         }
+
+        #[test]
+        fn unknown() -> Result<(), String> {
+            return test::err(
+                |i, o| return run(i, o, Policy::Strict, None, &[(String::from("flags"), String::from("ice,x"))]),
+                "res/test/simple.ans",
+                "Unknown flag: x",
+            );
+        }
     }
 
     mod font {
@@ -462,7 +1061,7 @@ mod tests {
         fn valid() -> Result<(), String> {
             return test::file_meta(
                 |i, o| {
-                    return run(i, o, &String::from("font"), &String::from("IBM VGA 437"));
+                    return run(i, o, Policy::Strict, None, &[(String::from("font"), String::from("IBM VGA 437"))]);
                 },
                 "res/test/simple.ans",
                 Some(Meta { font: String::from("IBM VGA 437"), size: 416, ..Default::default() }),
@@ -470,11 +1069,22 @@ mod tests {
         }
 
         #[test]
-        fn unsupported() -> Result<(), String> {
+        fn not_bundled() -> Result<(), String> {
+            return test::file_meta(
+                |i, o| {
+                    return run(i, o, Policy::Strict, None, &[(String::from("font"), String::from("Amiga Topaz 1+"))]);
+                },
+                "res/test/simple.ans",
+                Some(Meta { font: String::from("Amiga Topaz 1+"), size: 416, ..Default::default() }),
+            );
+        }
+
+        #[test]
+        fn unknown() -> Result<(), String> {
             return test::err(
-                |i, o| return run(i, o, &String::from("font"), &String::from("foo")),
+                |i, o| return run(i, o, Policy::Strict, None, &[(String::from("font"), String::from("foo"))]),
                 "res/test/simple.ans",
-                "Font is unsupported (foo)",
+                "Font is unknown (foo)",
             );
         }
     }
@@ -486,7 +1096,7 @@ mod tests {
         fn empty() -> Result<(), String> {
             return test::file_meta(
                 |i, o| {
-                    return run(i, o, &String::from("notes"), &String::from(""));
+                    return run(i, o, Policy::Strict, None, &[(String::from("notes"), String::new())]);
                 },
                 "res/test/simple.ans",
                 Some(Meta { notes: vec![], size: 416, ..Default::default() }),
@@ -497,7 +1107,7 @@ mod tests {
         fn single() -> Result<(), String> {
             return test::file_meta(
                 |i, o| {
-                    return run(i, o, &String::from("notes"), &String::from("foo"));
+                    return run(i, o, Policy::Strict, None, &[(String::from("notes"), String::from("foo"))]);
                 },
                 "res/test/simple.ans",
                 Some(Meta { notes: vec![String::from("foo")], size: 416, ..Default::default() }),
@@ -508,7 +1118,7 @@ mod tests {
         fn multiple() -> Result<(), String> {
             return test::file_meta(
                 |i, o| {
-                    return run(i, o, &String::from("notes"), &String::from("foo\\nbar"));
+                    return run(i, o, Policy::Strict, None, &[(String::from("notes"), String::from("foo\\nbar"))]);
                 },
                 "res/test/simple.ans",
                 Some(Meta { notes: vec![String::from("foo"), String::from("bar")], size: 416, ..Default::default() }),
@@ -519,7 +1129,7 @@ mod tests {
         fn trailing() -> Result<(), String> {
             return test::file_meta(
                 |i, o| {
-                    return run(i, o, &String::from("notes"), &String::from("foo\\n"));
+                    return run(i, o, Policy::Strict, None, &[(String::from("notes"), String::from("foo\\n"))]);
                 },
                 "res/test/simple.ans",
                 Some(Meta { notes: vec![String::from("foo")], size: 416, ..Default::default() }),
@@ -530,7 +1140,7 @@ mod tests {
         fn infix_empty() -> Result<(), String> {
             return test::file_meta(
                 |i, o| {
-                    return run(i, o, &String::from("notes"), &String::from("foo\\n\\nbar"));
+                    return run(i, o, Policy::Strict, None, &[(String::from("notes"), String::from("foo\\n\\nbar"))]);
                 },
                 "res/test/simple.ans",
                 Some(Meta { notes: vec![String::from("foo"), String::from("bar")], size: 416, ..Default::default() }),
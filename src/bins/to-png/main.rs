@@ -7,8 +7,9 @@ use std::{
 };
 
 use cp437_tools::{
-    internal::{process, ExitCode, Input, Output},
-    prelude::{Meta, CP437_TO_UTF8},
+    fonts::BitmapFont,
+    internal::{process, BlinkMode, ExitCode, Input, Output, BLINK_DELAY},
+    prelude::{meta, ColourScheme, Meta},
 };
 
 #[allow(dead_code)]
@@ -24,15 +25,36 @@ pub fn main() -> ExitCode {
 #[allow(missing_docs, reason = "Just an entry point")]
 #[allow(clippy::missing_docs_in_private_items, reason = "Just an entry point")]
 pub fn exec(args: &[String]) -> ExitCode {
-    let exit_code = if args.len() < 2 {
+    let mut font = None;
+    let mut missing_font = false;
+    let mut positional = vec![];
+    let mut iter = args.iter().skip(1);
+    while let Some(arg) = iter.next() {
+        if arg == "--font" {
+            font = iter.next().map(String::as_str);
+            missing_font = font.is_none();
+        } else {
+            positional.push(arg.clone());
+        }
+    }
+    let args = positional;
+    let exit_code = if missing_font {
+        ExitCode::USAGE(String::from("Missing font"))
+    } else if args.is_empty() {
         ExitCode::USAGE(String::from("Missing input file"))
     } else if args.len() > 3 {
         ExitCode::USAGE(String::from("Too many arguments"))
     } else if stdout().is_terminal() {
         ExitCode::USAGE(String::from("Refusing to write to terminal"))
     } else {
-        process(&args[1], |i, o| {
-            return run(i, o, args.get(2).unwrap_or(&String::from("CLASSIC")));
+        process(&args[0], |i, o| {
+            return run(
+                i,
+                o,
+                args.get(1).unwrap_or(&String::from("CLASSIC")),
+                args.get(2).unwrap_or(&String::from("on")),
+                font,
+            );
         })
     };
 
@@ -42,61 +64,68 @@ pub fn exec(args: &[String]) -> ExitCode {
 
 #[allow(missing_docs, reason = "Just an entry point")]
 #[allow(clippy::missing_docs_in_private_items, reason = "Just an entry point")]
-pub fn run(input: &mut Input, output: &mut Output, scheme: &String) -> ExitCode {
+pub fn run(input: &mut Input, output: &mut Output, scheme: &str, blink: &str, font: Option<&str>) -> ExitCode {
     let meta = input.meta.clone().unwrap_or(Meta { size: input.size, ..Default::default() });
+    let mut canvas = input.canvas()?;
+    if let Some(font) = font {
+        canvas.set_font(Some(BitmapFont::load(font, meta.code_page())?));
+    } else if canvas.font().is_none() {
+        if let Err(err) = meta::check_font(Some(&meta)).and_then(|()| return meta::check_font_support(Some(&meta))) {
+            eprintln!("\x1B[33mWARN: {err}, drawing with IBM VGA instead\x1B[0m");
+        }
+    }
 
-    let (width, height) = meta.dimensions();
+    let (width, height) = canvas.dimensions();
     let (width, height) = (width as usize, height as usize);
-    let (font_width, font_height) = meta.font_size();
-    let (font_width, font_height) = (font_width as usize, font_height as usize);
+    let font = canvas.font().unwrap_or_else(|| return meta.font_bitmap());
+    let (font_width, font_height) = (font.width() as usize, font.height() as usize);
     let (ar_x, ar_y) = meta.aspect_ratio();
     let (ar_x, ar_y) = (ar_x as usize, ar_y as usize);
-    let font_face = meta.font_face_otb();
-    let mut canvas = vec![0; 3 * width * height * font_width * font_height * ar_x * ar_y];
-    input.read_by_bytes_full(
-        |byte, (x, y), colour| {
+    let scheme = ColourScheme::get(scheme)?;
+    let scheme = canvas.palette().unwrap_or(&scheme);
+    let blink = BlinkMode::get(blink)?;
+    let frames = if canvas.cells().any(|(_, cell)| return cell.attributes.blink) { blink.frames() } else { &[true] };
+    let mut images = vec![];
+    for visible in frames {
+        let length = 3 * width * height * font_width * font_height * ar_x * ar_y;
+        let mut image = vec![];
+        image.try_reserve_exact(length).map_err(|_| {
+            return ExitCode::FAIL(format!("Image is too large ({width}x{height} characters)"));
+        })?;
+        image.resize(length, 0);
+        for ((x, y), cell) in canvas.cells() {
             let (x, y) = (x as usize, y as usize);
-            let bitmap = font_face
-                .glyph_raster_image(
-                    font_face
-                        .glyph_index(CP437_TO_UTF8[byte as usize])
-                        .ok_or_else(|| format!("Glyph for 0x{byte:02X} is missing"))?,
-                    u16::try_from(font_height)?,
-                )
-                .ok_or_else(|| format!("Glyph bitmap for 0x{byte:02X} is missing"))?;
+            let colour = [cell.bg.rgb(scheme), cell.fg.rgb(scheme)];
+            let pixels = font.raster(cell.byte, font.width());
+            let shown = *visible || !cell.attributes.blink;
 
             for i in 0..(font_width * ar_x) {
                 for j in 0..(font_height * ar_y) {
                     let offset =
                         3 * ((y * font_height * ar_y + j) * font_width * ar_x * width + (x * font_width * ar_x + i));
                     #[expect(clippy::integer_division, reason = "Intentional")]
-                    let bitmap_offset = i / ar_x + j / ar_y * font_width;
-                    canvas[offset..offset + 3].copy_from_slice(
-                        #[expect(clippy::integer_division, reason = "Intentional")]
-                        if (bitmap.data[bitmap_offset / 8] >> (7 - (bitmap_offset % 8))) & 1 == 0 {
-                            &colour[0]
-                        } else {
-                            &colour[1]
-                        },
-                    );
+                    let pixel = pixels[i / ar_x + j / ar_y * font_width] && shown;
+                    image[offset..offset + 3].copy_from_slice(if pixel { &colour[1] } else { &colour[0] });
                 }
             }
+        }
+        images.push(image);
+    }
 
-            return Ok(());
-        },
-        scheme,
-    )?;
-
-    return write(output, &canvas, &meta);
+    return write(
+        output,
+        &images,
+        (u32::try_from(width * font_width * ar_x)?, u32::try_from(height * font_height * ar_y)?),
+        &meta,
+    );
 }
 
 /// Write image to disk, adding all available metadata.
-fn write(output: &mut Output, canvas: &[u8], meta: &Meta) -> ExitCode {
-    let mut encoder = Encoder::new(
-        BufWriter::new(output),
-        u32::from(meta.width()) * u32::from(meta.font_width()) * u32::from(meta.aspect_ratio().0),
-        u32::from(meta.height()) * u32::from(meta.font_height()) * u32::from(meta.aspect_ratio().1),
-    );
+///
+/// More than one image makes for an animation, alternating between them.
+///
+fn write(output: &mut Output, images: &[Vec<u8>], (width, height): (u32, u32), meta: &Meta) -> ExitCode {
+    let mut encoder = Encoder::new(BufWriter::new(output), width, height);
     encoder.set_color(ColorType::Rgb);
     encoder.set_depth(BitDepth::Eight);
     encoder.set_pixel_dims(Some(PixelDimensions {
@@ -106,8 +135,14 @@ fn write(output: &mut Output, canvas: &[u8], meta: &Meta) -> ExitCode {
     }));
     encoder.set_compression(Compression::Best);
     encoder.validate_sequence(true);
+    if images.len() > 1 {
+        encoder.set_animated(u32::try_from(images.len())?, 0)?;
+        encoder.set_frame_delay(BLINK_DELAY.0, BLINK_DELAY.1)?;
+    }
     let mut writer = encoder.write_header()?;
-    writer.write_image_data(canvas)?;
+    for image in images {
+        writer.write_image_data(image)?;
+    }
     if meta.title().is_some() {
         let mut title = ITXtChunk::new(String::from("Title"), &meta.title);
         title.compress_text().map_err(|err| return ExitCode::ERROR(err.to_string()))?;
@@ -162,11 +197,25 @@ mod tests {
     #[test]
     fn too_many_args() {
         assert_eq!(
-            exec(&[String::from("cp437-to-png"), String::from("a"), String::from("b"), String::from("c")]),
+            exec(&[
+                String::from("cp437-to-png"),
+                String::from("a"),
+                String::from("b"),
+                String::from("c"),
+                String::from("d"),
+            ]),
             ExitCode::USAGE(String::from("Too many arguments")),
         );
     }
 
+    #[test]
+    fn no_font() {
+        assert_eq!(
+            exec(&[String::from("cp437-to-png"), String::from("a"), String::from("--font")]),
+            ExitCode::USAGE(String::from("Missing font")),
+        );
+    }
+
     #[ignore]
     #[test]
     fn stdout() {
@@ -179,21 +228,34 @@ mod tests {
     #[test]
     fn simple() -> Result<(), String> {
         return test::file(
-            |i, o| return run(i, o, &String::from("CLASSIC")),
+            |i, o| return run(i, o, &String::from("CLASSIC"), &String::from("on"), None),
             "res/test/simple.ans",
             "res/test/simple.png",
         );
     }
 
+    #[test]
+    fn code_page() -> Result<(), String> {
+        return test::file(
+            |i, o| return run(i, o, &String::from("CLASSIC"), &String::from("on"), None),
+            "res/test/cyrillic.ans",
+            "res/test/cyrillic.png",
+        );
+    }
+
     #[test]
     fn meta() -> Result<(), String> {
-        return test::file(|i, o| return run(i, o, &String::from("CLASSIC")), "res/test/meta.ans", "res/test/meta.png");
+        return test::file(
+            |i, o| return run(i, o, &String::from("CLASSIC"), &String::from("on"), None),
+            "res/test/meta.ans",
+            "res/test/meta.png",
+        );
     }
 
     #[test]
     fn notes() -> Result<(), String> {
         return test::file(
-            |i, o| return run(i, o, &String::from("CLASSIC")),
+            |i, o| return run(i, o, &String::from("CLASSIC"), &String::from("on"), None),
             "res/test/comments.ans",
             "res/test/comments.png",
         );
@@ -202,21 +264,97 @@ mod tests {
     #[test]
     fn background() -> Result<(), String> {
         return test::file(
-            |i, o| return run(i, o, &String::from("CLASSIC")),
+            |i, o| return run(i, o, &String::from("CLASSIC"), &String::from("on"), None),
             "res/test/background.ans",
             "res/test/background.png",
         );
     }
 
+    #[test]
+    fn xbin() -> Result<(), String> {
+        return test::file(
+            |i, o| return run(i, o, &String::from("CLASSIC"), &String::from("on"), None),
+            "res/test/xbin.xb",
+            "res/test/xbin.png",
+        );
+    }
+
+    #[test]
+    fn binary_text() -> Result<(), String> {
+        return test::file(
+            |i, o| return run(i, o, &String::from("CLASSIC"), &String::from("on"), None),
+            "res/test/binary.bin",
+            "res/test/binary.png",
+        );
+    }
+
+    #[test]
+    fn blink_on() -> Result<(), String> {
+        return test::file(
+            |i, o| return run(i, o, &String::from("CLASSIC"), &String::from("on"), None),
+            "res/test/blink.ans",
+            "res/test/blink_on.png",
+        );
+    }
+
+    #[test]
+    fn blink_off() -> Result<(), String> {
+        return test::file(
+            |i, o| return run(i, o, &String::from("CLASSIC"), &String::from("off"), None),
+            "res/test/blink.ans",
+            "res/test/blink_off.png",
+        );
+    }
+
+    #[test]
+    fn blink_animate() -> Result<(), String> {
+        return test::file(
+            |i, o| return run(i, o, &String::from("CLASSIC"), &String::from("animate"), None),
+            "res/test/blink.ans",
+            "res/test/blink_animate.png",
+        );
+    }
+
+    #[test]
+    fn unknown_blink() -> Result<(), String> {
+        return test::err(
+            |i, o| return run(i, o, &String::from("CLASSIC"), &String::from("x"), None),
+            "res/test/blink.ans",
+            "Unknown blink mode: x",
+        );
+    }
+
+    #[test]
+    fn font() -> Result<(), String> {
+        return test::file(
+            |i, o| return run(i, o, &String::from("CLASSIC"), &String::from("on"), Some("res/test/font.psf")),
+            "res/test/simple.ans",
+            "res/test/font.png",
+        );
+    }
+
+    #[test]
+    fn missing_font() -> Result<(), String> {
+        return test::err(
+            |i, o| return run(i, o, &String::from("CLASSIC"), &String::from("on"), Some("res/test/missing.psf")),
+            "res/test/simple.ans",
+            "Can't read font res/test/missing.psf: No such file or directory (os error 2)",
+        );
+    }
+
     #[test]
     fn logo() -> Result<(), String> {
-        return test::file(|i, o| return run(i, o, &String::from("CLASSIC")), "res/logo/logo.ans", "res/logo/logo.png");
+        return test::file(
+            |i, o| return run(i, o, &String::from("CLASSIC"), &String::from("on"), None),
+            "res/logo/logo.ans",
+            "res/logo/logo.png",
+        );
     }
 
     #[test]
     fn banner() -> Result<(), String> {
         return test::file(
-            |i, o| return run(i, o, &String::from("CLASSIC")),
+            |i, o| return run(i, o, &String::from("CLASSIC"), &String::from("on"), None),
             "res/banner/banner.ans",
             "res/banner/banner.png",
         );
@@ -2,21 +2,21 @@
 
 use base64::prelude::{Engine as _, BASE64_STANDARD};
 use std::{
-    cell::Cell,
     env::args,
     io::{stdout, IsTerminal as _},
 };
 use svg::{
     node::{
-        element::{Element, Group, Rectangle, Style, Text, Title, SVG},
+        element::{Animate, Element, Group, Path, Rectangle, Style, Text, Title, SVG},
         Comment, Text as TextNode,
     },
     Document, Node as _,
 };
 
 use cp437_tools::{
-    internal::{process, ExitCode, Input, Output},
-    prelude::{Meta, CP437_TO_UTF8},
+    fonts::BitmapFont,
+    internal::{process, BlinkMode, ExitCode, Input, Output, BLINK_DELAY},
+    prelude::{meta, CodePage, ColourScheme, Meta},
 };
 
 #[allow(dead_code)]
@@ -32,15 +32,36 @@ pub fn main() -> ExitCode {
 #[allow(missing_docs, reason = "Just an entry point")]
 #[allow(clippy::missing_docs_in_private_items, reason = "Just an entry point")]
 pub fn exec(args: &[String]) -> ExitCode {
-    let exit_code = if args.len() < 2 {
+    let mut font = None;
+    let mut missing_font = false;
+    let mut positional = vec![];
+    let mut iter = args.iter().skip(1);
+    while let Some(arg) = iter.next() {
+        if arg == "--font" {
+            font = iter.next().map(String::as_str);
+            missing_font = font.is_none();
+        } else {
+            positional.push(arg.clone());
+        }
+    }
+    let args = positional;
+    let exit_code = if missing_font {
+        ExitCode::USAGE(String::from("Missing font"))
+    } else if args.is_empty() {
         ExitCode::USAGE(String::from("Missing input file"))
     } else if args.len() > 3 {
         ExitCode::USAGE(String::from("Too many arguments"))
     } else if stdout().is_terminal() {
         ExitCode::USAGE(String::from("Refusing to write to terminal"))
     } else {
-        process(&args[1], |i, o| {
-            return run(i, o, args.get(2).unwrap_or(&String::from("CLASSIC")));
+        process(&args[0], |i, o| {
+            return run(
+                i,
+                o,
+                args.get(1).unwrap_or(&String::from("CLASSIC")),
+                args.get(2).unwrap_or(&String::from("on")),
+                font,
+            );
         })
     };
 
@@ -50,21 +71,36 @@ pub fn exec(args: &[String]) -> ExitCode {
 
 #[allow(missing_docs, reason = "Just an entry point")]
 #[allow(clippy::missing_docs_in_private_items, reason = "Just an entry point")]
-pub fn run(input: &mut Input, output: &mut Output, scheme: &String) -> ExitCode {
+pub fn run(input: &mut Input, output: &mut Output, scheme: &str, blink: &str, font: Option<&str>) -> ExitCode {
     let meta = input.meta.clone().unwrap_or(Meta { size: input.size, ..Default::default() });
+    let mut canvas = input.canvas()?;
+    if let Some(font) = font {
+        canvas.set_font(Some(BitmapFont::load(font, meta.code_page())?));
+    } else if canvas.font().is_none() {
+        if let Err(err) = meta::check_font(Some(&meta)).and_then(|()| return meta::check_font_support(Some(&meta))) {
+            eprintln!("\x1B[33mWARN: {err}, drawing with IBM VGA instead\x1B[0m");
+        }
+    }
 
-    let (width, height) = meta.dimensions();
+    let (width, height) = canvas.dimensions();
     let (width, height) = (width as usize, height as usize);
-    let (font_width, font_height) = meta.font_size();
-    let (font_width, font_height) = (font_width as usize, font_height as usize);
+    let font = canvas.font().unwrap_or_else(|| return meta.font_bitmap());
+    let (font_width, font_height) = (font.width() as usize, font.height() as usize);
     let (ar_x, ar_y) = meta.aspect_ratio();
     let (ar_x, ar_y) = (ar_x as usize, ar_y as usize);
-    let font_face = meta.font_face_woff();
+    // The embedded font only has the characters of CP437, so anything else
+    // gets traced out of the bitmap font instead
+    let outlined = canvas.font().or_else(|| return (meta.code_page() != CodePage::Cp437).then_some(font));
+    let font_face = if outlined.is_some() { None } else { Some(meta.font_face_woff()) };
 
     let mut document = prepare(input, (width, height), (font_width, font_height), (ar_x, ar_y), font_face);
 
     document = document.add(Comment::new("Drawing"));
-    let drawing = Cell::new(
+    let scheme = ColourScheme::get(scheme)?;
+    let scheme = canvas.palette().unwrap_or(&scheme);
+    let blink = BlinkMode::get(blink)?;
+    let chars = meta.code_page().table();
+    let mut drawing =
         Group::new().set("font-family", "IBM VGA").set("transform", format!("scale({ar_x}, {ar_y})")).add(
             Rectangle::new()
                 .set("x", 0)
@@ -72,65 +108,101 @@ pub fn run(input: &mut Input, output: &mut Output, scheme: &String) -> ExitCode
                 .set("width", width * font_width)
                 .set("height", height * font_height)
                 .set("fill", "#000"),
-        ),
-    );
-
-    input.read_by_bytes_full(
-        |byte, (x, y), colour| {
-            drawing.set(
-                drawing
-                    .take()
-                    .add(
-                        Rectangle::new()
-                            .set("x", x as usize * font_width)
-                            .set("y", y as usize * font_height)
-                            .set("width", font_width)
-                            .set("height", font_height)
-                            .set("fill", format!("#{:02X}{:02X}{:02X}", colour[0][0], colour[0][1], colour[0][2])),
-                    )
-                    .add(
-                        #[expect(clippy::integer_division, reason = "Intentional")]
-                        Text::new(CP437_TO_UTF8[if byte > 0 { byte as usize } else { 32 }])
-                            .set("x", x as usize * font_width)
-                            .set("y", (y + 1) as usize * font_height - font_height / 4)
-                            .set("font-size", font_height)
-                            .set("fill", format!("#{:02X}{:02X}{:02X}", colour[1][0], colour[1][1], colour[1][2])),
-                    ),
-            );
+        );
 
-            return Ok(());
-        },
-        scheme,
-    )?;
+    for ((x, y), cell) in canvas.cells() {
+        let byte = cell.byte;
+        let colour = [cell.bg.rgb(scheme), cell.fg.rgb(scheme)];
+        let (x, y) = (x as usize * font_width, y as usize * font_height);
+        drawing = drawing.add(
+            Rectangle::new()
+                .set("x", x)
+                .set("y", y)
+                .set("width", font_width)
+                .set("height", font_height)
+                .set("fill", format!("#{:02X}{:02X}{:02X}", colour[0][0], colour[0][1], colour[0][2])),
+        );
+        if cell.attributes.blink && blink == BlinkMode::Off {
+            continue;
+        }
 
-    document = document.add(drawing.take());
+        let animate = cell.attributes.blink && blink == BlinkMode::Animate;
+        drawing = if let Some(font) = outlined {
+            let glyph = Path::new()
+                .set("d", outline(font, byte, (x, y)))
+                .set("fill", format!("#{:02X}{:02X}{:02X}", colour[1][0], colour[1][1], colour[1][2]));
+            drawing.add(if animate { glyph.add(blinking()) } else { glyph })
+        } else {
+            #[expect(clippy::integer_division, reason = "Intentional")]
+            let glyph = Text::new(chars[if byte > 0 { byte as usize } else { 32 }])
+                .set("x", x)
+                .set("y", y + font_height - font_height / 4)
+                .set("font-size", font_height)
+                .set("fill", format!("#{:02X}{:02X}{:02X}", colour[1][0], colour[1][1], colour[1][2]));
+            drawing.add(if animate { glyph.add(blinking()) } else { glyph })
+        };
+    }
+
+    document = document.add(drawing);
 
     svg::write(output, &document)?;
 
     return ExitCode::OK;
 }
 
+/// Make an element blink, by toggling its visibility.
+fn blinking() -> Animate {
+    return Animate::new()
+        .set("attributeName", "visibility")
+        .set("values", "visible;hidden")
+        .set("calcMode", "discrete")
+        .set("dur", format!("{:.3}s", 2.0 * f64::from(BLINK_DELAY.0) / f64::from(BLINK_DELAY.1)))
+        .set("repeatCount", "indefinite");
+}
+
+/// Trace a bitmap glyph as a path, one rectangle per horizontal run of pixels.
+fn outline(font: &BitmapFont, byte: u8, (x, y): (usize, usize)) -> String {
+    let mut path = vec![];
+    for (row, j) in (0..font.height()).zip(y..) {
+        let mut i = 0;
+        while i < font.width() {
+            if font.pixel(byte, i, row) {
+                let start = i;
+                while i < font.width() && font.pixel(byte, i, row) {
+                    i += 1;
+                }
+                path.push(format!("M{},{j}h{}v1h-{}z", x + usize::from(start), i - start, i - start));
+            } else {
+                i += 1;
+            }
+        }
+    }
+
+    return path.concat();
+}
+
 /// Prepare the SVG with all corresponding metadata.
 fn prepare(
     input: &mut Input,
     (width, height): (usize, usize),
     (font_width, font_height): (usize, usize),
     (ar_x, ar_y): (usize, usize),
-    font_face: &[u8],
+    font_face: Option<&[u8]>,
 ) -> SVG {
     let mut document = Document::new()
-        .set(
-            "viewBox",
-            (0, 0, width * font_width * ar_x, height * font_height * ar_y),
-        )
+        .set("viewBox", (0, 0, width * font_width * ar_x, height * font_height * ar_y))
         .set("width", width * font_width * ar_x)
-        .set("height", height * font_height * ar_y)
-        .add(Comment::new("Embedded IBM VGA font, provided under CC-BY-SA-4.0"))
-        .add(Comment::new("https://int10h.org/oldschool-pc-fonts"))
-        .add(Style::new(format!(
-            "@font-face {{ font-family: \"IBM VGA\"; src: url(\"data:application/font-woff;charset=utf-8;base64,{}\"); }}",
-            BASE64_STANDARD.encode(font_face),
-        )));
+        .set("height", height * font_height * ar_y);
+
+    if let Some(font_face) = font_face {
+        document = document
+            .add(Comment::new("Embedded IBM VGA font, provided under CC-BY-SA-4.0"))
+            .add(Comment::new("https://int10h.org/oldschool-pc-fonts"))
+            .add(Style::new(format!(
+                "@font-face {{ font-family: \"IBM VGA\"; src: url(\"data:application/font-woff;charset=utf-8;base64,{}\"); }}",
+                BASE64_STANDARD.encode(font_face),
+            )));
+    }
 
     if let Some(meta) = &input.meta {
         document = document.add(Comment::new("Metadata"));
@@ -169,7 +241,7 @@ fn prepare(
 
         if let Some(date) = meta.date() {
             let mut date_elem = Element::new("dc:date");
-            date_elem.append(TextNode::new(format!("{}-{}-{}", &date[0..4], &date[4..6], &date[6..8])));
+            date_elem.append(TextNode::new(date.format("%Y-%m-%d").to_string()));
             description.append(date_elem);
         }
 
@@ -232,11 +304,25 @@ mod tests {
     #[test]
     fn too_many_args() {
         assert_eq!(
-            exec(&[String::from("cp437-to-svg"), String::from("a"), String::from("b"), String::from("c")]),
+            exec(&[
+                String::from("cp437-to-svg"),
+                String::from("a"),
+                String::from("b"),
+                String::from("c"),
+                String::from("d"),
+            ]),
             ExitCode::USAGE(String::from("Too many arguments")),
         );
     }
 
+    #[test]
+    fn no_font() {
+        assert_eq!(
+            exec(&[String::from("cp437-to-svg"), String::from("a"), String::from("--font")]),
+            ExitCode::USAGE(String::from("Missing font")),
+        );
+    }
+
     #[ignore]
     #[test]
     fn stdout() {
@@ -249,21 +335,34 @@ mod tests {
     #[test]
     fn simple() -> Result<(), String> {
         return test::file(
-            |i, o| return run(i, o, &String::from("CLASSIC")),
+            |i, o| return run(i, o, &String::from("CLASSIC"), &String::from("on"), None),
             "res/test/simple.ans",
             "res/test/simple.svg",
         );
     }
 
+    #[test]
+    fn code_page() -> Result<(), String> {
+        return test::file(
+            |i, o| return run(i, o, &String::from("CLASSIC"), &String::from("on"), None),
+            "res/test/cyrillic.ans",
+            "res/test/cyrillic.svg",
+        );
+    }
+
     #[test]
     fn meta() -> Result<(), String> {
-        return test::file(|i, o| return run(i, o, &String::from("CLASSIC")), "res/test/meta.ans", "res/test/meta.svg");
+        return test::file(
+            |i, o| return run(i, o, &String::from("CLASSIC"), &String::from("on"), None),
+            "res/test/meta.ans",
+            "res/test/meta.svg",
+        );
     }
 
     #[test]
     fn notes() -> Result<(), String> {
         return test::file(
-            |i, o| return run(i, o, &String::from("CLASSIC")),
+            |i, o| return run(i, o, &String::from("CLASSIC"), &String::from("on"), None),
             "res/test/comments.ans",
             "res/test/comments.svg",
         );
@@ -272,21 +371,97 @@ mod tests {
     #[test]
     fn background() -> Result<(), String> {
         return test::file(
-            |i, o| return run(i, o, &String::from("CLASSIC")),
+            |i, o| return run(i, o, &String::from("CLASSIC"), &String::from("on"), None),
             "res/test/background.ans",
             "res/test/background.svg",
         );
     }
 
+    #[test]
+    fn xbin() -> Result<(), String> {
+        return test::file(
+            |i, o| return run(i, o, &String::from("CLASSIC"), &String::from("on"), None),
+            "res/test/xbin.xb",
+            "res/test/xbin.svg",
+        );
+    }
+
+    #[test]
+    fn binary_text() -> Result<(), String> {
+        return test::file(
+            |i, o| return run(i, o, &String::from("CLASSIC"), &String::from("on"), None),
+            "res/test/binary.bin",
+            "res/test/binary.svg",
+        );
+    }
+
+    #[test]
+    fn blink_on() -> Result<(), String> {
+        return test::file(
+            |i, o| return run(i, o, &String::from("CLASSIC"), &String::from("on"), None),
+            "res/test/blink.ans",
+            "res/test/blink_on.svg",
+        );
+    }
+
+    #[test]
+    fn blink_off() -> Result<(), String> {
+        return test::file(
+            |i, o| return run(i, o, &String::from("CLASSIC"), &String::from("off"), None),
+            "res/test/blink.ans",
+            "res/test/blink_off.svg",
+        );
+    }
+
+    #[test]
+    fn blink_animate() -> Result<(), String> {
+        return test::file(
+            |i, o| return run(i, o, &String::from("CLASSIC"), &String::from("animate"), None),
+            "res/test/blink.ans",
+            "res/test/blink_animate.svg",
+        );
+    }
+
+    #[test]
+    fn unknown_blink() -> Result<(), String> {
+        return test::err(
+            |i, o| return run(i, o, &String::from("CLASSIC"), &String::from("x"), None),
+            "res/test/blink.ans",
+            "Unknown blink mode: x",
+        );
+    }
+
+    #[test]
+    fn font() -> Result<(), String> {
+        return test::file(
+            |i, o| return run(i, o, &String::from("CLASSIC"), &String::from("on"), Some("res/test/font.psf")),
+            "res/test/simple.ans",
+            "res/test/font.svg",
+        );
+    }
+
+    #[test]
+    fn missing_font() -> Result<(), String> {
+        return test::err(
+            |i, o| return run(i, o, &String::from("CLASSIC"), &String::from("on"), Some("res/test/missing.psf")),
+            "res/test/simple.ans",
+            "Can't read font res/test/missing.psf: No such file or directory (os error 2)",
+        );
+    }
+
     #[test]
     fn logo() -> Result<(), String> {
-        return test::file(|i, o| return run(i, o, &String::from("CLASSIC")), "res/logo/logo.ans", "res/logo/logo.svg");
+        return test::file(
+            |i, o| return run(i, o, &String::from("CLASSIC"), &String::from("on"), None),
+            "res/logo/logo.ans",
+            "res/logo/logo.svg",
+        );
     }
 
     #[test]
     fn banner() -> Result<(), String> {
         return test::file(
-            |i, o| return run(i, o, &String::from("CLASSIC")),
+            |i, o| return run(i, o, &String::from("CLASSIC"), &String::from("on"), None),
             "res/banner/banner.ans",
             "res/banner/banner.svg",
         );
@@ -4,7 +4,7 @@ use std::{cmp::Ordering, env::args};
 
 use cp437_tools::{
     internal::{process, ExitCode, Input, Output},
-    prelude::{Meta, CP437_TO_UTF8},
+    prelude::{binary_text, Canvas, Cell, Colour},
 };
 
 #[allow(dead_code)]
@@ -33,9 +33,13 @@ pub fn exec(args: &[String]) -> ExitCode {
 #[allow(missing_docs, reason = "Just an entry point")]
 #[allow(clippy::missing_docs_in_private_items, reason = "Just an entry point")]
 pub fn run(input: &mut Input, output: &mut Output) -> ExitCode {
-    let meta = input.meta.clone().unwrap_or_else(|| {
-        return Meta { size: input.size, ..Default::default() };
-    });
+    let meta = input.effective_meta()?;
+    let chars = meta.code_page().table();
+
+    if binary_text::is_binary_text(&meta) {
+        render(&input.canvas()?, chars, output)?;
+        return output.write(b"\x1B[0m").map(|_| return ExitCode::OK)?;
+    }
 
     let mut control: Vec<u8> = vec![];
     let (mut x, mut y) = (0, 0);
@@ -45,7 +49,7 @@ pub fn run(input: &mut Input, output: &mut Output) -> ExitCode {
             return Ok(());
         }
 
-        output.write(String::from(CP437_TO_UTF8[if byte > 0 { byte as usize } else { 32 }]).as_bytes())?;
+        output.write(String::from(chars[if byte > 0 { byte as usize } else { 32 }]).as_bytes())?;
         if !control.is_empty() {
             if control.len() > 1 && (0x40..=0x7E).contains(&byte) {
                 control.clear();
@@ -72,6 +76,40 @@ pub fn run(input: &mut Input, output: &mut Output) -> ExitCode {
     return output.write(b"\x1B[0m").map(|_| return ExitCode::OK)?;
 }
 
+/// Write out a canvas in the given characters, setting the colours of each
+/// cell as needed.
+fn render(canvas: &Canvas, chars: &[char], output: &mut Output) -> Result<(), ExitCode> {
+    let (width, height) = canvas.dimensions();
+    for y in 0..height {
+        let mut previous = None;
+        for x in 0..width {
+            let cell = canvas.cell(x, y).copied().unwrap_or_default();
+            if previous != Some((cell.fg, cell.bg, cell.attributes)) {
+                output.write(sgr(&cell).as_bytes())?;
+                previous = Some((cell.fg, cell.bg, cell.attributes));
+            }
+            output.write(String::from(chars[if cell.byte > 0 { cell.byte as usize } else { 32 }]).as_bytes())?;
+        }
+        output.write(b"\x1B[0m\r\n")?;
+    }
+
+    return Ok(());
+}
+
+/// Build the SGR sequence that sets up the colours of a cell.
+fn sgr(cell: &Cell) -> String {
+    let colour = |colour: Colour, base: u8| {
+        return match colour {
+            Colour::Index(index @ 0..8) => format!("{}", base + index),
+            Colour::Index(index) => format!("{}", base + 60 + (index & 0x07)),
+            Colour::Rgb([r, g, b]) => format!("{};2;{r};{g};{b}", base + 8),
+        };
+    };
+    let blink = if cell.attributes.blink { ";5" } else { "" };
+
+    return format!("\x1B[0;{};{}{blink}m", colour(cell.fg, 30), colour(cell.bg, 40));
+}
+
 #[path = "."]
 #[cfg(test)]
 mod tests {
@@ -109,4 +147,14 @@ mod tests {
     fn background() -> Result<(), String> {
         return test::file(run, "res/test/background.ans", "res/test/background.txt");
     }
+
+    #[test]
+    fn code_page() -> Result<(), String> {
+        return test::file(run, "res/test/cyrillic.ans", "res/test/cyrillic.txt");
+    }
+
+    #[test]
+    fn binary_text() -> Result<(), String> {
+        return test::file(run, "res/test/binary.bin", "res/test/binary.txt");
+    }
 }
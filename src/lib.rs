@@ -22,12 +22,22 @@
 //! * **cp437-check-meta**
 //!
 //!   Reads a file's metadata and run some validations to see if there's any
-//!   issues with it.
+//!   issues with it, including whether it agrees with the actual contents.
+//!   Every issue is reported as an error, a warning, or just info, and
+//!   `--strict` makes warnings fail the check too.
+//!
+//! * **cp437-fix-meta**
+//!
+//!   Takes a file and repairs whatever it can of its metadata (illegal
+//!   characters, over-long strings, odd dates, reserved flags, the size),
+//!   piping the resulting file to stdout (or rewriting only the metadata in
+//!   place), and showing what changed.
 //!
 //! * **cp437-read-meta**
 //!
 //!   Reads and prints a file's metadata, highlighting values to show potential
 //!   errors, as well as showing the effective value when a real one is missing.
+//!   It can also print JSON, TOML, YAML, or shell variables, for scripts.
 //!
 //! * **cp437-remove-meta**
 //!
@@ -36,13 +46,26 @@
 //! * **cp437-set-meta**
 //!
 //!   Takes a file and modifies its metadata, piping the resulting file to
-//!   stdout.
+//!   stdout (or rewriting only the metadata in place).
 //!
 //!   If the file has no metadata, it will add one filled with default values,
-//!   and then proceed to add set the given field.
+//!   and then proceed to set the given fields, either from `key=value` pairs
+//!   or from a JSON or TOML document.
+//!
+//!   Characters that don't exist in CP437 can be replaced (or transliterated)
+//!   instead of failing, with `--encoding`.
 //!
 //! ## Rendering
 //!
+//! Files without metadata are drawn on a screen sized after their contents,
+//! never smaller than the usual 80x25.
+//!
+//! The images are drawn with the IBM VGA font by default, but any PSF, BDF, or
+//! raw bitmap font can be given instead with `--font`.
+//!
+//! Bytes are read in the code page named by the font in the file's metadata
+//! (such as `IBM VGA 866`), and CP437 otherwise.
+//!
 //! * **cp437-to-png**
 //!
 //!   Renders the given file as a PNG image, piping the resulting file to
@@ -88,20 +111,36 @@
 //!
 //! </div>
 //!
+//! Enabling the "serde" feature makes [`Meta`](meta::Meta), the types and
+//! flags it's made of, and [`ColourScheme`](colour::ColourScheme)
+//! (de)serialisable.
+//!
+//! Big files can be converted to and from UTF-8 a chunk at a time with
+//! [`Cp437Decoder`](stream::Cp437Decoder) and
+//! [`Cp437Encoder`](stream::Cp437Encoder).
+//!
 #![cfg_attr(all(),
     doc = ::embed_doc_image::embed_image!("logo", "res/logo/tiny.png"),
     doc = ::embed_doc_image::embed_image!("png", "res/screenshots/png.png"),
     doc = ::embed_doc_image::embed_image!("svg", "res/screenshots/svg.png"),
     doc = ::embed_doc_image::embed_image!("txt", "res/screenshots/txt.png"),
 )]
-#![cfg_attr(feature = "binaries", feature(try_trait_v2))] // TODO https://github.com/rust-lang/rust/issues/84277
+#![cfg_attr(feature = "binaries", feature(try_trait_v2, try_trait_v2_residual))] // TODO https://github.com/rust-lang/rust/issues/84277
 
 /// A list of things likely to be required by most dependents.
 pub mod prelude {
     pub use super::{
+        binary_text,
+        canvas::{Attributes, Canvas, CanvasError, Cell},
+        code_page::CodePage,
         colour::*,
         cp437::*,
-        meta::{self, Meta},
+        meta::{
+            self, AspectRatio, CheckError, DataType, FileType, FlagsError, Issue, LetterSpacing, Meta, MetaError,
+            Report, SauceFlags, Severity, TypeError, TypeInfo,
+        },
+        stream::{Cp437Decoder, Cp437Encoder},
+        xbin::{self, XBinError},
     };
 }
 
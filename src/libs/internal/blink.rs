@@ -0,0 +1,54 @@
+use crate::internal::ExitCode;
+
+/// How long blinking text stays on (and then off), in seconds, as a fraction.
+///
+/// VGA toggles it every 16 frames, at 70Hz.
+///
+pub const BLINK_DELAY: (u16, u16) = (16, 70);
+
+/// How to draw blinking text.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BlinkMode {
+    /// Always show it.
+    On,
+    /// Always hide it.
+    Off,
+    /// Alternate between showing and hiding it.
+    Animate,
+}
+
+impl BlinkMode {
+    pub fn get(name: &str) -> Result<BlinkMode, ExitCode> {
+        return match name.to_lowercase().as_str() {
+            "on" => Ok(BlinkMode::On),
+            "off" => Ok(BlinkMode::Off),
+            "animate" => Ok(BlinkMode::Animate),
+            _ => Err(ExitCode::USAGE(format!("Unknown blink mode: {name}"))),
+        };
+    }
+
+    /// Whether blinking text is visible, for each frame to be drawn.
+    #[must_use]
+    pub fn frames(self) -> &'static [bool] {
+        return match self {
+            BlinkMode::On => &[true],
+            BlinkMode::Off => &[false],
+            BlinkMode::Animate => &[true, false],
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn get() {
+        assert_eq!(BlinkMode::get("on"), Ok(BlinkMode::On));
+        assert_eq!(BlinkMode::get("OFF"), Ok(BlinkMode::Off));
+        assert_eq!(BlinkMode::get("Animate"), Ok(BlinkMode::Animate));
+        assert_eq!(BlinkMode::get("x"), Err(ExitCode::USAGE(String::from("Unknown blink mode: x"))));
+    }
+}
@@ -3,16 +3,20 @@ use std::{
     io,
     num::ParseIntError,
     num::TryFromIntError,
-    ops::{ControlFlow, FromResidual, Try},
+    ops::{ControlFlow, FromResidual, Residual, Try},
     process::{ExitCode as StdExitCode, Termination},
     ptr,
     string::FromUtf8Error,
 };
 
 #[cfg(feature = "binaries")]
-use png::EncodingError;
+use png::EncodingError as PngError;
 
-use crate::internal::help;
+use crate::{
+    fonts::FontError,
+    internal::help,
+    prelude::{CanvasError, CheckError, EncodingError, FlagsError, MetaError, SchemeError, TypeError, XBinError},
+};
 
 #[repr(u8)]
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -148,10 +152,77 @@ impl From<ParseIntError> for ExitCode {
 }
 
 #[cfg(feature = "binaries")]
+impl From<PngError> for ExitCode {
+    #[inline]
+    fn from(err: PngError) -> ExitCode {
+        return ExitCode::ERROR(err.to_string());
+    }
+}
+
+impl From<MetaError> for ExitCode {
+    #[inline]
+    fn from(err: MetaError) -> ExitCode {
+        return match err {
+            MetaError::Io(err) => ExitCode::from(err),
+            MetaError::Encoding(err) => ExitCode::from(err),
+            MetaError::Check(err) => ExitCode::from(err),
+        };
+    }
+}
+
+impl From<CheckError> for ExitCode {
+    #[inline]
+    fn from(err: CheckError) -> ExitCode {
+        return ExitCode::FAIL(err.to_string());
+    }
+}
+
 impl From<EncodingError> for ExitCode {
     #[inline]
     fn from(err: EncodingError) -> ExitCode {
-        return ExitCode::ERROR(err.to_string());
+        return ExitCode::FAIL(err.to_string());
+    }
+}
+
+impl From<XBinError> for ExitCode {
+    #[inline]
+    fn from(err: XBinError) -> ExitCode {
+        return ExitCode::FAIL(err.to_string());
+    }
+}
+
+impl From<FontError> for ExitCode {
+    #[inline]
+    fn from(err: FontError) -> ExitCode {
+        return ExitCode::FAIL(err.to_string());
+    }
+}
+
+impl From<CanvasError> for ExitCode {
+    #[inline]
+    fn from(err: CanvasError) -> ExitCode {
+        return ExitCode::FAIL(err.to_string());
+    }
+}
+
+impl From<SchemeError> for ExitCode {
+    #[inline]
+    fn from(err: SchemeError) -> ExitCode {
+        return ExitCode::USAGE(err.to_string());
+    }
+}
+
+impl From<TypeError> for ExitCode {
+    #[inline]
+    fn from(err: TypeError) -> ExitCode {
+        return ExitCode::USAGE(err.to_string());
+    }
+}
+
+impl From<FlagsError> for ExitCode {
+    #[inline]
+    fn from(err: FlagsError) -> ExitCode {
+        return ExitCode::USAGE(err.to_string());
     }
 }
 
@@ -195,6 +266,10 @@ where
     }
 }
 
+impl Residual<ExitCode> for ExitCode {
+    type TryType = ExitCode;
+}
+
 impl Try for ExitCode {
     type Output = Self;
     type Residual = Self;
@@ -226,6 +301,11 @@ mod tests {
 
     use pretty_assertions::assert_eq;
 
+    use crate::prelude::{
+        meta::{Field, Reason},
+        CodePage,
+    };
+
     const MSG: &str = "foo";
 
     #[test]
@@ -303,6 +383,58 @@ mod tests {
         assert_eq!(ExitCode::from(io_err()), err());
     }
 
+    #[test]
+    fn from_meta_io_error() {
+        assert_eq!(ExitCode::from(MetaError::Io(io_err())), err());
+    }
+
+    #[test]
+    fn from_check_error() {
+        assert_eq!(ExitCode::from(Reason::Invalid.at(Field::Flags)), ExitCode::FAIL(String::from("Invalid flags")),);
+    }
+
+    #[test]
+    fn from_encoding_error() {
+        assert_eq!(
+            ExitCode::from(EncodingError { r#char: '🚫', position: 0, code_page: CodePage::Cp437 }),
+            ExitCode::FAIL(String::from("🚫 (U+1F6AB) is not a valid CP437 character")),
+        );
+    }
+
+    #[test]
+    fn from_font_error() {
+        assert_eq!(
+            ExitCode::from(FontError::MissingGlyph(0x41)),
+            ExitCode::FAIL(String::from("Glyph for 0x41 is missing"))
+        );
+    }
+
+    #[test]
+    fn from_canvas_error() {
+        assert_eq!(
+            ExitCode::from(CanvasError::InvalidParam(String::from(MSG))),
+            ExitCode::FAIL(String::from("Invalid control sequence param: foo")),
+        );
+    }
+
+    #[test]
+    fn from_scheme_error() {
+        assert_eq!(
+            ExitCode::from(SchemeError::Unknown(String::from(MSG))),
+            ExitCode::USAGE(String::from("Unknown scheme: foo"))
+        );
+    }
+
+    #[test]
+    fn from_flags_error() {
+        assert_eq!(ExitCode::from(FlagsError(String::from(MSG))), ExitCode::USAGE(String::from("Unknown flag: foo")));
+    }
+
+    #[test]
+    fn from_type_error() {
+        assert_eq!(ExitCode::from(TypeError(String::from(MSG))), ExitCode::USAGE(String::from("Unknown type: foo")));
+    }
+
     #[test]
     fn from_residual_error() {
         assert_eq!(ExitCode::from_residual(err()), err());
@@ -2,10 +2,11 @@
 
 #![doc(hidden)]
 
+pub mod blink;
 pub mod escape;
 pub mod exit;
 pub mod help;
 pub mod process;
 
 #[doc(hidden)]
-pub use self::{escape::*, exit::*, process::*};
+pub use self::{blink::*, escape::*, exit::*, process::*};
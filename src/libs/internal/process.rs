@@ -1,42 +1,84 @@
 use std::{
     cmp::min,
     fs::File,
-    io::{self, stdout, BufReader, Read as _, Seek as _, Write},
+    io::{self, stdin, stdout, BufReader, Cursor, Read, Seek, SeekFrom, Write},
     path::Path,
 };
 
 use crate::{
     internal::ExitCode,
     prelude::{
+        binary_text,
         meta::{self, Meta},
-        ColourScheme,
+        xbin, Canvas,
     },
 };
 
+/// Anything the input can be read back from.
+trait Source: Read + Seek {}
+
+impl<T: Read + Seek> Source for T {}
+
 pub struct Input {
-    real: File,
+    real: Box<dyn Source>,
     pub size: u32,
     pub meta: Option<Meta>,
 }
 
-struct Colour {
-    bg: [u8; 3],
-    fg: [u8; 3],
-    bright: bool,
-}
-
 impl Input {
+    /// Open a file, or stdin if given `-`.
     pub fn new<P: AsRef<Path>>(input: P) -> Result<Self, ExitCode> {
-        let mut real = File::open(input)?;
+        return if input.as_ref() == Path::new("-") { Self::stdin() } else { Self::file(input) };
+    }
+
+    pub fn file<P: AsRef<Path>>(input: P) -> Result<Self, ExitCode> {
+        return Self::seekable(File::open(input)?);
+    }
+
+    /// Stdin can't be seeked, so it gets buffered into memory first.
+    pub fn stdin() -> Result<Self, ExitCode> {
+        return Self::buffer(stdin().lock());
+    }
+
+    pub fn buffer<R: Read>(mut reader: R) -> Result<Self, ExitCode> {
+        let mut buffer = vec![];
+        reader.read_to_end(&mut buffer)?;
 
+        return Self::seekable(Cursor::new(buffer));
+    }
+
+    /// Wrap a seekable source.
+    fn seekable<R: Read + Seek + 'static>(mut real: R) -> Result<Self, ExitCode> {
         let meta = meta::read(&mut real)?;
 
         let size = match meta {
             Some(ref meta) => meta.size,
-            None => u32::try_from(real.metadata()?.len())?,
+            None => u32::try_from(real.seek(SeekFrom::End(0))?)?,
         };
 
-        return Ok(Self { real, size, meta });
+        return Ok(Self { real: Box::new(real), size, meta });
+    }
+
+    /// Read the whole input, metadata included.
+    pub fn bytes(&mut self) -> Result<Vec<u8>, ExitCode> {
+        self.real.rewind()?;
+        let mut bytes = vec![];
+        self.real.read_to_end(&mut bytes)?;
+
+        return Ok(bytes);
+    }
+
+    /// Read the contents, sans metadata.
+    fn data(&mut self) -> Result<Vec<u8>, ExitCode> {
+        // The size comes from the metadata, so it can't be trusted to fit
+        let length = self.real.seek(SeekFrom::End(0))?;
+        let mut bytes = Vec::with_capacity(usize::try_from(min(u64::from(self.size), length))?);
+        self.read_by_chunks(|chunk| {
+            bytes.extend_from_slice(chunk);
+            return Ok(());
+        })?;
+
+        return Ok(bytes);
     }
 
     pub fn read_by_chunks<'a, F: for<'b> FnMut(&'b [u8]) -> Result<(), ExitCode> + 'a>(
@@ -46,11 +88,15 @@ impl Input {
         self.real.rewind()?;
 
         let mut chunk = vec![0; 1 << 12]; // 4k chunks
-        let mut reader = BufReader::with_capacity(chunk.len(), &self.real);
+        let mut reader = BufReader::with_capacity(chunk.len(), &mut self.real);
 
         let mut index = 0;
         while index < self.size {
             let count = u32::try_from(reader.read(&mut chunk)?)?;
+            if count == 0 {
+                // The file is shorter than its metadata says
+                break;
+            }
             let count = min(count, self.size.saturating_sub(index));
             index += count;
             callback(&chunk[..count as usize])?;
@@ -73,116 +119,46 @@ impl Input {
         });
     }
 
-    #[inline]
-    pub fn read_by_bytes_full<'a, F: for<'b> FnMut(u8, (u16, u16), [[u8; 3]; 2]) -> Result<(), ExitCode> + 'a>(
-        &mut self,
-        mut callback: F,
-        scheme: &String,
-    ) -> Result<(), ExitCode> {
-        let meta = self.meta.clone().unwrap_or_else(|| {
-            return Meta { size: self.size, ..Default::default() };
-        });
-        let colours = ColourScheme::get(scheme)?.colours();
-        let mut colour = Colour { bg: colours[0], fg: colours[15], bright: false };
-        let mut control: Vec<u8> = vec![];
-        let (mut x, mut y) = (0, 0);
-
-        return self.read_by_bytes(|byte| {
-            if y >= meta.height() {
-                return Ok(());
-            }
+    /// The file's metadata or, when there's none, an estimate based on its
+    /// contents.
+    ///
+    /// The estimate is never smaller than the default screen, so it only ever
+    /// makes room for contents that wouldn't fit otherwise.
+    ///
+    pub fn effective_meta(&mut self) -> Result<Meta, ExitCode> {
+        if let Some(meta) = &self.meta {
+            return Ok(meta.clone());
+        }
 
-            if !control.is_empty() {
-                if byte == b'm' {
-                    for mut num in control[2..].split(|r#char| return *r#char == b';') {
-                        if num.is_empty() {
-                            num = b"0";
-                        }
-                        let num = String::from_utf8(num.to_vec())?.parse::<usize>()?;
-                        match num {
-                            0 => {
-                                colour.bg = colours[0];
-                                colour.fg = colours[15];
-                                colour.bright = false;
-                            },
-                            1 => {
-                                colour.bright = true;
-                            },
-                            30..=37 => {
-                                colour.fg = colours[num - 30 + (if colour.bright { 8 } else { 0 })];
-                            },
-                            39 => {
-                                colour.fg = colours[15];
-                            },
-                            40..=47 => {
-                                colour.bg = colours[num - 40];
-                            },
-                            49 => {
-                                colour.bg = colours[0];
-                            },
-                            90..=97 => {
-                                colour.fg = colours[num - 82];
-                            },
-                            100..=107 => {
-                                colour.bg = colours[num - 92];
-                            },
-                            _ => {
-                                eprintln!("\x1B[33mWARN: Unknown SGR param: {num}\x1B[0m");
-                            },
-                        }
-                    }
-                    control.clear();
-                } else if byte == b't' {
-                    let cmd = control[2..].split(|r#char| return *r#char == b';').collect::<Vec<&[u8]>>();
-                    let r = String::from_utf8(cmd[1].to_vec())?.parse::<u8>()?;
-                    let g = String::from_utf8(cmd[2].to_vec())?.parse::<u8>()?;
-                    let b = String::from_utf8(cmd[3].to_vec())?.parse::<u8>()?;
-                    match cmd[0] {
-                        b"0" => {
-                            colour.bg = [r, g, b];
-                        },
-                        b"1" => {
-                            colour.fg = [r, g, b];
-                        },
-                        _ => {
-                            eprintln!(
-                                "\x1B[33mWARN: Invalid RGB target: {}\x1B[0m",
-                                String::from_utf8(cmd[0].to_vec())?
-                            );
-                        },
-                    }
-                    control.clear();
-                } else if byte == b'B' {
-                    y += String::from_utf8(control[2..].to_vec())?.parse::<u16>()?;
-                    control.clear();
-                } else if byte == b'C' {
-                    x = min(x + String::from_utf8(control[2..].to_vec())?.parse::<u16>()?, meta.width() - 1);
-                    control.clear();
-                } else if control.len() > 1 && (0x40..=0x7E).contains(&byte) {
-                    eprintln!("\x1B[33mWARN: Invalid control sequence argument: 0x{byte:02X}\x1B[0m");
-                    control.clear();
-                } else {
-                    control.push(byte);
-                }
-            } else if byte == 0x1B {
-                control.push(byte);
-            } else if byte == 0x0D {
-                (x, y) = (0, y);
-            } else if byte == 0x0A {
-                (x, y) = (0, y + 1);
-            } else {
-                callback(byte, (x, y), [colour.bg, colour.fg])?;
-                x += 1;
-                if x >= meta.width() {
-                    (x, y) = (0, y + 1);
-                }
-            }
+        return Ok(screen(&self.data()?));
+    }
 
-            return Ok(());
-        });
+    pub fn canvas(&mut self) -> Result<Canvas, ExitCode> {
+        let bytes = self.data()?;
+        let meta = self.meta.clone().unwrap_or_else(|| return screen(&bytes));
+
+        let canvas = if xbin::is_xbin(&bytes) {
+            xbin::read(&bytes)?
+        } else if binary_text::is_binary_text(&meta) {
+            binary_text::read(&bytes, &meta)
+        } else {
+            Canvas::from_bytes(&bytes, &meta)?
+        };
+
+        for warning in canvas.warnings() {
+            eprintln!("\x1B[33mWARN: {warning}\x1B[0m");
+        }
+
+        return Ok(canvas);
     }
 }
 
+/// Estimate the metadata of some contents, growing the default screen to fit.
+fn screen(bytes: &[u8]) -> Meta {
+    let (default, estimate) = (Meta::default(), meta::estimate(bytes));
+    return Meta { width: estimate.width.max(default.width), height: estimate.height.max(default.height), ..estimate };
+}
+
 pub struct Output {
     real: Box<dyn Write>,
 }
@@ -217,3 +193,113 @@ impl Write for Output {
 pub fn process<F: for<'a> FnOnce(&'a mut Input, &'a mut Output) -> ExitCode>(input: &String, callback: F) -> ExitCode {
     return callback(&mut Input::new(input)?, &mut Output::stdout()?);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+    use std::{
+        env::current_exe,
+        fs::read,
+        process::{Command, Stdio},
+    };
+
+    use crate::prelude::meta::FileType;
+
+    #[test]
+    fn buffer() -> Result<(), String> {
+        let file = Input::file("res/test/meta.ans")?;
+        let mut input = Input::buffer(read("res/test/meta.ans").map_err(|err| return err.to_string())?.as_slice())?;
+        assert_eq!(input.size, file.size);
+        assert_eq!(input.meta, file.meta);
+
+        let mut bytes = vec![];
+        input.read_by_chunks(|chunk| {
+            bytes.extend_from_slice(chunk);
+            return Ok(());
+        })?;
+        assert_eq!(bytes.len(), input.size as usize);
+
+        return Ok(());
+    }
+
+    #[test]
+    fn buffer_no_meta() -> Result<(), String> {
+        let input = Input::buffer(b"Hello".as_slice())?;
+        assert_eq!(input.size, 5);
+        assert_eq!(input.meta, None);
+
+        return Ok(());
+    }
+
+    #[test]
+    fn truncated() -> Result<(), String> {
+        let mut input = Input::file("res/test/truncated.ans")?;
+        assert_eq!(input.size, 1000);
+        assert_eq!(input.data()?.len(), 136);
+        assert_eq!(input.canvas()?.cell(0, 0).map(|cell| return cell.byte), Some(b'H'));
+
+        return Ok(());
+    }
+
+    #[test]
+    fn effective_meta() -> Result<(), String> {
+        let mut input = Input::file("res/test/meta.ans")?;
+        assert_eq!(Some(input.effective_meta()?), input.meta);
+
+        let mut input = Input::buffer(b"Hello".as_slice())?;
+        assert_eq!(
+            input.effective_meta()?,
+            Meta { size: 5, r#type: FileType::Ascii, width: 80, height: 25, ..Default::default() },
+        );
+
+        let mut input = Input::buffer("X\r\n".repeat(30).as_bytes())?;
+        assert_eq!((input.effective_meta()?.width, input.effective_meta()?.height), (80, 30));
+
+        let mut input = Input::buffer(format!("{}\r\n", "X".repeat(100)).as_bytes())?;
+        assert_eq!((input.effective_meta()?.width, input.effective_meta()?.height), (100, 25));
+
+        return Ok(());
+    }
+
+    #[test]
+    fn canvas_estimate() -> Result<(), String> {
+        let mut input = Input::buffer("X\r\n".repeat(30).as_bytes())?;
+        let canvas = input.canvas()?;
+        assert_eq!(canvas.dimensions(), (80, 30));
+        assert!(canvas.cell(0, 29).is_some());
+
+        return Ok(());
+    }
+
+    #[test]
+    fn stdin() -> Result<(), String> {
+        let mut child = Command::new(current_exe().map_err(|err| return err.to_string())?)
+            .args(["--exact", "--ignored", "internal::process::tests::piped"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()
+            .map_err(|err| return err.to_string())?;
+        child
+            .stdin
+            .take()
+            .expect("Stdin is piped")
+            .write_all(&read("res/test/meta.ans").map_err(|err| return err.to_string())?)
+            .map_err(|err| return err.to_string())?;
+        assert!(child.wait().map_err(|err| return err.to_string())?.success());
+
+        return Ok(());
+    }
+
+    #[test]
+    #[ignore = "Reads res/test/meta.ans from stdin, run by the stdin test"]
+    fn piped() -> ExitCode {
+        let file = Input::file("res/test/meta.ans")?;
+        return process(&String::from("-"), |input, _| {
+            assert_eq!(input.size, file.size);
+            assert_eq!(input.meta, file.meta);
+            return ExitCode::OK;
+        });
+    }
+}
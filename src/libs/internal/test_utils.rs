@@ -76,7 +76,7 @@ pub fn file_meta<F: for<'a> FnOnce(&'a mut Input, &'a mut Output) -> ExitCode>(
 
     assert!(callback(&mut Input::new(&String::from(input))?, &mut Output::file(&target)?).is_ok());
     assert!(tmp_dir.path().join("output.txt").exists());
-    assert_eq!(meta::get(&target)?, output);
+    assert_eq!(meta::get(&target).map_err(|err| return err.to_string())?, output);
 
     tmp_dir.close().map_err(|err| return err.to_string())?;
 
@@ -0,0 +1,144 @@
+//! BinaryText images.
+//!
+//! A BinaryText file is nothing but character/attribute pairs, as they'd be
+//! laid out in the VGA's text mode memory. There's no header, so the width
+//! comes from the metadata instead, where the file type is half of it.
+//!
+//! See <https://web.archive.org/web/20250427042053id_/https://www.acid.org/info/sauce/sauce.htm#FileType>
+//!
+
+use crate::prelude::{Canvas, Cell, FileType, Meta};
+
+/// Width assumed when the metadata doesn't define one.
+pub const DEFAULT_WIDTH: u16 = 160;
+
+/// Check whether some metadata marks a file as BinaryText.
+///
+/// # Arguments
+///
+/// * `meta`: The metadata to check.
+///
+#[inline]
+#[must_use]
+pub fn is_binary_text(meta: &Meta) -> bool {
+    return matches!(meta.r#type, FileType::BinaryText(_));
+}
+
+/// Get the width of a BinaryText image, in characters.
+///
+/// # Arguments
+///
+/// * `meta`: The metadata of the file.
+///
+#[inline]
+#[must_use]
+pub fn width(meta: &Meta) -> u16 {
+    return match meta.r#type {
+        FileType::BinaryText(half_width) if half_width > 0 => u16::from(half_width) * 2,
+        _ => DEFAULT_WIDTH,
+    };
+}
+
+/// Decode a BinaryText file into a canvas.
+///
+/// The height is however many rows the contents fill up.
+///
+/// # Arguments
+///
+/// * `bytes`: The contents of the file, sans metadata.
+/// * `meta`: The metadata of the file.
+///
+#[must_use]
+pub fn read(bytes: &[u8], meta: &Meta) -> Canvas {
+    let width = width(meta);
+    let height = u16::try_from(bytes.chunks_exact(2).len().div_ceil(usize::from(width))).unwrap_or(u16::MAX);
+    let ice = meta.flags().ice_colours;
+
+    let mut canvas = Canvas::new(&Meta { width, height, ..Default::default() });
+    if meta.r#type == FileType::BinaryText(0) {
+        canvas.warn(format!("BinaryText width is missing, assuming {DEFAULT_WIDTH}"));
+    }
+    if bytes.len() % 2 != 0 {
+        canvas.warn(String::from("BinaryText has a trailing byte"));
+    }
+
+    for (pair, i) in bytes.chunks_exact(2).zip(0_usize..) {
+        #[expect(clippy::integer_division, reason = "Intentional")]
+        let (x, y) = (i % usize::from(width), i / usize::from(width));
+        if let (Ok(x), Ok(y)) = (u16::try_from(x), u16::try_from(y)) {
+            canvas.set(x, y, Cell::from_attribute(pair[0], pair[1], ice));
+        }
+    }
+
+    return canvas;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+    use std::fs::read as read_file;
+
+    use crate::prelude::{meta, Attributes, Colour};
+
+    #[test]
+    fn width_from_type() {
+        assert_eq!(width(&Meta { r#type: FileType::BinaryText(40), ..Default::default() }), 80);
+        assert_eq!(width(&Meta { r#type: FileType::BinaryText(4), ..Default::default() }), 8);
+        assert_eq!(width(&Meta { r#type: FileType::BinaryText(0), ..Default::default() }), DEFAULT_WIDTH);
+    }
+
+    #[test]
+    fn file() -> Result<(), String> {
+        let bytes = read_file("res/test/binary.bin").map_err(|err| return err.to_string())?;
+        let (meta, range) = meta::parse(&bytes).map_err(|err| return err.to_string())?;
+        let meta = meta.expect("Has metadata");
+        assert!(is_binary_text(&meta));
+
+        let canvas = read(&bytes[range], &meta);
+        assert_eq!(canvas.dimensions(), (8, 3));
+        assert_eq!(canvas.cells().count(), 24);
+        assert!(canvas.warnings().is_empty());
+        assert_eq!(
+            canvas.cell(0, 0),
+            Some(&Cell {
+                byte: b'B',
+                fg: Colour::Index(15),
+                bg: Colour::Index(4),
+                attributes: Attributes { bold: true, blink: false },
+            }),
+        );
+        assert_eq!(canvas.cell(7, 2).map(|cell| return cell.bg), Some(Colour::Index(15)));
+
+        return Ok(());
+    }
+
+    #[test]
+    fn partial_row() {
+        let canvas = read(b"A\x07B\x07C", &Meta { r#type: FileType::BinaryText(1), ..Default::default() });
+        assert_eq!(canvas.dimensions(), (2, 1));
+        assert_eq!(canvas.cells().count(), 2);
+        assert_eq!(canvas.warnings(), &[String::from("BinaryText has a trailing byte")]);
+    }
+
+    #[test]
+    fn blink() {
+        let canvas = read(b"A\x9C", &Meta { r#type: FileType::BinaryText(1), flags: 0x00, ..Default::default() });
+        assert_eq!(
+            canvas.cell(0, 0),
+            Some(&Cell {
+                byte: b'A',
+                fg: Colour::Index(9),
+                bg: Colour::Index(4),
+                attributes: Attributes { bold: true, blink: true },
+            }),
+        );
+    }
+
+    #[test]
+    fn missing_width() {
+        let canvas = read(b"", &Meta { r#type: FileType::BinaryText(0), ..Default::default() });
+        assert_eq!(canvas.warnings(), &[format!("BinaryText width is missing, assuming {DEFAULT_WIDTH}")]);
+    }
+}
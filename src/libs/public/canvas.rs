@@ -0,0 +1,856 @@
+//! A virtual screen to interpret files into.
+
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    iter::repeat,
+    ops::Range,
+};
+
+use crate::{
+    fonts::BitmapFont,
+    prelude::{vga_to_ansi, Colour, ColourScheme, Meta},
+};
+
+/// Something went wrong while interpreting the contents into a canvas.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CanvasError {
+    /// A control sequence has a parameter that isn't a number, or that is out
+    /// of range.
+    InvalidParam(String),
+}
+
+impl Display for CanvasError {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        return match self {
+            CanvasError::InvalidParam(param) => write!(f, "Invalid control sequence param: {param}"),
+        };
+    }
+}
+
+impl Error for CanvasError {}
+
+/// Display attributes of a single cell.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Attributes {
+    /// Whether the text was set to bold (AKA bright).
+    pub bold: bool,
+    /// Whether the text was set to blink.
+    ///
+    /// With iCE colours this brightens the background instead, so it's never
+    /// set on the cells of such canvases.
+    ///
+    pub blink: bool,
+}
+
+/// A single character cell.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Cell {
+    /// The CP437 character.
+    pub byte: u8,
+    /// The foreground colour.
+    pub fg: Colour,
+    /// The background colour.
+    pub bg: Colour,
+    /// The display attributes.
+    pub attributes: Attributes,
+}
+
+impl Default for Cell {
+    /// A blank cell, with the default colours.
+    fn default() -> Cell {
+        return Cell { byte: b' ', fg: Colour::Index(15), bg: Colour::Index(0), attributes: Attributes::default() };
+    }
+}
+
+impl Cell {
+    /// Build a cell out of a character and a VGA attribute byte.
+    ///
+    /// The low nibble of the attribute is the foreground, and the high nibble
+    /// is the background, both in VGA order (see [`vga_to_ansi`]). Without
+    /// iCE colours, the high background bit means blink instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `byte`: The CP437 character.
+    /// * `attribute`: The attribute byte.
+    /// * `ice`: Whether iCE colours are enabled.
+    ///
+    #[must_use]
+    pub fn from_attribute(byte: u8, attribute: u8, ice: bool) -> Cell {
+        let (fg, bg) = (attribute & 0x0F, attribute >> 4);
+        let (bg, blink) = if ice { (bg, false) } else { (bg & 0x07, bg & 0x08 != 0) };
+
+        return Cell {
+            byte,
+            fg: Colour::Index(vga_to_ansi(fg)),
+            bg: Colour::Index(vga_to_ansi(bg)),
+            attributes: Attributes { bold: fg & 0x08 != 0, blink },
+        };
+    }
+}
+
+/// How much room the contents written into a canvas take up, whether they
+/// fit in it or not.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(crate) struct Extent {
+    /// The widest line.
+    pub width: u16,
+    /// How many rows there are.
+    pub height: u16,
+    /// The furthest column the cursor was explicitly moved to, counting from
+    /// 1.
+    pub reach: u16,
+    /// Whether there's explicit line breaks, as opposed to relying on lines
+    /// wrapping by themselves.
+    pub breaks: bool,
+    /// Whether there's any escape sequences.
+    pub escapes: bool,
+}
+
+/// A grid of cells, as a DOS terminal would display them.
+///
+/// Writing to a canvas interprets ANSI escape sequences, so the cursor can be
+/// moved around and anything already written can be overdrawn.
+///
+/// Cells that were never written to are left empty, which is different from
+/// them being blank. Rows are only allocated once the cursor reaches them, so
+/// that a bogus height doesn't take up any memory.
+///
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Canvas {
+    /// Width, in characters.
+    width: u16,
+    /// Height, in characters.
+    height: u16,
+    /// All cells, row by row, up to the furthest row reached so far.
+    cells: Vec<Option<Cell>>,
+    /// Where the next character will be written.
+    cursor: (u16, u16),
+    /// A cursor position saved for later.
+    saved: (u16, u16),
+    /// Colours and attributes for the next character.
+    pen: Cell,
+    /// An unfinished escape sequence.
+    control: Vec<u8>,
+    /// Issues found while interpreting the input.
+    warnings: Vec<String>,
+    /// Colours provided by the file itself.
+    palette: Option<ColourScheme>,
+    /// Font provided by the file itself.
+    font: Option<BitmapFont>,
+    /// Whether iCE colours are enabled, as opposed to blink mode.
+    ice: bool,
+    /// How much room everything written so far takes up.
+    extent: Extent,
+}
+
+impl Canvas {
+    /// Create an empty canvas.
+    ///
+    /// # Arguments
+    ///
+    /// * `meta`: The metadata that defines the canvas' dimensions, and whether
+    ///   iCE colours are enabled.
+    ///
+    #[must_use]
+    pub fn new(meta: &Meta) -> Canvas {
+        return Canvas::sized(meta.dimensions(), meta.flags().ice_colours);
+    }
+
+    /// Create an empty canvas of any size, even an empty one.
+    fn sized((width, height): (u16, u16), ice: bool) -> Canvas {
+        return Canvas {
+            width,
+            height,
+            cells: vec![],
+            cursor: (0, 0),
+            saved: (0, 0),
+            pen: Cell::default(),
+            control: vec![],
+            warnings: vec![],
+            palette: None,
+            font: None,
+            ice,
+            extent: Extent::default(),
+        };
+    }
+
+    /// Measure how much room some contents take up, following the cursor
+    /// around without drawing anything.
+    ///
+    /// Lines wrap once they fill the given width, same as they'd do when
+    /// drawing them, so the measured width can't ever exceed it. Contents that
+    /// are too wide end up with too many rows instead.
+    ///
+    pub(crate) fn measure(bytes: &[u8], width: u16) -> Extent {
+        let mut canvas = Canvas::sized((width, 0), true);
+        for byte in bytes {
+            // A sequence that can't be parsed never moves the cursor, so just
+            // carry on
+            canvas.write_byte(*byte).unwrap_or_default();
+        }
+
+        return canvas.extent;
+    }
+
+    /// Create a canvas and write some contents into it.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes`: The contents of the file, sans metadata.
+    /// * `meta`: The metadata that defines the canvas' dimensions.
+    ///
+    /// # Errors
+    ///
+    /// Fails when there's an unparseable escape sequence.
+    ///
+    pub fn from_bytes(bytes: &[u8], meta: &Meta) -> Result<Canvas, CanvasError> {
+        let mut canvas = Canvas::new(meta);
+        canvas.write(bytes)?;
+
+        return Ok(canvas);
+    }
+
+    /// Width, in characters.
+    #[inline]
+    #[must_use]
+    pub fn width(&self) -> u16 {
+        return self.width;
+    }
+
+    /// Height, in characters.
+    #[inline]
+    #[must_use]
+    pub fn height(&self) -> u16 {
+        return self.height;
+    }
+
+    /// Get both the width and the height.
+    #[inline]
+    #[must_use]
+    pub fn dimensions(&self) -> (u16, u16) {
+        return (self.width, self.height);
+    }
+
+    /// Get a single cell, if it was written to.
+    ///
+    /// # Arguments
+    ///
+    /// * `x`: The column.
+    /// * `y`: The row.
+    ///
+    #[must_use]
+    pub fn cell(&self, x: u16, y: u16) -> Option<&Cell> {
+        return if x < self.width && y < self.height { self.cells.get(self.index((x, y)))?.as_ref() } else { None };
+    }
+
+    /// Iterate over all rows, top to bottom.
+    ///
+    /// Rows past the furthest one reached are empty slices.
+    ///
+    pub fn rows(&self) -> impl Iterator<Item = &[Option<Cell>]> {
+        let rows = self.cells.chunks(usize::from(self.width));
+        let missing = usize::from(self.height) - rows.len();
+        return rows.chain(repeat(&[] as &[Option<Cell>]).take(missing));
+    }
+
+    /// Iterate over all written cells along with their positions, row by row.
+    pub fn cells(&self) -> impl Iterator<Item = ((u16, u16), &Cell)> {
+        return self.rows().zip(0..).flat_map(|(row, y)| {
+            return row
+                .iter()
+                .zip(0..)
+                .filter_map(move |(cell, x)| return cell.as_ref().map(|cell| return ((x, y), cell)));
+        });
+    }
+
+    /// Overwrite a single cell.
+    ///
+    /// Out of bounds positions are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `x`: The column.
+    /// * `y`: The row.
+    /// * `cell`: The new contents of the cell.
+    ///
+    pub fn set(&mut self, x: u16, y: u16, cell: Cell) {
+        if x < self.width && y < self.height {
+            self.grow(y);
+            let index = self.index((x, y));
+            self.cells[index] = Some(cell);
+        }
+    }
+
+    /// The colours the file asks to be drawn with, if any.
+    ///
+    /// When present, these take precedence over any user provided scheme.
+    ///
+    #[inline]
+    #[must_use]
+    pub fn palette(&self) -> Option<&ColourScheme> {
+        return self.palette.as_ref();
+    }
+
+    /// Set the colours this canvas should be drawn with.
+    ///
+    /// # Arguments
+    ///
+    /// * `palette`: The colours, or `None` to use the user's choice.
+    ///
+    #[inline]
+    pub fn set_palette(&mut self, palette: Option<ColourScheme>) {
+        self.palette = palette;
+    }
+
+    /// The font the file asks to be drawn with, if any.
+    #[inline]
+    #[must_use]
+    pub fn font(&self) -> Option<&BitmapFont> {
+        return self.font.as_ref();
+    }
+
+    /// Set the font this canvas should be drawn with.
+    ///
+    /// # Arguments
+    ///
+    /// * `font`: The font, or `None` to use the one from the metadata.
+    ///
+    #[inline]
+    pub fn set_font(&mut self, font: Option<BitmapFont>) {
+        self.font = font;
+    }
+
+    /// Record an issue that doesn't prevent the canvas from being drawn.
+    pub(crate) fn warn(&mut self, warning: String) {
+        self.warnings.push(warning);
+    }
+
+    /// Issues found so far while interpreting the contents.
+    ///
+    /// These don't prevent the canvas from being drawn, but the result may be
+    /// off.
+    ///
+    #[inline]
+    #[must_use]
+    pub fn warnings(&self) -> &[String] {
+        return &self.warnings;
+    }
+
+    /// Write some contents into the canvas.
+    ///
+    /// This can be called repeatedly to process a file in chunks, since escape
+    /// sequences may be split across calls.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes`: A chunk of the contents of the file.
+    ///
+    /// # Errors
+    ///
+    /// Fails when there's an unparseable escape sequence.
+    ///
+    pub fn write(&mut self, bytes: &[u8]) -> Result<(), CanvasError> {
+        for byte in bytes {
+            self.write_byte(*byte)?;
+        }
+
+        return Ok(());
+    }
+
+    /// Interpret a single byte.
+    fn write_byte(&mut self, byte: u8) -> Result<(), CanvasError> {
+        if !self.control.is_empty() {
+            if self.control.len() > 1 && (0x40..=0x7E).contains(&byte) {
+                let control = self.control.split_off(0);
+                self.csi(byte, &control[2..])?;
+            } else {
+                self.control.push(byte);
+            }
+        } else if byte == 0x1B {
+            self.control.push(byte);
+        } else if byte == 0x0D {
+            self.move_to((0, self.cursor.1));
+        } else if byte == 0x0A {
+            self.move_to((0, self.cursor.1.saturating_add(1)));
+            self.extent.breaks = true;
+        } else {
+            self.put(byte);
+        }
+
+        return Ok(());
+    }
+
+    /// Apply a control sequence.
+    fn csi(&mut self, command: u8, args: &[u8]) -> Result<(), CanvasError> {
+        self.extent.escapes = true;
+        if command == b'm' {
+            return self.sgr(args);
+        } else if command == b't' {
+            return self.rgb(args);
+        } else if !b"ABCDEFGHJKfsu".contains(&command) {
+            self.warnings.push(format!("Invalid control sequence argument: 0x{command:02X}"));
+            return Ok(());
+        }
+
+        let args = params(args)?;
+        let arg = |i: usize, default: u16| return args.get(i).copied().flatten().unwrap_or(default);
+        let column = match command {
+            b'C' => self.cursor.0.saturating_add(arg(0, 1)).saturating_add(1),
+            b'G' => arg(0, 1),
+            b'H' | b'f' => arg(1, 1),
+            _ => 0,
+        };
+        self.extent.reach = self.extent.reach.max(column);
+        match command {
+            b'A' => self.move_by(0, -i32::from(arg(0, 1))),
+            b'B' => self.move_by(0, i32::from(arg(0, 1))),
+            b'C' => self.move_by(i32::from(arg(0, 1)), 0),
+            b'D' => self.move_by(-i32::from(arg(0, 1)), 0),
+            b'E' => self.move_to((0, self.cursor.1.saturating_add(arg(0, 1)))),
+            b'F' => self.move_to((0, self.cursor.1.saturating_sub(arg(0, 1)))),
+            b'G' => self.move_to((arg(0, 1).saturating_sub(1), self.cursor.1)),
+            b'H' | b'f' => self.move_to((arg(1, 1).saturating_sub(1), arg(0, 1).saturating_sub(1))),
+            b'J' | b'K' if arg(0, 0) > 2 => {
+                self.warnings.push(format!("Unknown erase mode: {}", arg(0, 0)));
+            },
+            b'J' => self.erase_display(arg(0, 0)),
+            b'K' => self.erase_line(arg(0, 0)),
+            b's' => self.saved = self.cursor,
+            b'u' => self.cursor = self.saved,
+            _ => unreachable!("Already filtered"),
+        }
+
+        return Ok(());
+    }
+
+    /// Select graphic rendition, i.e. set colours and attributes.
+    fn sgr(&mut self, args: &[u8]) -> Result<(), CanvasError> {
+        for num in params(args)? {
+            let num = num.unwrap_or(0);
+            match num {
+                0 => {
+                    self.pen = Cell::default();
+                },
+                1 => {
+                    self.pen.attributes.bold = true;
+                },
+                5 => {
+                    self.pen.attributes.blink = true;
+                },
+                25 => {
+                    self.pen.attributes.blink = false;
+                },
+                30..=37 => {
+                    self.pen.fg = Colour::Index(index(num - 30) + if self.pen.attributes.bold { 8 } else { 0 });
+                },
+                39 => {
+                    self.pen.fg = Cell::default().fg;
+                },
+                40..=47 => {
+                    self.pen.bg = Colour::Index(index(num - 40));
+                },
+                49 => {
+                    self.pen.bg = Cell::default().bg;
+                },
+                90..=97 => {
+                    self.pen.fg = Colour::Index(index(num - 82));
+                },
+                100..=107 => {
+                    self.pen.bg = Colour::Index(index(num - 92));
+                },
+                _ => {
+                    self.warnings.push(format!("Unknown SGR param: {num}"));
+                },
+            }
+        }
+
+        return Ok(());
+    }
+
+    /// Set a true colour, in the `ESC[{0,1};{R};{G};{B}t` format.
+    fn rgb(&mut self, args: &[u8]) -> Result<(), CanvasError> {
+        let args = args.split(|r#char| return *r#char == b';').map(|arg| return to_str(arg)).collect::<Vec<_>>();
+        if args.len() != 4 {
+            self.warnings.push(format!("Invalid RGB sequence: {}", args.join(";")));
+            return Ok(());
+        }
+
+        let channel =
+            |arg: &String| return arg.parse::<u8>().map_err(|_err| return CanvasError::InvalidParam(arg.clone()));
+        let colour = Colour::Rgb([channel(&args[1])?, channel(&args[2])?, channel(&args[3])?]);
+        match args[0].as_str() {
+            "0" => {
+                self.pen.bg = colour;
+            },
+            "1" => {
+                self.pen.fg = colour;
+            },
+            target => {
+                self.warnings.push(format!("Invalid RGB target: {target}"));
+            },
+        }
+
+        return Ok(());
+    }
+
+    /// Write a character at the cursor, and advance it.
+    ///
+    /// Anything that falls out of the canvas is silently dropped.
+    ///
+    fn put(&mut self, byte: u8) {
+        let (x, y) = self.cursor;
+        self.extent.width = self.extent.width.max(x.saturating_add(1));
+        self.extent.height = self.extent.height.max(y.saturating_add(1));
+        if y < self.height {
+            self.grow(y);
+            let index = self.index((x, y));
+            self.cells[index] = Some(self.ink(byte));
+        }
+
+        self.cursor = if x + 1 >= self.width { (0, y.saturating_add(1)) } else { (x + 1, y) };
+    }
+
+    /// Move the cursor to an absolute position.
+    ///
+    /// The column is clamped to the canvas, but the row is left unbounded so
+    /// that whatever's written past the bottom gets dropped.
+    ///
+    fn move_to(&mut self, (x, y): (u16, u16)) {
+        self.cursor = (x.min(self.width - 1), y);
+    }
+
+    /// Move the cursor relative to its current position.
+    fn move_by(&mut self, dx: i32, dy: i32) {
+        let (x, y) = self.cursor;
+        self.move_to((
+            u16::try_from((i32::from(x) + dx).max(0)).unwrap_or(u16::MAX),
+            u16::try_from((i32::from(y) + dy).max(0)).unwrap_or(u16::MAX),
+        ));
+    }
+
+    /// Erase (part of) the canvas.
+    ///
+    /// * `0`: From the cursor to the end of the canvas.
+    /// * `1`: From the start of the canvas to the cursor.
+    /// * `2`: The whole canvas, also moving the cursor to the top left.
+    ///
+    /// Rows that were never reached are left empty rather than blanked, as
+    /// they're drawn the same unless the background has been changed.
+    ///
+    fn erase_display(&mut self, mode: u16) {
+        self.grow(self.cursor.1);
+        let cursor = self.index(self.cursor);
+        match mode {
+            0 => self.erase(cursor..self.cells.len()),
+            1 => self.erase(0..cursor + 1),
+            _ => {
+                self.erase(0..self.cells.len());
+                self.cursor = (0, 0);
+            },
+        }
+    }
+
+    /// Erase (part of) the current line.
+    ///
+    /// * `0`: From the cursor to the end of the line.
+    /// * `1`: From the start of the line to the cursor.
+    /// * `2`: The whole line.
+    ///
+    fn erase_line(&mut self, mode: u16) {
+        self.grow(self.cursor.1);
+        let start = self.index((0, self.cursor.1));
+        let cursor = self.index(self.cursor);
+        let end = self.index((0, self.cursor.1.saturating_add(1)));
+        match mode {
+            0 => self.erase(cursor..end),
+            1 => self.erase(start..cursor + 1),
+            _ => self.erase(start..end),
+        }
+    }
+
+    /// Fill a range of cells with blanks, using the current colours.
+    fn erase(&mut self, range: Range<usize>) {
+        let end = range.end.min(self.cells.len());
+        let start = range.start.min(end);
+        let blank = self.ink(b' ');
+        self.cells[start..end].fill(Some(blank));
+    }
+
+    /// A cell as the current pen would draw it.
+    ///
+    /// With iCE colours blink brightens the background instead, and without
+    /// them the background is limited to the 8 dark colours.
+    ///
+    fn ink(&self, byte: u8) -> Cell {
+        let mut cell = Cell { byte, ..self.pen };
+        if let Colour::Index(bg) = cell.bg {
+            cell.bg = Colour::Index(if !self.ice {
+                bg & 0x07
+            } else if cell.attributes.blink {
+                bg | 0x08
+            } else {
+                bg
+            });
+        }
+        cell.attributes.blink &= !self.ice;
+
+        return cell;
+    }
+
+    /// Allocate the rows up to the given one, as far as the canvas goes.
+    fn grow(&mut self, y: u16) {
+        let rows = y.saturating_add(1).min(self.height);
+        let length = usize::from(rows) * usize::from(self.width);
+        if self.cells.len() < length {
+            self.cells.resize(length, None);
+        }
+    }
+
+    /// Index of a position within the cells.
+    #[inline]
+    fn index(&self, (x, y): (u16, u16)) -> usize {
+        return usize::from(y) * usize::from(self.width) + usize::from(x);
+    }
+}
+
+/// Parse the `;` separated numeric parameters of a control sequence.
+///
+/// Empty parameters are kept as [`None`], so that defaults can be applied.
+///
+fn params(args: &[u8]) -> Result<Vec<Option<u16>>, CanvasError> {
+    return args
+        .split(|r#char| return *r#char == b';')
+        .map(|arg| {
+            return if arg.is_empty() {
+                Ok(None)
+            } else {
+                let arg = to_str(arg);
+                arg.parse::<u16>().map(Some).map_err(|_err| return CanvasError::InvalidParam(arg))
+            };
+        })
+        .collect();
+}
+
+/// Control sequences are plain ASCII, so no need for any fancy decoding.
+#[inline]
+fn to_str(arg: &[u8]) -> String {
+    return arg.iter().map(|byte| return char::from(*byte)).collect();
+}
+
+/// Narrow down an SGR param, which by then has been already bound checked.
+#[inline]
+fn index(num: u16) -> u8 {
+    return u8::try_from(num).expect("Already in range");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+    use std::fs::read;
+
+    #[test]
+    fn cursor() -> Result<(), CanvasError> {
+        let canvas = Canvas::from_bytes(&read("res/test/cursor.ans").expect("File exists"), &Meta::default())?;
+        assert_eq!(
+            canvas.cells().map(|(position, cell)| return (cell.byte, position)).collect::<Vec<_>>(),
+            vec![
+                (b'A', (0, 0)),
+                (b'C', (1, 0)),
+                (b'F', (2, 0)),
+                (b'G', (3, 0)),
+                (b'H', (4, 0)),
+                (b'E', (3, 1)),
+                (b'I', (5, 3))
+            ],
+        );
+        assert_eq!(canvas.cell(3, 1).map(|cell| return cell.byte), Some(b'E'));
+        assert_eq!(canvas.cell(4, 1), None);
+        assert_eq!(canvas.cell(5, 3).map(|cell| return cell.byte), Some(b'I'));
+        assert_eq!(canvas.cell(80, 0), None);
+        assert!(canvas.warnings().is_empty());
+
+        return Ok(());
+    }
+
+    #[test]
+    fn wrap() -> Result<(), CanvasError> {
+        let canvas = Canvas::from_bytes(b"ABCDE", &Meta { width: 2, height: 2, ..Default::default() })?;
+        assert_eq!(canvas.cursor, (1, 2));
+        assert_eq!(
+            canvas
+                .rows()
+                .map(|row| return row.iter().map(|cell| return cell.map(|c| return c.byte)).collect())
+                .collect::<Vec<Vec<_>>>(),
+            vec![vec![Some(b'A'), Some(b'B')], vec![Some(b'C'), Some(b'D')]],
+        );
+
+        return Ok(());
+    }
+
+    #[test]
+    fn split() -> Result<(), CanvasError> {
+        let mut canvas = Canvas::new(&Meta::default());
+        canvas.write(b"\x1B[3")?;
+        canvas.write(b"1mA")?;
+        assert_eq!(canvas.cell(0, 0).map(|cell| return cell.fg), Some(Colour::Index(1)));
+
+        return Ok(());
+    }
+
+    #[test]
+    fn colours() -> Result<(), CanvasError> {
+        let canvas =
+            Canvas::from_bytes(b"\x1B[1;31;44mA\x1B[0;91;104mB\x1B[1;0;1;2t\x1B[0;3;4;5tC\x1B[0mD", &Meta::default())?;
+        assert_eq!(
+            canvas.cells().map(|(_, cell)| return (cell.fg, cell.bg, cell.attributes.bold)).collect::<Vec<_>>(),
+            vec![
+                (Colour::Index(9), Colour::Index(4), true),
+                (Colour::Index(9), Colour::Index(12), false),
+                (Colour::Rgb([0, 1, 2]), Colour::Rgb([3, 4, 5]), false),
+                (Colour::Index(15), Colour::Index(0), false),
+            ],
+        );
+
+        return Ok(());
+    }
+
+    #[test]
+    fn ice() -> Result<(), CanvasError> {
+        let canvas = Canvas::from_bytes(b"\x1B[5;44mA\x1B[25mB\x1B[5;104mC", &Meta::default())?;
+        assert_eq!(
+            canvas.cells().map(|(_, cell)| return (cell.bg, cell.attributes.blink)).collect::<Vec<_>>(),
+            vec![(Colour::Index(12), false), (Colour::Index(4), false), (Colour::Index(12), false)],
+        );
+
+        return Ok(());
+    }
+
+    #[test]
+    fn blink() -> Result<(), CanvasError> {
+        let canvas =
+            Canvas::from_bytes(b"\x1B[5;44mA\x1B[25mB\x1B[104mC", &Meta { flags: 0x00, ..Default::default() })?;
+        assert_eq!(
+            canvas.cells().map(|(_, cell)| return (cell.bg, cell.attributes.blink)).collect::<Vec<_>>(),
+            vec![(Colour::Index(4), true), (Colour::Index(4), false), (Colour::Index(4), false)],
+        );
+
+        return Ok(());
+    }
+
+    #[test]
+    fn warnings() -> Result<(), CanvasError> {
+        let canvas = Canvas::from_bytes(b"\x1B[6m\x1B[?7h\x1B[3J\x1B[1t", &Meta::default())?;
+        assert_eq!(
+            canvas.warnings(),
+            [
+                "Unknown SGR param: 6",
+                "Invalid control sequence argument: 0x68",
+                "Unknown erase mode: 3",
+                "Invalid RGB sequence: 1",
+            ],
+        );
+
+        return Ok(());
+    }
+
+    #[test]
+    fn unparseable() {
+        assert_eq!(
+            Canvas::from_bytes(b"\x1B[1:2H", &Meta::default()).err(),
+            Some(CanvasError::InvalidParam(String::from("1:2"))),
+        );
+        assert_eq!(
+            Canvas::from_bytes(b"\x1B[1;0;256;0t", &Meta::default()).err(),
+            Some(CanvasError::InvalidParam(String::from("256"))),
+        );
+    }
+
+    #[test]
+    fn clamp() -> Result<(), CanvasError> {
+        let mut canvas = Canvas::new(&Meta { width: 4, height: 4, ..Default::default() });
+        canvas.write(b"\x1B[9C")?;
+        assert_eq!(canvas.cursor, (3, 0));
+        canvas.write(b"\x1B[9D")?;
+        assert_eq!(canvas.cursor, (0, 0));
+        canvas.write(b"\x1B[9;9H")?;
+        assert_eq!(canvas.cursor, (3, 8));
+        canvas.write(b"\x1B[A")?;
+        assert_eq!(canvas.cursor, (3, 7));
+        canvas.write(b"\x1B[H")?;
+        assert_eq!(canvas.cursor, (0, 0));
+
+        return Ok(());
+    }
+
+    #[test]
+    fn erase_line() -> Result<(), CanvasError> {
+        let blank = Some(Cell::default());
+        let red = Some(Cell { bg: Colour::Index(1), ..Cell::default() });
+        let mut canvas = Canvas::new(&Meta { width: 3, height: 2, ..Default::default() });
+        canvas.write(b"\x1B[1;2H\x1B[K")?;
+        assert_eq!(canvas.cells, vec![None, blank, blank]);
+        canvas.write(b"\x1B[41m\x1B[1K")?;
+        assert_eq!(canvas.cells, vec![red, red, blank]);
+
+        return Ok(());
+    }
+
+    #[test]
+    fn huge() -> Result<(), CanvasError> {
+        let mut canvas = Canvas::new(&Meta { width: u16::MAX, height: u16::MAX, ..Default::default() });
+        assert!(canvas.cells.is_empty());
+        canvas.write(b"\x1B[2;1HA\x1B[1;65535HZ")?;
+        assert_eq!(canvas.cells.len(), 2 * usize::from(u16::MAX));
+        assert_eq!(canvas.cell(0, 1).map(|cell| return cell.byte), Some(b'A'));
+        assert_eq!(canvas.cell(65534, 0).map(|cell| return cell.byte), Some(b'Z'));
+        assert_eq!(canvas.cell(0, 2), None);
+        assert_eq!(canvas.rows().count(), usize::from(u16::MAX));
+        assert_eq!(canvas.rows().nth(2), Some(&[] as &[Option<Cell>]));
+
+        return Ok(());
+    }
+
+    #[test]
+    fn erase_display() -> Result<(), CanvasError> {
+        let blank = Some(Cell::default());
+        let mut canvas = Canvas::new(&Meta { width: 2, height: 2, ..Default::default() });
+        canvas.write(b"\x1B[2;1H\x1B[J")?;
+        assert_eq!(canvas.cells, vec![None, None, blank, blank]);
+        canvas.write(b"\x1B[2J")?;
+        assert_eq!(canvas.cells, vec![blank; 4]);
+        assert_eq!(canvas.cursor, (0, 0));
+
+        return Ok(());
+    }
+
+    #[test]
+    fn lines() -> Result<(), CanvasError> {
+        let mut canvas = Canvas::new(&Meta { width: 4, height: 4, ..Default::default() });
+        canvas.write(b"\x1B[2;3H\x1B[E")?;
+        assert_eq!(canvas.cursor, (0, 2));
+        canvas.write(b"\x1B[3G\x1B[2F")?;
+        assert_eq!(canvas.cursor, (0, 0));
+        canvas.write(b"\x1B[9B\x1B[2;2f")?;
+        assert_eq!(canvas.cursor, (1, 1));
+        canvas.write(b"A\rB\nC")?;
+        assert_eq!(canvas.cursor, (1, 2));
+        assert_eq!(canvas.cell(0, 1).map(|cell| return cell.byte), Some(b'B'));
+        assert_eq!(canvas.cell(0, 2).map(|cell| return cell.byte), Some(b'C'));
+
+        return Ok(());
+    }
+
+    #[test]
+    fn save_restore() -> Result<(), CanvasError> {
+        let mut canvas = Canvas::new(&Meta { width: 4, height: 4, ..Default::default() });
+        canvas.write(b"\x1B[2;3H\x1B[s\x1B[H\x1B[u")?;
+        assert_eq!(canvas.cursor, (2, 1));
+
+        return Ok(());
+    }
+}
@@ -0,0 +1,327 @@
+//! Other DOS code pages to/from UTF-8.
+//!
+//! Every code page shares the lower half with CP437 (see
+//! [`struct@CP437_TO_UTF8`]), including its exceptions, and only differ in the
+//! upper half.
+//!
+
+use indexmap::IndexMap;
+use lazy_static::lazy_static;
+use std::fmt::{self, Display, Formatter};
+
+use crate::{
+    fonts::FontName,
+    prelude::{EncodingError, CP437_TO_UTF8, UTF8_TO_CP437},
+};
+
+/// A DOS code page, which decides what characters the upper half of the
+/// bytes stand for.
+///
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum CodePage {
+    /// The original IBM PC one, also known as DOS Latin US.
+    #[default]
+    Cp437,
+    /// Greek.
+    Cp737,
+    /// Baltic.
+    Cp775,
+    /// Western European, also known as DOS Latin 1.
+    Cp850,
+    /// Central European, also known as DOS Latin 2.
+    Cp852,
+    /// Cyrillic.
+    Cp866,
+}
+
+/// Upper half of CP737 (Greek), the lower half being the same as
+/// CP437's.
+#[rustfmt::skip]
+const CP737_HIGH: [char; 128] = [
+    /* XX    X0   X1   X2   X3   X4   X5   X6   X7    X8   X9   XA   XB   XC   XD   XE   XF */
+    /* 8X */ 'Α', 'Β', 'Γ', 'Δ', 'Ε', 'Ζ', 'Η', 'Θ',  'Ι', 'Κ', 'Λ', 'Μ', 'Ν', 'Ξ', 'Ο', 'Π',
+    /* 9X */ 'Ρ', 'Σ', 'Τ', 'Υ', 'Φ', 'Χ', 'Ψ', 'Ω',  'α', 'β', 'γ', 'δ', 'ε', 'ζ', 'η', 'θ',
+    /* AX */ 'ι', 'κ', 'λ', 'μ', 'ν', 'ξ', 'ο', 'π',  'ρ', 'σ', 'ς', 'τ', 'υ', 'φ', 'χ', 'ψ',
+    /* BX */ '░', '▒', '▓', '│', '┤', '╡', '╢', '╖',  '╕', '╣', '║', '╗', '╝', '╜', '╛', '┐',
+    /* CX */ '└', '┴', '┬', '├', '─', '┼', '╞', '╟',  '╚', '╔', '╩', '╦', '╠', '═', '╬', '╧',
+    /* DX */ '╨', '╤', '╥', '╙', '╘', '╒', '╓', '╫',  '╪', '┘', '┌', '█', '▄', '▌', '▐', '▀',
+    /* EX */ 'ω', 'ά', 'έ', 'ή', 'ϊ', 'ί', 'ό', 'ύ',  'ϋ', 'ώ', 'Ά', 'Έ', 'Ή', 'Ί', 'Ό', 'Ύ',
+    /* FX */ 'Ώ', '±', '≥', '≤', 'Ϊ', 'Ϋ', '÷', '≈',  '°', '∙', '·', '√', 'ⁿ', '²', '■', '\u{A0}',
+];
+
+/// Upper half of CP775 (Baltic), the lower half being the same as
+/// CP437's.
+#[rustfmt::skip]
+const CP775_HIGH: [char; 128] = [
+    /* XX    X0   X1   X2   X3   X4   X5   X6   X7    X8   X9   XA   XB   XC   XD   XE   XF */
+    /* 8X */ 'Ć', 'ü', 'é', 'ā', 'ä', 'ģ', 'å', 'ć',  'ł', 'ē', 'Ŗ', 'ŗ', 'ī', 'Ź', 'Ä', 'Å',
+    /* 9X */ 'É', 'æ', 'Æ', 'ō', 'ö', 'Ģ', '¢', 'Ś',  'ś', 'Ö', 'Ü', 'ø', '£', 'Ø', '×', '¤',
+    /* AX */ 'Ā', 'Ī', 'ó', 'Ż', 'ż', 'ź', '”', '¦',  '©', '®', '¬', '½', '¼', 'Ł', '«', '»',
+    /* BX */ '░', '▒', '▓', '│', '┤', 'Ą', 'Č', 'Ę',  'Ė', '╣', '║', '╗', '╝', 'Į', 'Š', '┐',
+    /* CX */ '└', '┴', '┬', '├', '─', '┼', 'Ų', 'Ū',  '╚', '╔', '╩', '╦', '╠', '═', '╬', 'Ž',
+    /* DX */ 'ą', 'č', 'ę', 'ė', 'į', 'š', 'ų', 'ū',  'ž', '┘', '┌', '█', '▄', '▌', '▐', '▀',
+    /* EX */ 'Ó', 'ß', 'Ō', 'Ń', 'õ', 'Õ', 'µ', 'ń',  'Ķ', 'ķ', 'Ļ', 'ļ', 'ņ', 'Ē', 'Ņ', '’',
+    /* FX */ '\u{AD}', '±', '“', '¾', '¶', '§', '÷', '„',  '°', '∙', '·', '¹', '³', '²', '■', '\u{A0}',
+];
+
+/// Upper half of CP850 (Western European), the lower half being the same as
+/// CP437's.
+#[rustfmt::skip]
+const CP850_HIGH: [char; 128] = [
+    /* XX    X0   X1   X2   X3   X4   X5   X6   X7    X8   X9   XA   XB   XC   XD   XE   XF */
+    /* 8X */ 'Ç', 'ü', 'é', 'â', 'ä', 'à', 'å', 'ç',  'ê', 'ë', 'è', 'ï', 'î', 'ì', 'Ä', 'Å',
+    /* 9X */ 'É', 'æ', 'Æ', 'ô', 'ö', 'ò', 'û', 'ù',  'ÿ', 'Ö', 'Ü', 'ø', '£', 'Ø', '×', 'ƒ',
+    /* AX */ 'á', 'í', 'ó', 'ú', 'ñ', 'Ñ', 'ª', 'º',  '¿', '®', '¬', '½', '¼', '¡', '«', '»',
+    /* BX */ '░', '▒', '▓', '│', '┤', 'Á', 'Â', 'À',  '©', '╣', '║', '╗', '╝', '¢', '¥', '┐',
+    /* CX */ '└', '┴', '┬', '├', '─', '┼', 'ã', 'Ã',  '╚', '╔', '╩', '╦', '╠', '═', '╬', '¤',
+    /* DX */ 'ð', 'Ð', 'Ê', 'Ë', 'È', 'ı', 'Í', 'Î',  'Ï', '┘', '┌', '█', '▄', '¦', 'Ì', '▀',
+    /* EX */ 'Ó', 'ß', 'Ô', 'Ò', 'õ', 'Õ', 'µ', 'þ',  'Þ', 'Ú', 'Û', 'Ù', 'ý', 'Ý', '¯', '´',
+    /* FX */ '\u{AD}', '±', '‗', '¾', '¶', '§', '÷', '¸',  '°', '¨', '·', '¹', '³', '²', '■', '\u{A0}',
+];
+
+/// Upper half of CP852 (Central European), the lower half being the same as
+/// CP437's.
+#[rustfmt::skip]
+const CP852_HIGH: [char; 128] = [
+    /* XX    X0   X1   X2   X3   X4   X5   X6   X7    X8   X9   XA   XB   XC   XD   XE   XF */
+    /* 8X */ 'Ç', 'ü', 'é', 'â', 'ä', 'ů', 'ć', 'ç',  'ł', 'ë', 'Ő', 'ő', 'î', 'Ź', 'Ä', 'Ć',
+    /* 9X */ 'É', 'Ĺ', 'ĺ', 'ô', 'ö', 'Ľ', 'ľ', 'Ś',  'ś', 'Ö', 'Ü', 'Ť', 'ť', 'Ł', '×', 'č',
+    /* AX */ 'á', 'í', 'ó', 'ú', 'Ą', 'ą', 'Ž', 'ž',  'Ę', 'ę', '¬', 'ź', 'Č', 'ş', '«', '»',
+    /* BX */ '░', '▒', '▓', '│', '┤', 'Á', 'Â', 'Ě',  'Ş', '╣', '║', '╗', '╝', 'Ż', 'ż', '┐',
+    /* CX */ '└', '┴', '┬', '├', '─', '┼', 'Ă', 'ă',  '╚', '╔', '╩', '╦', '╠', '═', '╬', '¤',
+    /* DX */ 'đ', 'Đ', 'Ď', 'Ë', 'ď', 'Ň', 'Í', 'Î',  'ě', '┘', '┌', '█', '▄', 'Ţ', 'Ů', '▀',
+    /* EX */ 'Ó', 'ß', 'Ô', 'Ń', 'ń', 'ň', 'Š', 'š',  'Ŕ', 'Ú', 'ŕ', 'Ű', 'ý', 'Ý', 'ţ', '´',
+    /* FX */ '\u{AD}', '˝', '˛', 'ˇ', '˘', '§', '÷', '¸',  '°', '¨', '˙', 'ű', 'Ř', 'ř', '■', '\u{A0}',
+];
+
+/// Upper half of CP866 (Cyrillic), the lower half being the same as
+/// CP437's.
+#[rustfmt::skip]
+const CP866_HIGH: [char; 128] = [
+    /* XX    X0   X1   X2   X3   X4   X5   X6   X7    X8   X9   XA   XB   XC   XD   XE   XF */
+    /* 8X */ 'А', 'Б', 'В', 'Г', 'Д', 'Е', 'Ж', 'З',  'И', 'Й', 'К', 'Л', 'М', 'Н', 'О', 'П',
+    /* 9X */ 'Р', 'С', 'Т', 'У', 'Ф', 'Х', 'Ц', 'Ч',  'Ш', 'Щ', 'Ъ', 'Ы', 'Ь', 'Э', 'Ю', 'Я',
+    /* AX */ 'а', 'б', 'в', 'г', 'д', 'е', 'ж', 'з',  'и', 'й', 'к', 'л', 'м', 'н', 'о', 'п',
+    /* BX */ '░', '▒', '▓', '│', '┤', '╡', '╢', '╖',  '╕', '╣', '║', '╗', '╝', '╜', '╛', '┐',
+    /* CX */ '└', '┴', '┬', '├', '─', '┼', '╞', '╟',  '╚', '╔', '╩', '╦', '╠', '═', '╬', '╧',
+    /* DX */ '╨', '╤', '╥', '╙', '╘', '╒', '╓', '╫',  '╪', '┘', '┌', '█', '▄', '▌', '▐', '▀',
+    /* EX */ 'р', 'с', 'т', 'у', 'ф', 'х', 'ц', 'ч',  'ш', 'щ', 'ъ', 'ы', 'ь', 'э', 'ю', 'я',
+    /* FX */ 'Ё', 'ё', 'Є', 'є', 'Ї', 'ї', 'Ў', 'ў',  '°', '∙', '·', '√', '№', '¤', '■', '\u{A0}',
+];
+
+lazy_static! {
+    /// The full table of each code page, mapping every byte to UTF-8.
+    static ref TO_UTF8: IndexMap<CodePage, Vec<char>> = CodePage::ALL
+        .iter()
+        .map(|code_page| {
+            let high: &[char] = match code_page {
+                CodePage::Cp437 => &CP437_TO_UTF8[0x80..],
+                CodePage::Cp737 => &CP737_HIGH,
+                CodePage::Cp775 => &CP775_HIGH,
+                CodePage::Cp850 => &CP850_HIGH,
+                CodePage::Cp852 => &CP852_HIGH,
+                CodePage::Cp866 => &CP866_HIGH,
+            };
+            return (*code_page, CP437_TO_UTF8[..0x80].iter().chain(high).copied().collect());
+        })
+        .collect();
+
+    /// The inverse of [`struct@TO_UTF8`].
+    ///
+    /// Some characters show up twice, as CP437 control glyphs and in the upper
+    /// half (such as ¶ and § in CP850), in which case the upper half wins.
+    static ref FROM_UTF8: IndexMap<CodePage, IndexMap<char, u8>> = TO_UTF8
+        .iter()
+        .map(|(code_page, table)| {
+            return (
+                *code_page,
+                table
+                    .iter()
+                    .enumerate()
+                    .map(|(byte, r#char)| return (*r#char, u8::try_from(byte).expect("Only 256 values")))
+                    .collect(),
+            );
+        })
+        .collect();
+}
+
+impl CodePage {
+    /// Every supported code page.
+    pub const ALL: [CodePage; 6] =
+        [CodePage::Cp437, CodePage::Cp737, CodePage::Cp775, CodePage::Cp850, CodePage::Cp852, CodePage::Cp866];
+
+    /// Find a code page by its number, as used in SAUCE font names.
+    ///
+    /// # Arguments
+    ///
+    /// * `name`: The number, optionally prefixed by `CP` (e.g., `850` or
+    ///   `CP850`).
+    ///
+    #[must_use]
+    pub fn get(name: &str) -> Option<CodePage> {
+        let name = name.to_uppercase();
+        let number = name.strip_prefix("CP").unwrap_or(&name);
+        return CodePage::ALL.into_iter().find(|code_page| return code_page.number() == number);
+    }
+
+    /// Pick the code page from a SAUCE font name, such as `IBM VGA 850`.
+    ///
+    /// Fonts with no code page, or with one that isn't supported, use CP437.
+    ///
+    /// # Arguments
+    ///
+    /// * `font`: The font name, exactly as in the metadata.
+    ///
+    #[must_use]
+    pub fn from_font(font: &str) -> CodePage {
+        return FontName::parse(font)
+            .and_then(|name| return name.code_page())
+            .and_then(CodePage::get)
+            .unwrap_or_default();
+    }
+
+    /// The number of this code page.
+    #[must_use]
+    pub fn number(self) -> &'static str {
+        return match self {
+            CodePage::Cp437 => "437",
+            CodePage::Cp737 => "737",
+            CodePage::Cp775 => "775",
+            CodePage::Cp850 => "850",
+            CodePage::Cp852 => "852",
+            CodePage::Cp866 => "866",
+        };
+    }
+
+    /// An array of 256 elements, mapping each byte to UTF-8.
+    ///
+    /// For CP437 this is the same as [`struct@CP437_TO_UTF8`].
+    ///
+    #[must_use]
+    pub fn table(self) -> &'static [char] {
+        return &TO_UTF8[&self];
+    }
+
+    /// Convert some bytes in this code page to UTF-8.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes`: The bytes to convert.
+    ///
+    #[must_use]
+    pub fn decode(self, bytes: &[u8]) -> String {
+        let table = self.table();
+        return bytes.iter().map(|byte| return table[usize::from(*byte)]).collect();
+    }
+
+    /// Convert a character to a byte in this code page, if possible.
+    ///
+    /// # Arguments
+    ///
+    /// * `char`: The character to convert.
+    ///
+    #[must_use]
+    pub fn encode_char(self, r#char: char) -> Option<u8> {
+        return if self == CodePage::Cp437 { UTF8_TO_CP437.get(&r#char) } else { FROM_UTF8[&self].get(&r#char) }
+            .copied();
+    }
+
+    /// Convert a string to bytes in this code page.
+    ///
+    /// # Arguments
+    ///
+    /// * `utf8`: The string to convert.
+    ///
+    /// # Errors
+    ///
+    /// Fails when there's no equivalent character in this code page.
+    ///
+    pub fn encode(self, utf8: &str) -> Result<Vec<u8>, EncodingError> {
+        return utf8
+            .chars()
+            .enumerate()
+            .map(|(position, r#char)| {
+                return self.encode_char(r#char).ok_or(EncodingError { r#char, position, code_page: self });
+            })
+            .collect();
+    }
+}
+
+impl Display for CodePage {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        return write!(f, "CP{}", self.number());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn round_trip() {
+        for code_page in CodePage::ALL {
+            for byte in 0x00..=0xFF_u8 {
+                let r#char = code_page.table()[usize::from(byte)];
+                let encoded = code_page.encode_char(r#char).map(|byte| return code_page.table()[usize::from(byte)]);
+                assert_eq!(encoded, Some(r#char), "{code_page} 0x{byte:02X}");
+            }
+        }
+    }
+
+    #[test]
+    fn duplicates() {
+        assert_eq!(CodePage::Cp437.encode_char('¶'), Some(0x14));
+        assert_eq!(CodePage::Cp850.encode_char('¶'), Some(0xF4));
+        assert_eq!(CodePage::Cp850.decode(&[0x14, 0xF4]), "¶¶");
+    }
+
+    #[test]
+    fn cp437() {
+        assert_eq!(CodePage::Cp437.table(), &CP437_TO_UTF8[..]);
+    }
+
+    #[test]
+    fn decode() {
+        assert_eq!(CodePage::Cp437.decode(&[0x01, 0x41, 0x80]), "☺AÇ");
+        assert_eq!(CodePage::Cp737.decode(&[0x80, 0x98]), "Αα");
+        assert_eq!(CodePage::Cp775.decode(&[0x80, 0xB5]), "ĆĄ");
+        assert_eq!(CodePage::Cp850.decode(&[0x9B, 0xD0]), "øð");
+        assert_eq!(CodePage::Cp852.decode(&[0x85, 0xFD]), "ůř");
+        assert_eq!(CodePage::Cp866.decode(&[0x01, 0x80, 0xEF]), "☺Ая");
+    }
+
+    #[test]
+    fn encode() {
+        assert_eq!(CodePage::Cp866.encode("☺Привет"), Ok(vec![0x01, 0x8F, 0xE0, 0xA8, 0xA2, 0xA5, 0xE2]));
+        assert_eq!(
+            CodePage::Cp866.encode("Ok é"),
+            Err(EncodingError { r#char: 'é', position: 3, code_page: CodePage::Cp866 }),
+        );
+        assert_eq!(
+            EncodingError { r#char: 'é', position: 3, code_page: CodePage::Cp866 }.to_string(),
+            "é (U+E9) is not a valid CP866 character",
+        );
+    }
+
+    #[test]
+    fn get() {
+        assert_eq!(CodePage::get("437"), Some(CodePage::Cp437));
+        assert_eq!(CodePage::get("cp850"), Some(CodePage::Cp850));
+        assert_eq!(CodePage::get("CP866"), Some(CodePage::Cp866));
+        assert_eq!(CodePage::get("720"), None);
+        assert_eq!(CodePage::get("KAM"), None);
+    }
+
+    #[test]
+    fn from_font() {
+        assert_eq!(CodePage::from_font("IBM VGA"), CodePage::Cp437);
+        assert_eq!(CodePage::from_font("IBM VGA 850"), CodePage::Cp850);
+        assert_eq!(CodePage::from_font("IBM EGA43 737"), CodePage::Cp737);
+        assert_eq!(CodePage::from_font("IBM VGA50 720"), CodePage::Cp437);
+        assert_eq!(CodePage::from_font("Amiga Topaz 1+"), CodePage::Cp437);
+        assert_eq!(CodePage::from_font("X"), CodePage::Cp437);
+    }
+}
@@ -1,6 +1,12 @@
 //! ANSI colour schemes.
 
 use regex::Regex;
+#[cfg(feature = "serde")]
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
 #[cfg(feature = "_gen")]
 use strum_macros::EnumIter;
 
@@ -79,7 +85,7 @@ impl ColourScheme {
     /// Fails when the theme is invalid.
     ///
     #[expect(clippy::too_many_lines, reason = "Not much that can be done")]
-    pub fn get(name: &String) -> Result<ColourScheme, String> {
+    pub fn get(name: &str) -> Result<ColourScheme, SchemeError> {
         let uppercase_name = name.to_uppercase();
         return match uppercase_name.as_str() {
             "CLASSIC" => Ok(ColourScheme::CLASSIC),
@@ -99,107 +105,107 @@ impl ColourScheme {
                             // DARK
                             [
                                 // BLACK
-                                parse_hex(&c[1..3])?,
-                                parse_hex(&c[3..5])?,
-                                parse_hex(&c[5..7])?,
+                                parse_hex(name, &c[1..3])?,
+                                parse_hex(name, &c[3..5])?,
+                                parse_hex(name, &c[5..7])?,
                             ],
                             [
                                 // RED
-                                parse_hex(&c[9..11])?,
-                                parse_hex(&c[11..13])?,
-                                parse_hex(&c[13..15])?,
+                                parse_hex(name, &c[9..11])?,
+                                parse_hex(name, &c[11..13])?,
+                                parse_hex(name, &c[13..15])?,
                             ],
                             [
                                 // GREEN
-                                parse_hex(&c[17..19])?,
-                                parse_hex(&c[19..21])?,
-                                parse_hex(&c[21..23])?,
+                                parse_hex(name, &c[17..19])?,
+                                parse_hex(name, &c[19..21])?,
+                                parse_hex(name, &c[21..23])?,
                             ],
                             [
                                 // YELLOW
-                                parse_hex(&c[25..27])?,
-                                parse_hex(&c[27..29])?,
-                                parse_hex(&c[29..31])?,
+                                parse_hex(name, &c[25..27])?,
+                                parse_hex(name, &c[27..29])?,
+                                parse_hex(name, &c[29..31])?,
                             ],
                             [
                                 // BLUE
-                                parse_hex(&c[33..35])?,
-                                parse_hex(&c[35..37])?,
-                                parse_hex(&c[37..39])?,
+                                parse_hex(name, &c[33..35])?,
+                                parse_hex(name, &c[35..37])?,
+                                parse_hex(name, &c[37..39])?,
                             ],
                             [
                                 // MAGENTA
-                                parse_hex(&c[41..43])?,
-                                parse_hex(&c[43..45])?,
-                                parse_hex(&c[45..47])?,
+                                parse_hex(name, &c[41..43])?,
+                                parse_hex(name, &c[43..45])?,
+                                parse_hex(name, &c[45..47])?,
                             ],
                             [
                                 // CYAN
-                                parse_hex(&c[49..51])?,
-                                parse_hex(&c[51..53])?,
-                                parse_hex(&c[53..55])?,
+                                parse_hex(name, &c[49..51])?,
+                                parse_hex(name, &c[51..53])?,
+                                parse_hex(name, &c[53..55])?,
                             ],
                             [
                                 // WHITE
-                                parse_hex(&c[57..59])?,
-                                parse_hex(&c[59..61])?,
-                                parse_hex(&c[61..63])?,
+                                parse_hex(name, &c[57..59])?,
+                                parse_hex(name, &c[59..61])?,
+                                parse_hex(name, &c[61..63])?,
                             ],
                             // BRIGHT
                             [
                                 // BLACK
-                                parse_hex(&c[65..67])?,
-                                parse_hex(&c[67..69])?,
-                                parse_hex(&c[69..71])?,
+                                parse_hex(name, &c[65..67])?,
+                                parse_hex(name, &c[67..69])?,
+                                parse_hex(name, &c[69..71])?,
                             ],
                             [
                                 // RED
-                                parse_hex(&c[73..75])?,
-                                parse_hex(&c[75..77])?,
-                                parse_hex(&c[77..79])?,
+                                parse_hex(name, &c[73..75])?,
+                                parse_hex(name, &c[75..77])?,
+                                parse_hex(name, &c[77..79])?,
                             ],
                             [
                                 // GREEN
-                                parse_hex(&c[81..83])?,
-                                parse_hex(&c[83..85])?,
-                                parse_hex(&c[85..87])?,
+                                parse_hex(name, &c[81..83])?,
+                                parse_hex(name, &c[83..85])?,
+                                parse_hex(name, &c[85..87])?,
                             ],
                             [
                                 // YELLOW
-                                parse_hex(&c[89..91])?,
-                                parse_hex(&c[91..93])?,
-                                parse_hex(&c[93..95])?,
+                                parse_hex(name, &c[89..91])?,
+                                parse_hex(name, &c[91..93])?,
+                                parse_hex(name, &c[93..95])?,
                             ],
                             [
                                 // BLUE
-                                parse_hex(&c[97..99])?,
-                                parse_hex(&c[99..101])?,
-                                parse_hex(&c[101..103])?,
+                                parse_hex(name, &c[97..99])?,
+                                parse_hex(name, &c[99..101])?,
+                                parse_hex(name, &c[101..103])?,
                             ],
                             [
                                 // MAGENTA
-                                parse_hex(&c[105..107])?,
-                                parse_hex(&c[107..109])?,
-                                parse_hex(&c[109..111])?,
+                                parse_hex(name, &c[105..107])?,
+                                parse_hex(name, &c[107..109])?,
+                                parse_hex(name, &c[109..111])?,
                             ],
                             [
                                 // CYAN
-                                parse_hex(&c[113..115])?,
-                                parse_hex(&c[115..117])?,
-                                parse_hex(&c[117..119])?,
+                                parse_hex(name, &c[113..115])?,
+                                parse_hex(name, &c[115..117])?,
+                                parse_hex(name, &c[117..119])?,
                             ],
                             [
                                 // WHITE
-                                parse_hex(&c[121..123])?,
-                                parse_hex(&c[123..125])?,
-                                parse_hex(&c[125..127])?,
+                                parse_hex(name, &c[121..123])?,
+                                parse_hex(name, &c[123..125])?,
+                                parse_hex(name, &c[125..127])?,
                             ],
                         ]))
                     } else {
-                        Err(format!("Unparseable colour scheme: {name}"))
+                        Err(SchemeError::Unparseable(name.to_string()))
                     }
                 } else {
-                    Err(format!("Unknown scheme: {name}"))
+                    Err(SchemeError::Unknown(name.to_string()))
                 }
             },
         };
@@ -321,9 +327,90 @@ impl ColourScheme {
     }
 }
 
+/// Serialised by [name](ColourScheme::name), with custom schemes as hex codes.
+#[cfg(feature = "serde")]
+impl Serialize for ColourScheme {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        return serializer.serialize_str(&self.name());
+    }
+}
+
+/// Deserialised the same way as [`ColourScheme::get`].
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for ColourScheme {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<ColourScheme, D::Error> {
+        return ColourScheme::get(&String::deserialize(deserializer)?).map_err(D::Error::custom);
+    }
+}
+
+/// A colour scheme that couldn't be understood.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SchemeError {
+    /// Not one of the predefined schemes.
+    Unknown(String),
+    /// Looks like a custom scheme, but the colours are malformed.
+    Unparseable(String),
+}
+
+impl Display for SchemeError {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        return match self {
+            SchemeError::Unknown(name) => write!(f, "Unknown scheme: {name}"),
+            SchemeError::Unparseable(name) => write!(f, "Unparseable colour scheme: {name}"),
+        };
+    }
+}
+
+impl Error for SchemeError {}
+
+/// A colour, as set by a file.
+///
+/// It's either a reference to a [`ColourScheme`] entry, or a fixed value.
+///
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Colour {
+    /// One of the 16 colours of a scheme.
+    Index(u8),
+    /// A true colour.
+    Rgb([u8; 3]),
+}
+
+impl Colour {
+    /// Get the actual RGB value for this colour.
+    ///
+    /// # Arguments
+    ///
+    /// * `scheme`: The scheme used to resolve indexed colours.
+    ///
+    #[must_use]
+    pub fn rgb(&self, scheme: &ColourScheme) -> [u8; 3] {
+        return match self {
+            Colour::Index(index) => scheme.colour(*index),
+            Colour::Rgb(rgb) => *rgb,
+        };
+    }
+}
+
+/// Convert a VGA colour index into a scheme one.
+///
+/// VGA attributes store the colour bits as blue, green, red, whereas ANSI
+/// (and so the schemes) store them as red, green, blue. Swapping the red and
+/// blue bits goes either way.
+///
+/// # Arguments
+///
+/// * `index`: The VGA colour index, `0..16`.
+///
+#[inline]
+#[must_use]
+pub const fn vga_to_ansi(index: u8) -> u8 {
+    return (index & 0x0A) | ((index & 0x01) << 2) | ((index & 0x04) >> 2);
+}
+
 #[inline]
-fn parse_hex(hex: &str) -> Result<u8, String> {
-    return u8::from_str_radix(hex, 16).map_err(|err| return err.to_string());
+fn parse_hex(name: &str, hex: &str) -> Result<u8, SchemeError> {
+    return u8::from_str_radix(hex, 16).map_err(|_| return SchemeError::Unparseable(name.to_string()));
 }
 
 #[cfg(test)]
@@ -334,7 +421,7 @@ mod tests {
     use rand::{rng, Rng};
 
     #[test]
-    fn classic() -> Result<(), String> {
+    fn classic() -> Result<(), SchemeError> {
         assert_eq!(ColourScheme::get(&String::from("ClAsSiC"))?, ColourScheme::CLASSIC);
         for i in 0..16 {
             assert_eq!(ColourScheme::CLASSIC.colours()[i], ColourScheme::CLASSIC.colour(i as u8));
@@ -344,7 +431,7 @@ mod tests {
     }
 
     #[test]
-    fn modern() -> Result<(), String> {
+    fn modern() -> Result<(), SchemeError> {
         assert_eq!(ColourScheme::get(&String::from("MoDeRn"))?, ColourScheme::MODERN);
         for i in 0..16 {
             assert_eq!(ColourScheme::MODERN.colours()[i], ColourScheme::MODERN.colour(i as u8));
@@ -354,7 +441,7 @@ mod tests {
     }
 
     #[test]
-    fn dracula() -> Result<(), String> {
+    fn dracula() -> Result<(), SchemeError> {
         assert_eq!(ColourScheme::get(&String::from("DrAcUlA"))?, ColourScheme::DRACULA);
         for i in 0..16 {
             assert_eq!(ColourScheme::DRACULA.colours()[i], ColourScheme::DRACULA.colour(i as u8));
@@ -364,7 +451,7 @@ mod tests {
     }
 
     #[test]
-    fn custom() -> Result<(), String> {
+    fn custom() -> Result<(), SchemeError> {
         let colours = [
             [rng().random(), rng().random(), rng().random()],
             [rng().random(), rng().random(), rng().random()],
@@ -400,13 +487,44 @@ mod tests {
     fn custom_unparseable() {
         let result = ColourScheme::get(&String::from("CuStOm()"));
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Unparseable colour scheme: CuStOm()");
+        let err = result.unwrap_err();
+        assert_eq!(err, SchemeError::Unparseable(String::from("CuStOm()")));
+        assert_eq!(err.to_string(), "Unparseable colour scheme: CuStOm()");
+    }
+
+    #[test]
+    fn vga() {
+        assert_eq!(
+            (0..16).map(vga_to_ansi).collect::<Vec<u8>>(),
+            [0, 4, 2, 6, 1, 5, 3, 7, 8, 12, 10, 14, 9, 13, 11, 15]
+        );
+    }
+
+    #[test]
+    fn rgb() {
+        assert_eq!(Colour::Index(4).rgb(&ColourScheme::CLASSIC), [0x00, 0x00, 0xAB]);
+        assert_eq!(Colour::Rgb([0x01, 0x02, 0x03]).rgb(&ColourScheme::CLASSIC), [0x01, 0x02, 0x03]);
     }
 
     #[test]
     fn invalid() {
         let result = ColourScheme::get(&String::from("x"));
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Unknown scheme: x");
+        let err = result.unwrap_err();
+        assert_eq!(err, SchemeError::Unknown(String::from("x")));
+        assert_eq!(err.to_string(), "Unknown scheme: x");
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde() -> Result<(), serde_json::Error> {
+        let custom = ColourScheme::CUSTOM([[0x12, 0x34, 0x56]; 16]);
+        assert_eq!(serde_json::to_string(&ColourScheme::CLASSIC)?, "\"CLASSIC\"");
+        assert_eq!(serde_json::to_string(&custom)?, format!("\"CUSTOM({})\"", ["#123456"; 16].join(",")));
+        assert_eq!(serde_json::from_str::<ColourScheme>("\"dracula\"")?, ColourScheme::DRACULA);
+        assert_eq!(serde_json::from_str::<ColourScheme>(&serde_json::to_string(&custom)?)?, custom);
+        assert_eq!(serde_json::from_str::<ColourScheme>("\"x\"").unwrap_err().to_string(), "Unknown scheme: x");
+
+        return Ok(());
     }
 }
@@ -2,6 +2,12 @@
 
 use indexmap::IndexMap;
 use lazy_static::lazy_static;
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+use crate::code_page::CodePage;
 
 lazy_static! {
     /// An array of 256 elements, mapping most of the CP437 values to UTF-8 characters.
@@ -48,27 +54,45 @@ lazy_static! {
             .map(|(a, b)| return (*b, u8::try_from(a).expect("Spec only has 256 values"))).collect::<IndexMap<_, _>>();
 }
 
+/// A character that has no equivalent in a code page.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct EncodingError {
+    /// The offending character.
+    pub char: char,
+    /// Where the character was found, counted in characters (not bytes).
+    pub position: usize,
+    /// The code page it was being encoded to.
+    pub code_page: CodePage,
+}
+
+impl Display for EncodingError {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        return write!(f, "{} (U+{:X}) is not a valid {} character", self.char, self.char as u32, self.code_page);
+    }
+}
+
+impl Error for EncodingError {}
+
 /// Apply [`struct@CP437_TO_UTF8`] to the given bytes.
+///
+/// See [`CodePage::decode`] for other code pages.
+///
 #[must_use]
 pub fn to_utf8(cp437: &[u8]) -> String {
-    return cp437.iter().map(|byte| return CP437_TO_UTF8[*byte as usize]).collect();
+    return CodePage::Cp437.decode(cp437);
 }
 
 /// Apply [`struct@UTF8_TO_CP437`] to the given string.
 ///
+/// See [`CodePage::encode`] for other code pages.
+///
 /// # Errors
 ///
 /// Fails when there's no equivalent UTF-8 -> CP437 character.
 ///
-pub fn to_cp437(utf8: &str) -> Result<Vec<u8>, String> {
-    return utf8
-        .chars()
-        .map(|r#char| {
-            return UTF8_TO_CP437.get(&r#char).map(|byte| return *byte).ok_or_else(|| {
-                return format!("{} (U+{:X}) is not a valid CP437 character", r#char, r#char as u32);
-            });
-        })
-        .collect::<Result<Vec<u8>, String>>();
+pub fn to_cp437(utf8: &str) -> Result<Vec<u8>, EncodingError> {
+    return CodePage::Cp437.encode(utf8);
 }
 
 #[cfg(test)]
@@ -105,8 +129,10 @@ mod tests {
 
     #[test]
     fn str_to_cp437_err() {
-        let result = to_cp437("🚫");
+        let result = to_cp437("ok🚫");
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "🚫 (U+1F6AB) is not a valid CP437 character");
+        let err = result.unwrap_err();
+        assert_eq!(err, EncodingError { char: '🚫', position: 2, code_page: CodePage::Cp437 });
+        assert_eq!(err.to_string(), "🚫 (U+1F6AB) is not a valid CP437 character");
     }
 }
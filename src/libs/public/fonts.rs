@@ -1,4 +1,4 @@
-//! Fonts used for rendering PNG images
+//! Fonts used for rendering images
 //!
 //! These fonts are free to use under the
 //! [CC-BY-SA-4.0](https://creativecommons.org/licenses/by-sa/4.0) license.
@@ -6,10 +6,19 @@
 //! See <https://int10h.org/oldschool-pc-fonts>
 //!
 
+use indexmap::IndexMap;
 use lazy_static::lazy_static;
 use rust_embed::RustEmbed;
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    fs::read as read_file,
+    str::from_utf8,
+};
 use ttf_parser::Face;
 
+use crate::prelude::CodePage;
+
 #[derive(RustEmbed)]
 #[folder = "$CARGO_MANIFEST_DIR/res/fonts"]
 #[include = "*.otb"]
@@ -37,4 +46,1037 @@ lazy_static! {
     /// See [`ttf_parser::Face`](https://docs.rs/ttf-parser/latest/ttf_parser/struct.Face.html)
     ///
     pub static ref VGA_9X16: Face<'static> = Face::parse(&VGA_9X16_OTB, 0).expect("Valid font");
+
+    /// IBM VGA 8x16 and 9x16 fonts, by code page and width.
+    ///
+    /// The faces only have the glyphs of CP437, so characters from other code
+    /// pages that aren't in it are left blank.
+    ///
+    pub static ref VGA_BITMAPS: IndexMap<(CodePage, u8), BitmapFont> = CodePage::ALL
+        .iter()
+        .flat_map(|code_page| {
+            return [(8, &*VGA_8X16), (9, &*VGA_9X16)].map(|(width, face)| {
+                let font = BitmapFont::from_face(face, (width, 16), *code_page).expect("Valid font");
+                return ((*code_page, width), font);
+            });
+        })
+        .collect();
+}
+
+/// Something went wrong while loading a font.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FontError {
+    /// The font file can't be read, with the path and the reason why.
+    Io(String, String),
+    /// The font ends before the given part of it does.
+    Truncated(&'static str),
+    /// The header of the given format is wrong.
+    BadHeader(&'static str),
+    /// The glyphs don't add up to 256 of the expected length, if known.
+    InvalidSize(Option<usize>, usize),
+    /// The glyphs are wider or taller than 255 pixels.
+    TooLarge(u32, u32),
+    /// The PSF2 glyph length doesn't match its width and height.
+    GlyphLength(usize, u8, u8),
+    /// The BDF font has no `FONTBOUNDINGBOX`.
+    MissingBoundingBox,
+    /// The given part of a BDF font can't be parsed.
+    InvalidBdf(String),
+    /// The face has no glyph for the given byte.
+    MissingGlyph(u8),
+    /// The face has no bitmap at the requested height for the given byte.
+    MissingBitmap(u8),
+}
+
+impl Display for FontError {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        return match self {
+            FontError::Io(path, err) => write!(f, "Can't read font {path}: {err}"),
+            FontError::Truncated(part) => write!(f, "Truncated {part}"),
+            FontError::BadHeader(format) => write!(f, "Invalid {format} header"),
+            FontError::InvalidSize(Some(length), size) => {
+                write!(f, "Invalid font size (expected 256 glyphs of {length} bytes, got {size} bytes)")
+            },
+            FontError::InvalidSize(None, size) => {
+                write!(f, "Invalid font size (expected 256 glyphs, got {size} bytes)")
+            },
+            FontError::TooLarge(width, height) => write!(f, "Glyphs are too large ({width}x{height})"),
+            FontError::GlyphLength(length, width, height) => {
+                write!(f, "Invalid PSF2 glyph size ({length} bytes for {width}x{height})")
+            },
+            FontError::MissingBoundingBox => write!(f, "Missing BDF bounding box"),
+            FontError::InvalidBdf(part) => write!(f, "Invalid BDF {part}"),
+            FontError::MissingGlyph(byte) => write!(f, "Glyph for 0x{byte:02X} is missing"),
+            FontError::MissingBitmap(byte) => write!(f, "Glyph bitmap for 0x{byte:02X} is missing"),
+        };
+    }
+}
+
+impl Error for FontError {}
+
+/// Code pages the IBM fonts come in, as listed in the SAUCE spec.
+///
+/// The first one is the default, when the name has no code page.
+///
+const IBM_CODE_PAGES: &[&str] = &[
+    "437", "720", "737", "775", "819", "850", "852", "855", "857", "858", "860", "861", "862", "863", "864", "865",
+    "866", "869", "872", "KAM", "MAZ", "MIK", "667", "790", "867", "895", "991",
+];
+
+/// A font from the list in the SAUCE spec.
+///
+/// See <https://www.acid.org/info/sauce/sauce.htm#FontName>
+///
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SauceFont {
+    /// Name of the font, without any code page.
+    name: &'static str,
+    /// Width and height of each character, in pixels.
+    size: (u8, u8),
+    /// Code pages the font comes in, if any.
+    code_pages: &'static [&'static str],
+}
+
+impl SauceFont {
+    /// Name of the font, without any code page.
+    #[inline]
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        return self.name;
+    }
+
+    /// Width and height of each character, in pixels.
+    ///
+    /// 9 pixel wide fonts can be drawn 8 pixels wide instead, see
+    /// [`LetterSpacing`](crate::prelude::LetterSpacing).
+    ///
+    #[inline]
+    #[must_use]
+    pub fn size(&self) -> (u8, u8) {
+        return self.size;
+    }
+
+    /// Code pages the font comes in, if any.
+    #[inline]
+    #[must_use]
+    pub fn code_pages(&self) -> &'static [&'static str] {
+        return self.code_pages;
+    }
+}
+
+/// Every font listed in the SAUCE spec.
+pub const SAUCE_FONTS: &[SauceFont] = &[
+    SauceFont { name: "IBM VGA", size: (9, 16), code_pages: IBM_CODE_PAGES },
+    SauceFont { name: "IBM VGA50", size: (9, 8), code_pages: IBM_CODE_PAGES },
+    SauceFont { name: "IBM VGA25G", size: (8, 19), code_pages: IBM_CODE_PAGES },
+    SauceFont { name: "IBM EGA", size: (8, 14), code_pages: IBM_CODE_PAGES },
+    SauceFont { name: "IBM EGA43", size: (8, 8), code_pages: IBM_CODE_PAGES },
+    SauceFont { name: "Amiga Topaz 1", size: (8, 8), code_pages: &[] },
+    SauceFont { name: "Amiga Topaz 1+", size: (8, 8), code_pages: &[] },
+    SauceFont { name: "Amiga Topaz 2", size: (8, 8), code_pages: &[] },
+    SauceFont { name: "Amiga Topaz 2+", size: (8, 8), code_pages: &[] },
+    SauceFont { name: "Amiga P0T-NOoDLE", size: (8, 8), code_pages: &[] },
+    SauceFont { name: "Amiga MicroKnight", size: (8, 8), code_pages: &[] },
+    SauceFont { name: "Amiga MicroKnight+", size: (8, 8), code_pages: &[] },
+    SauceFont { name: "Amiga mOsOul", size: (8, 8), code_pages: &[] },
+    SauceFont { name: "C64 PETSCII unshifted", size: (8, 8), code_pages: &[] },
+    SauceFont { name: "C64 PETSCII shifted", size: (8, 8), code_pages: &[] },
+    SauceFont { name: "Atari ATASCII", size: (8, 8), code_pages: &[] },
+];
+
+/// A font name, as found in the metadata, split into the font and its code
+/// page.
+///
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FontName {
+    /// The font.
+    font: &'static SauceFont,
+    /// The code page, for fonts that come in more than one.
+    code_page: Option<&'static str>,
+}
+
+impl FontName {
+    /// Look a name up in the list of fonts from the SAUCE spec.
+    ///
+    /// IBM fonts without a code page use the default one (`437`), so
+    /// `"IBM VGA"` and `"IBM VGA 437"` are the same font.
+    ///
+    /// # Arguments
+    ///
+    /// * `name`: The font name, exactly as in the metadata.
+    ///
+    #[must_use]
+    pub fn parse(name: &str) -> Option<FontName> {
+        return SAUCE_FONTS.iter().find_map(|font| {
+            let rest = name.strip_prefix(font.name)?;
+            let code_page = if rest.is_empty() {
+                font.code_pages.first().copied()
+            } else {
+                Some(*font.code_pages.iter().find(|code_page| return rest.strip_prefix(' ') == Some(**code_page))?)
+            };
+
+            return Some(FontName { font, code_page });
+        });
+    }
+
+    /// The font.
+    #[inline]
+    #[must_use]
+    pub fn font(&self) -> &'static SauceFont {
+        return self.font;
+    }
+
+    /// The code page, for fonts that come in more than one.
+    #[inline]
+    #[must_use]
+    pub fn code_page(&self) -> Option<&'static str> {
+        return self.code_page;
+    }
+
+    /// The glyphs for this font, for the given width, if they're bundled.
+    ///
+    /// Only IBM VGA is bundled for now, in every code page there's a
+    /// [`CodePage`] for (see [`struct@VGA_BITMAPS`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `width`: The width to draw each character at, 8 or 9.
+    ///
+    #[must_use]
+    pub fn bitmap(&self, width: u8) -> Option<&'static BitmapFont> {
+        if self.font.name != "IBM VGA" {
+            return None;
+        }
+
+        return VGA_BITMAPS.get(&(CodePage::get(self.code_page?)?, width));
+    }
+}
+
+impl Default for FontName {
+    #[inline]
+    fn default() -> FontName {
+        return FontName { font: &SAUCE_FONTS[0], code_page: Some(IBM_CODE_PAGES[0]) };
+    }
+}
+
+impl Display for FontName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        return match self.code_page {
+            Some(code_page) => write!(f, "{} {code_page}", self.font.name),
+            None => write!(f, "{}", self.font.name),
+        };
+    }
+}
+
+/// Check whether a character is one of VGA's line graphics.
+///
+/// When drawing 9 pixel wide characters, VGA fills the extra column by
+/// repeating the last one for these (`0xC0..=0xDF`), so that lines and blocks
+/// connect with their neighbours. Every other character gets a blank column.
+///
+/// # Arguments
+///
+/// * `byte`: The CP437 character.
+///
+#[inline]
+#[must_use]
+pub fn is_line_graphics(byte: u8) -> bool {
+    return (0xC0..=0xDF).contains(&byte);
+}
+
+/// A raw bitmap font, with glyphs stored row by row, each row padded to a whole
+/// byte.
+///
+/// This is the format fonts are stored in the VGA's memory, and what formats
+/// such as XBin embed. Glyphs are always indexed by byte, and so follow
+/// whatever code page the font was loaded for (CP437 unless stated
+/// otherwise).
+///
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BitmapFont {
+    /// Width of each glyph, in pixels.
+    width: u8,
+    /// Height of each glyph, in pixels.
+    height: u8,
+    /// All 256 glyphs, back to back.
+    glyphs: Vec<u8>,
+}
+
+/// Magic bytes of PSF1 fonts.
+const PSF1_MAGIC: [u8; 2] = [0x36, 0x04];
+/// Magic bytes of PSF2 fonts.
+const PSF2_MAGIC: [u8; 4] = [0x72, 0xB5, 0x4A, 0x86];
+
+impl BitmapFont {
+    /// Load a font from its raw glyphs, 8 pixels wide.
+    ///
+    /// # Arguments
+    ///
+    /// * `height`: Height of each glyph, in pixels.
+    /// * `glyphs`: The 256 glyphs, each `height` bytes long.
+    ///
+    /// # Errors
+    ///
+    /// Fails when the glyphs don't match the given height.
+    ///
+    pub fn new(height: u8, glyphs: Vec<u8>) -> Result<BitmapFont, FontError> {
+        return BitmapFont::with_size((8, height), glyphs);
+    }
+
+    /// Load a font from its raw glyphs, of any size.
+    ///
+    /// # Arguments
+    ///
+    /// * `size`: Width and height of each glyph, in pixels.
+    /// * `glyphs`: The 256 glyphs, each row padded to a whole byte.
+    ///
+    /// # Errors
+    ///
+    /// Fails when the glyphs don't match the given size.
+    ///
+    pub fn with_size((width, height): (u8, u8), glyphs: Vec<u8>) -> Result<BitmapFont, FontError> {
+        let length = usize::from(width.div_ceil(8)) * usize::from(height);
+        if width == 0 || height == 0 || glyphs.len() != length * 256 {
+            return Err(FontError::InvalidSize(Some(length), glyphs.len()));
+        }
+
+        return Ok(BitmapFont { width, height, glyphs });
+    }
+
+    /// Load a font from a file, detecting its format.
+    ///
+    /// See [`BitmapFont::parse`].
+    ///
+    /// # Arguments
+    ///
+    /// * `path`: The path to the font file.
+    /// * `code_page`: The code page to pick the glyphs for.
+    ///
+    /// # Errors
+    ///
+    /// Fails when the file can't be read, or it's not a valid font.
+    ///
+    pub fn load(path: &str, code_page: CodePage) -> Result<BitmapFont, FontError> {
+        let bytes = read_file(path).map_err(|err| return FontError::Io(path.to_owned(), err.to_string()))?;
+        return BitmapFont::parse(&bytes, code_page);
+    }
+
+    /// Load a font out of its bytes, detecting its format.
+    ///
+    /// PSF1, PSF2 and BDF fonts are detected by their header, and anything
+    /// else is read as a raw dump of 256 glyphs (the likes of `.F08` or
+    /// `.F16` files).
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes`: The contents of the font file.
+    /// * `code_page`: The code page to pick the glyphs for, when the font
+    ///   says which characters they are. Raw dumps are taken as they are.
+    ///
+    /// # Errors
+    ///
+    /// Fails when the bytes aren't a valid font.
+    ///
+    pub fn parse(bytes: &[u8], code_page: CodePage) -> Result<BitmapFont, FontError> {
+        return if bytes.starts_with(&PSF1_MAGIC) || bytes.starts_with(&PSF2_MAGIC) {
+            BitmapFont::from_psf(bytes, code_page)
+        } else if bytes.starts_with(b"STARTFONT") {
+            BitmapFont::from_bdf(bytes, code_page)
+        } else {
+            BitmapFont::from_raw(bytes)
+        };
+    }
+
+    /// Load a raw dump of 256 glyphs, 8 pixels wide.
+    ///
+    /// The height is deduced from the size, so a 4096 bytes dump has 16
+    /// pixels tall glyphs.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes`: The glyphs.
+    ///
+    /// # Errors
+    ///
+    /// Fails when the size isn't a multiple of 256 bytes.
+    ///
+    pub fn from_raw(bytes: &[u8]) -> Result<BitmapFont, FontError> {
+        #[expect(clippy::integer_division, reason = "Checked below")]
+        let height = bytes.len() / 256;
+        if bytes.len() % 256 != 0 {
+            return Err(FontError::InvalidSize(None, bytes.len()));
+        }
+
+        let too_large = |_| return FontError::TooLarge(8, u32::try_from(height).unwrap_or(u32::MAX));
+        return BitmapFont::new(u8::try_from(height).map_err(too_large)?, bytes.to_vec());
+    }
+
+    /// Load a PC Screen Font, either version 1 or 2.
+    ///
+    /// When the font has a unicode table it's used to find the character of
+    /// each byte in the given code page, otherwise glyphs are taken in order.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes`: The contents of the font file.
+    /// * `code_page`: The code page to pick the glyphs for.
+    ///
+    /// # Errors
+    ///
+    /// Fails when the header is wrong, or the file is truncated.
+    ///
+    pub fn from_psf(bytes: &[u8], code_page: CodePage) -> Result<BitmapFont, FontError> {
+        if let [0x36, 0x04, mode, height, data @ ..] = bytes {
+            let count = if mode & 0x01 == 0 { 256 } else { 512 };
+            let length = count * usize::from(*height);
+            let glyphs = data.get(..length).ok_or(FontError::Truncated("PSF1 font"))?;
+            let table = if mode & 0x06 == 0 { None } else { Some(psf1_table(&data[length..])) };
+
+            return BitmapFont::with_size(
+                (8, *height),
+                by_code_page(glyphs, usize::from(*height), table.as_deref(), code_page),
+            );
+        }
+
+        if bytes.starts_with(&PSF2_MAGIC) {
+            let field = |index: usize| {
+                return bytes
+                    .get(index * 4..index * 4 + 4)
+                    .map(|field| return u32::from_le_bytes([field[0], field[1], field[2], field[3]]))
+                    .ok_or(FontError::Truncated("PSF2 header"));
+            };
+            let (offset, flags, count, length) = (field(2)?, field(3)?, field(4)?, field(5)?);
+            let (height, width) = (field(6)?, field(7)?);
+            let (Ok(height), Ok(width)) = (u8::try_from(height), u8::try_from(width)) else {
+                return Err(FontError::TooLarge(width, height));
+            };
+            // Anything that doesn't fit in memory can't be in the file either
+            let truncated = |_| return FontError::Truncated("PSF2 font");
+            let (offset, count, length) = (
+                usize::try_from(offset).map_err(truncated)?,
+                usize::try_from(count).map_err(truncated)?,
+                usize::try_from(length).map_err(truncated)?,
+            );
+            if length != usize::from(width.div_ceil(8)) * usize::from(height) {
+                return Err(FontError::GlyphLength(length, width, height));
+            }
+            let glyphs = bytes.get(offset..offset + count * length).ok_or(FontError::Truncated("PSF2 font"))?;
+            let table = if flags & 0x01 == 0 { None } else { Some(psf2_table(&bytes[offset + count * length..])) };
+
+            return BitmapFont::with_size((width, height), by_code_page(glyphs, length, table.as_deref(), code_page));
+        }
+
+        return Err(FontError::BadHeader("PSF"));
+    }
+
+    /// Load a Glyph Bitmap Distribution Format font.
+    ///
+    /// Glyphs are placed in the font's bounding box. Their encoding is read
+    /// as unicode when the font says so (`ISO10646`, or `ISO8859-1`) and
+    /// mapped to the given code page, and as the byte itself otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes`: The contents of the font file.
+    /// * `code_page`: The code page to pick the glyphs for.
+    ///
+    /// # Errors
+    ///
+    /// Fails when the font is malformed, or it has no bounding box.
+    ///
+    pub fn from_bdf(bytes: &[u8], code_page: CodePage) -> Result<BitmapFont, FontError> {
+        let text = from_utf8(bytes).map_err(|err| return FontError::InvalidBdf(format!("text ({err})")))?;
+        let mut bounds = None;
+        let (mut registry, mut encoding) = (String::new(), String::new());
+        let mut chars = vec![];
+        let mut lines = text.lines();
+        while let Some(line) = lines.next() {
+            let mut words = line.split_whitespace();
+            match words.next() {
+                Some("FONTBOUNDINGBOX") => bounds = Some(bdf_box(words)?),
+                Some("CHARSET_REGISTRY") => {
+                    registry = words.next().unwrap_or_default().trim_matches('"').to_uppercase();
+                },
+                Some("CHARSET_ENCODING") => {
+                    encoding = words.next().unwrap_or_default().trim_matches('"').to_uppercase();
+                },
+                Some("STARTCHAR") => chars.push(bdf_char(&mut lines)?),
+                _ => {},
+            }
+        }
+
+        let (width, height, left, bottom) = bounds.ok_or(FontError::MissingBoundingBox)?;
+        let (Ok(width), Ok(height)) = (u8::try_from(width), u8::try_from(height)) else {
+            return Err(FontError::TooLarge(
+                u32::try_from(width).unwrap_or(u32::MAX),
+                u32::try_from(height).unwrap_or(u32::MAX),
+            ));
+        };
+        let unicode = registry == "ISO10646" || (registry == "ISO8859" && encoding == "1");
+        let stride = usize::from(width.div_ceil(8));
+        let length = stride * usize::from(height);
+        let mut glyphs = vec![0x00; length * 256];
+        for (code, bbx, rows) in chars {
+            let byte = if unicode {
+                u32::try_from(code)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .and_then(|r#char| return code_page.encode_char(r#char))
+            } else {
+                u8::try_from(code).ok()
+            };
+            let Some(byte) = byte else {
+                continue;
+            };
+
+            let (_, bbx_height, bbx_left, bbx_bottom) = bbx.unwrap_or((0, 0, left, bottom));
+            let top = (i64::from(height) + bottom) - (bbx_bottom + bbx_height);
+            for (y, row) in (top..).zip(rows) {
+                for (x, bit) in (bbx_left - left..).zip(row) {
+                    let (Ok(x), Ok(y)) = (usize::try_from(x), usize::try_from(y)) else {
+                        continue;
+                    };
+                    if bit && x < usize::from(width) && y < usize::from(height) {
+                        #[expect(clippy::integer_division, reason = "Intentional")]
+                        let offset = usize::from(byte) * length + y * stride + x / 8;
+                        glyphs[offset] |= 0x80 >> (x % 8);
+                    }
+                }
+            }
+        }
+
+        return BitmapFont::with_size((width, height), glyphs);
+    }
+
+    /// Load a font out of a font face with embedded bitmaps.
+    ///
+    /// Glyphs are looked up once, by their unicode character, so that the
+    /// font can then be used by byte like any other. Glyphs narrower than the
+    /// requested width get extra columns, see [`raster`], and characters the
+    /// face has no glyph for are left blank.
+    ///
+    /// # Arguments
+    ///
+    /// * `face`: The font face.
+    /// * `size`: The width and height of each glyph.
+    /// * `code_page`: The code page to pick the glyphs for.
+    ///
+    /// # Errors
+    ///
+    /// Fails when the face has no bitmap for some character at that height.
+    ///
+    pub fn from_face(face: &Face, (width, height): (u8, u8), code_page: CodePage) -> Result<BitmapFont, FontError> {
+        let stride = usize::from(width.div_ceil(8));
+        let mut glyphs = vec![];
+        for byte in 0..=255 {
+            let pixels = match raster(face, byte, (width, height), code_page) {
+                Err(FontError::MissingGlyph(_)) => vec![false; usize::from(width) * usize::from(height)],
+                pixels => pixels?,
+            };
+            for row in pixels.chunks(usize::from(width)) {
+                let mut bytes = vec![0x00; stride];
+                for (x, _) in row.iter().enumerate().filter(|(_, bit)| return **bit) {
+                    #[expect(clippy::integer_division, reason = "Intentional")]
+                    let index = x / 8;
+                    bytes[index] |= 0x80 >> (x % 8);
+                }
+                glyphs.extend(bytes);
+            }
+        }
+
+        return BitmapFont::with_size((width, height), glyphs);
+    }
+
+    /// Width of each glyph, in pixels.
+    #[inline]
+    #[must_use]
+    pub fn width(&self) -> u8 {
+        return self.width;
+    }
+
+    /// Height of each glyph, in pixels.
+    #[inline]
+    #[must_use]
+    pub fn height(&self) -> u8 {
+        return self.height;
+    }
+
+    /// Get the rows of a glyph, top to bottom.
+    ///
+    /// Each row takes as many bytes as needed to fit the glyph's width.
+    ///
+    /// # Arguments
+    ///
+    /// * `byte`: The CP437 character.
+    ///
+    #[must_use]
+    pub fn glyph(&self, byte: u8) -> &[u8] {
+        let length = usize::from(self.width.div_ceil(8)) * usize::from(self.height);
+        let offset = usize::from(byte) * length;
+        return &self.glyphs[offset..offset + length];
+    }
+
+    /// Check whether a single pixel of a glyph is set.
+    ///
+    /// # Arguments
+    ///
+    /// * `byte`: The CP437 character.
+    /// * `x`: The column, left to right.
+    /// * `y`: The row, top to bottom.
+    ///
+    #[must_use]
+    pub fn pixel(&self, byte: u8, x: u8, y: u8) -> bool {
+        let stride = usize::from(self.width.div_ceil(8));
+        #[expect(clippy::integer_division, reason = "Intentional")]
+        return x < self.width
+            && y < self.height
+            && (self.glyph(byte)[usize::from(y) * stride + usize::from(x / 8)] >> (7 - x % 8)) & 1 == 1;
+    }
+
+    /// Get whether each pixel of a glyph is set, row by row.
+    ///
+    /// Glyphs narrower than the requested width get extra columns the way VGA
+    /// does it in its 9 dot mode, see [`is_line_graphics`].
+    ///
+    /// # Arguments
+    ///
+    /// * `byte`: The CP437 character.
+    /// * `width`: The width to draw the glyph at, usually 8 or 9.
+    ///
+    #[must_use]
+    pub fn raster(&self, byte: u8, width: u8) -> Vec<bool> {
+        return draw(byte, (width, self.height), self.width, &|x, y| return self.pixel(byte, x, y));
+    }
+}
+
+/// Pick the glyphs for each byte in a code page out of a font.
+///
+/// Without a unicode table, glyphs are assumed to already be in order.
+/// Missing glyphs are left blank.
+fn by_code_page(glyphs: &[u8], length: usize, table: Option<&[Vec<char>]>, code_page: CodePage) -> Vec<u8> {
+    let chars = code_page.table();
+    return (0..256)
+        .flat_map(|byte| {
+            let index = table
+                .and_then(|table| return table.iter().position(|entry| return entry.contains(&chars[byte])))
+                .unwrap_or(byte);
+            return glyphs
+                .get(index * length..(index + 1) * length)
+                .map_or_else(|| return vec![0x00; length], <[u8]>::to_vec);
+        })
+        .collect();
+}
+
+/// Read the unicode table of a PSF1 font, which lists the characters of each
+/// glyph as little endian UCS-2, separated by `0xFFFF`.
+///
+/// Multi-character sequences (after a `0xFFFE`) are skipped.
+///
+fn psf1_table(bytes: &[u8]) -> Vec<Vec<char>> {
+    let mut table = vec![vec![]];
+    let mut sequence = false;
+    for code in bytes.chunks_exact(2).map(|code| return u16::from_le_bytes([code[0], code[1]])) {
+        match code {
+            0xFFFF => {
+                table.push(vec![]);
+                sequence = false;
+            },
+            0xFFFE => sequence = true,
+            _ if !sequence => table.last_mut().expect("Never empty").extend(char::from_u32(u32::from(code))),
+            _ => {},
+        }
+    }
+
+    return table;
+}
+
+/// Read the unicode table of a PSF2 font, which lists the characters of each
+/// glyph as UTF-8, separated by `0xFF`.
+///
+/// Multi-character sequences (after a `0xFE`) are skipped.
+///
+fn psf2_table(bytes: &[u8]) -> Vec<Vec<char>> {
+    return bytes
+        .split(|byte| return *byte == 0xFF)
+        .map(|entry| {
+            let chars = entry.split(|byte| return *byte == 0xFE).next().unwrap_or_default();
+            return String::from_utf8_lossy(chars).chars().filter(|r#char| return *r#char != '\u{FFFD}').collect();
+        })
+        .collect();
+}
+
+/// A BDF bounding box, as its width, height, and left and bottom offsets.
+type BdfBox = (i64, i64, i64, i64);
+
+/// Parse a BDF bounding box out of the rest of its line.
+fn bdf_box<'a>(words: impl Iterator<Item = &'a str>) -> Result<BdfBox, FontError> {
+    let numbers = words.map(str::parse).collect::<Result<Vec<i64>, _>>().unwrap_or_default();
+    return match numbers[..] {
+        [width, height, left, bottom] if width >= 0 && height >= 0 => Ok((width, height, left, bottom)),
+        _ => Err(FontError::InvalidBdf(String::from("bounding box"))),
+    };
+}
+
+/// A BDF character, as its encoding, bounding box (if any), and the pixels of
+/// each row.
+type BdfChar = (i64, Option<BdfBox>, Vec<Vec<bool>>);
+
+/// Parse a BDF character, up to its `ENDCHAR`.
+fn bdf_char<'a>(lines: &mut impl Iterator<Item = &'a str>) -> Result<BdfChar, FontError> {
+    let (mut code, mut bbx, mut rows) = (-1, None, vec![]);
+    let mut bitmap = false;
+    for line in lines.by_ref() {
+        let mut words = line.split_whitespace();
+        match words.next() {
+            Some("ENDCHAR") => return Ok((code, bbx, rows)),
+            Some("ENCODING") => {
+                code = words
+                    .next()
+                    .unwrap_or_default()
+                    .parse()
+                    .map_err(|_| return FontError::InvalidBdf(format!("encoding: {line}")))?;
+            },
+            Some("BBX") => bbx = Some(bdf_box(words)?),
+            Some("BITMAP") => bitmap = true,
+            Some(row) if bitmap => {
+                let bytes = (0..row.len())
+                    .step_by(2)
+                    .map(|i| return u8::from_str_radix(row.get(i..i + 2).unwrap_or_default(), 16))
+                    .collect::<Result<Vec<u8>, _>>()
+                    .map_err(|_| return FontError::InvalidBdf(format!("bitmap: {row}")))?;
+                let width =
+                    bbx.map_or(bytes.len() * 8, |(width, ..)| return usize::try_from(width).unwrap_or_default());
+                #[expect(clippy::integer_division, reason = "Intentional")]
+                rows.push(
+                    (0..width)
+                        .map(|x| return bytes.get(x / 8).is_some_and(|byte| return (byte >> (7 - x % 8)) & 1 == 1))
+                        .collect(),
+                );
+            },
+            _ => {},
+        }
+    }
+
+    return Err(FontError::Truncated("BDF character"));
+}
+
+/// Get whether each pixel of a glyph is set, row by row, out of a font face
+/// with embedded bitmaps.
+///
+/// Glyphs narrower than the requested width get extra columns the way VGA
+/// does it in its 9 dot mode, see [`is_line_graphics`].
+///
+/// # Arguments
+///
+/// * `face`: The font face.
+/// * `byte`: The character, in the given code page.
+/// * `size`: The width and height to draw the glyph at.
+/// * `code_page`: The code page of the character.
+///
+/// # Errors
+///
+/// Fails when the face has no glyph for that character, or no bitmap for it
+/// at that height.
+///
+pub fn raster(face: &Face, byte: u8, (width, height): (u8, u8), code_page: CodePage) -> Result<Vec<bool>, FontError> {
+    let r#char = code_page.table()[usize::from(byte)];
+    let bitmap = face.glyph_index(r#char).ok_or(FontError::MissingGlyph(byte))?;
+    let bitmap = face.glyph_raster_image(bitmap, u16::from(height)).ok_or(FontError::MissingBitmap(byte))?;
+    let own_width = u8::try_from(bitmap.width.min(u16::from(width))).expect("Less than the width");
+
+    return Ok(draw(byte, (width, height), own_width, &|x, y| {
+        let offset = usize::from(y) * usize::from(bitmap.width) + usize::from(x);
+        #[expect(clippy::integer_division, reason = "Intentional")]
+        return bitmap.data.get(offset / 8).is_some_and(|bits| return (bits >> (7 - (offset % 8))) & 1 == 1);
+    }));
+}
+
+/// Lay out a glyph at some width, extending it past its own width if needed.
+fn draw(byte: u8, (width, height): (u8, u8), own_width: u8, pixel: &dyn Fn(u8, u8) -> bool) -> Vec<bool> {
+    let repeat = is_line_graphics(byte) && own_width > 0;
+    return (0..height)
+        .flat_map(|y| {
+            return (0..width).map(move |x| {
+                return if x < own_width { pixel(x, y) } else { repeat && pixel(own_width - 1, y) };
+            });
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn line_graphics() {
+        assert!(!is_line_graphics(0xBF));
+        assert!(is_line_graphics(0xC0));
+        assert!(is_line_graphics(0xDF));
+        assert!(!is_line_graphics(0xE0));
+    }
+
+    #[test]
+    fn bitmap() -> Result<(), FontError> {
+        let mut glyphs = vec![0x00; 256];
+        glyphs[usize::from(b'A')] = 0x81;
+        glyphs[0xC4] = 0x81;
+        let font = BitmapFont::new(1, glyphs)?;
+
+        assert_eq!(font.raster(b'A', 8), [true, false, false, false, false, false, false, true]);
+        assert_eq!(font.raster(b'A', 9), [true, false, false, false, false, false, false, true, false]);
+        assert_eq!(font.raster(0xC4, 9), [true, false, false, false, false, false, false, true, true]);
+
+        return Ok(());
+    }
+
+    #[test]
+    fn wide() -> Result<(), FontError> {
+        let mut glyphs = vec![0x00; 512];
+        glyphs[usize::from(b'A') * 2..usize::from(b'A') * 2 + 2].copy_from_slice(&[0x80, 0x80]);
+        let font = BitmapFont::with_size((9, 1), glyphs)?;
+
+        assert_eq!((font.width(), font.height()), (9, 1));
+        assert_eq!(font.glyph(b'A'), &[0x80, 0x80]);
+        assert_eq!(font.raster(b'A', 9), [true, false, false, false, false, false, false, false, true]);
+        assert_eq!(BitmapFont::with_size((9, 1), vec![0x00; 256]), Err(FontError::InvalidSize(Some(2), 256)),);
+
+        return Ok(());
+    }
+
+    #[test]
+    fn raw() -> Result<(), FontError> {
+        let mut glyphs = vec![0x00; 512];
+        glyphs[usize::from(b'A') * 2] = 0xFF;
+        let font = BitmapFont::parse(&glyphs, CodePage::Cp437)?;
+
+        assert_eq!((font.width(), font.height()), (8, 2));
+        assert_eq!(font.glyph(b'A'), &[0xFF, 0x00]);
+        assert_eq!(
+            BitmapFont::from_raw(&[0x00; 100]).map_err(|err| return err.to_string()),
+            Err(String::from("Invalid font size (expected 256 glyphs, got 100 bytes)")),
+        );
+        assert_eq!(BitmapFont::from_raw(&[]), Err(FontError::InvalidSize(Some(0), 0)));
+        assert_eq!(BitmapFont::from_raw(&vec![0x00; 256 * 256]), Err(FontError::TooLarge(8, 256)));
+
+        return Ok(());
+    }
+
+    #[test]
+    fn psf1() -> Result<(), FontError> {
+        let mut bytes = vec![0x36, 0x04, 0x00, 0x01];
+        bytes.extend(0..=255_u8);
+        let font = BitmapFont::parse(&bytes, CodePage::Cp437)?;
+        assert_eq!((font.width(), font.height()), (8, 1));
+        assert_eq!(font.glyph(b'A'), b"A");
+
+        // With a unicode table, mapping the first glyph to `A` (and to `B`, but
+        // only as part of a sequence).
+        bytes[2] = 0x02;
+        bytes.extend([0x41, 0x00, 0xFE, 0xFF, 0x42, 0x00, 0xFF, 0xFF]);
+        let font = BitmapFont::parse(&bytes, CodePage::Cp437)?;
+        assert_eq!(font.glyph(b'A'), &[0x00]);
+        assert_eq!(font.glyph(b'B'), b"B");
+
+        assert_eq!(BitmapFont::parse(&bytes[..100], CodePage::Cp437), Err(FontError::Truncated("PSF1 font")));
+
+        return Ok(());
+    }
+
+    #[test]
+    fn psf2() -> Result<(), FontError> {
+        let mut bytes = vec![0x72, 0xB5, 0x4A, 0x86];
+        for field in [0, 32, 1, 2, 4, 2, 10] {
+            bytes.extend(u32::to_le_bytes(field));
+        }
+        bytes.extend([0x80, 0x80, 0x00, 0x00, 0xFF, 0xC0, 0x00, 0x00]);
+        bytes.extend("é".as_bytes());
+        bytes.extend([0xFE, b'x', b'y', 0xFF, b'A', 0xFF]);
+        let font = BitmapFont::parse(&bytes, CodePage::Cp437)?;
+
+        assert_eq!((font.width(), font.height()), (10, 2));
+        assert_eq!(font.glyph(0x82), &[0x80, 0x80, 0x00, 0x00]);
+        assert_eq!(font.glyph(b'A'), &[0xFF, 0xC0, 0x00, 0x00]);
+        assert_eq!(font.glyph(b'B'), &[0x00; 4]);
+
+        assert_eq!(BitmapFont::parse(&bytes[..20], CodePage::Cp437), Err(FontError::Truncated("PSF2 header")));
+        assert_eq!(BitmapFont::parse(&bytes[..34], CodePage::Cp437), Err(FontError::Truncated("PSF2 font")));
+        bytes[20] = 0x03;
+        assert_eq!(
+            BitmapFont::parse(&bytes, CodePage::Cp437).map_err(|err| return err.to_string()),
+            Err(String::from("Invalid PSF2 glyph size (3 bytes for 10x2)")),
+        );
+        assert_eq!(BitmapFont::from_psf(b"PSF", CodePage::Cp437), Err(FontError::BadHeader("PSF")));
+
+        return Ok(());
+    }
+
+    #[test]
+    fn code_page() -> Result<(), FontError> {
+        let mut bytes = vec![0x72, 0xB5, 0x4A, 0x86];
+        for field in [0, 32, 1, 1, 1, 1, 8] {
+            bytes.extend(u32::to_le_bytes(field));
+        }
+        bytes.push(0xFF);
+        bytes.extend("Я".as_bytes());
+        bytes.push(0xFF);
+
+        assert_eq!(BitmapFont::parse(&bytes, CodePage::Cp866)?.glyph(0x9F), &[0xFF]);
+        assert_eq!(BitmapFont::parse(&bytes, CodePage::Cp437)?.glyph(0x9F), &[0x00]);
+
+        return Ok(());
+    }
+
+    #[test]
+    fn bdf() -> Result<(), FontError> {
+        let font = BitmapFont::parse(
+            indoc! {"
+                STARTFONT 2.1
+                FONT -test-fixed-medium-r-normal--4-40-75-75-c-40-iso10646-1
+                SIZE 4 75 75
+                FONTBOUNDINGBOX 4 4 0 -1
+                STARTPROPERTIES 2
+                CHARSET_REGISTRY \"ISO10646\"
+                CHARSET_ENCODING \"1\"
+                ENDPROPERTIES
+                CHARS 3
+                STARTCHAR A
+                ENCODING 65
+                DWIDTH 4 0
+                BBX 3 3 0 0
+                BITMAP
+                40
+                A0
+                E0
+                ENDCHAR
+                STARTCHAR uni2588
+                ENCODING 9608
+                BBX 4 4 0 -1
+                BITMAP
+                F0
+                F0
+                F0
+                F0
+                ENDCHAR
+                STARTCHAR uni2603
+                ENCODING 9731
+                BBX 4 4 0 -1
+                BITMAP
+                F0
+                F0
+                F0
+                F0
+                ENDCHAR
+                ENDFONT
+            "}
+            .as_bytes(),
+            CodePage::Cp437,
+        )?;
+
+        assert_eq!((font.width(), font.height()), (4, 4));
+        assert_eq!(font.glyph(b'A'), &[0x40, 0xA0, 0xE0, 0x00]);
+        assert_eq!(font.glyph(0xDB), &[0xF0; 4]);
+        assert_eq!(font.glyph(b'B'), &[0x00; 4]);
+
+        assert_eq!(BitmapFont::parse(b"STARTFONT 2.1\nENDFONT\n", CodePage::Cp437), Err(FontError::MissingBoundingBox));
+        assert_eq!(
+            BitmapFont::parse(b"STARTFONT 2.1\nSTARTCHAR A\nBITMAP\nFF\n", CodePage::Cp437),
+            Err(FontError::Truncated("BDF character")),
+        );
+        assert_eq!(
+            BitmapFont::parse(b"STARTFONT 2.1\nFONTBOUNDINGBOX 4 x 0 0\n", CodePage::Cp437),
+            Err(FontError::InvalidBdf(String::from("bounding box"))),
+        );
+        assert_eq!(
+            BitmapFont::parse(b"STARTFONT 2.1\nFONTBOUNDINGBOX 300 4 0 0\n", CodePage::Cp437),
+            Err(FontError::TooLarge(300, 4)),
+        );
+
+        return Ok(());
+    }
+
+    #[test]
+    fn load() -> Result<(), FontError> {
+        let font = BitmapFont::load("res/test/font.psf", CodePage::Cp437)?;
+        assert_eq!((font.width(), font.height()), (8, 8));
+        assert!(BitmapFont::load("res/test/missing.psf", CodePage::Cp437)
+            .is_err_and(|err| return matches!(err, FontError::Io(path, _) if path == "res/test/missing.psf")));
+
+        return Ok(());
+    }
+
+    #[test]
+    fn font_name() {
+        assert_eq!(FontName::parse("IBM VGA"), Some(FontName::default()));
+        assert_eq!(FontName::parse("IBM VGA 437"), Some(FontName::default()));
+        assert_eq!(
+            FontName::parse("IBM VGA 437").map(|name| return name.to_string()),
+            Some(String::from("IBM VGA 437"))
+        );
+        assert_eq!(
+            FontName::parse("IBM EGA43 866").map(|name| return (name.font().size(), name.code_page())),
+            Some(((8, 8), Some("866"))),
+        );
+        assert_eq!(
+            FontName::parse("Amiga Topaz 1+").map(|name| return (name.font().name(), name.to_string())),
+            Some(("Amiga Topaz 1+", String::from("Amiga Topaz 1+"))),
+        );
+        assert_eq!(FontName::parse("Amiga Topaz 1+ 437"), None);
+        assert_eq!(FontName::parse("IBM VGA437"), None);
+        assert_eq!(FontName::parse("IBM VGA 123"), None);
+        assert_eq!(FontName::parse("ibm vga"), None);
+        assert_eq!(FontName::parse(""), None);
+    }
+
+    #[test]
+    fn font_name_bitmap() {
+        assert_eq!(FontName::default().bitmap(8), VGA_BITMAPS.get(&(CodePage::Cp437, 8)));
+        assert_eq!(FontName::default().bitmap(9), VGA_BITMAPS.get(&(CodePage::Cp437, 9)));
+        assert_eq!(FontName::default().bitmap(9).map(BitmapFont::width), Some(9));
+        assert_eq!(
+            FontName::parse("IBM VGA 850").and_then(|name| return name.bitmap(9)),
+            VGA_BITMAPS.get(&(CodePage::Cp850, 9)),
+        );
+        assert_eq!(FontName::parse("IBM VGA MIK").and_then(|name| return name.bitmap(9)), None);
+        assert_eq!(FontName::parse("IBM EGA").and_then(|name| return name.bitmap(8)), None);
+    }
+
+    #[test]
+    fn face() -> Result<(), FontError> {
+        let cp437 = CodePage::Cp437;
+        assert_eq!(raster(&VGA_8X16, b'A', (8, 16), cp437)?, raster(&VGA_9X16, b'A', (8, 16), cp437)?);
+        for byte in 0xC0..=0xDF {
+            assert_eq!(
+                raster(&VGA_8X16, byte, (9, 16), cp437)?,
+                raster(&VGA_9X16, byte, (9, 16), cp437)?,
+                "0x{byte:02X}",
+            );
+        }
+        for byte in 0x00..=0xFF {
+            assert_eq!(VGA_BITMAPS[&(cp437, 8)].raster(byte, 8), raster(&VGA_8X16, byte, (8, 16), cp437)?);
+            assert_eq!(VGA_BITMAPS[&(cp437, 9)].raster(byte, 9), raster(&VGA_9X16, byte, (9, 16), cp437)?);
+        }
+
+        return Ok(());
+    }
+
+    #[test]
+    fn face_code_page() -> Result<(), FontError> {
+        // `░` is at the same place in both, `Ç` is `А` in CP866, and the face
+        // only has the glyphs of CP437
+        let (cp437, cp866) = (CodePage::Cp437, CodePage::Cp866);
+        assert_eq!(raster(&VGA_9X16, 0xB0, (9, 16), cp866)?, raster(&VGA_9X16, 0xB0, (9, 16), cp437)?);
+        assert_eq!(raster(&VGA_9X16, 0x80, (9, 16), cp866), Err(FontError::MissingGlyph(0x80)));
+        assert_eq!(VGA_BITMAPS[&(cp866, 9)].glyph(0x80), &[0x00; 32]);
+        assert_eq!(VGA_BITMAPS[&(cp866, 9)].glyph(b'A'), VGA_BITMAPS[&(cp437, 9)].glyph(b'A'));
+
+        // `Σ` is in both, but at a different place
+        let cp737 = CodePage::Cp737;
+        assert_eq!(raster(&VGA_8X16, 0x91, (8, 16), cp737)?, raster(&VGA_8X16, 0xE4, (8, 16), cp437)?);
+
+        return Ok(());
+    }
 }
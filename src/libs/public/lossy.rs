@@ -0,0 +1,314 @@
+//! Lossy UTF-8 to code page encoding.
+//!
+//! Text typed in a modern editor tends to have a few characters that don't
+//! exist in any DOS code page (e.g., curly quotes or em dashes). Instead of
+//! giving up on them, they can be replaced, either with a plain `?` or with
+//! the closest looking character.
+
+use std::fmt::{self, Display, Formatter};
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization as _};
+
+use crate::prelude::{CodePage, EncodingError};
+
+/// What to do with characters that don't exist in the code page.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Policy {
+    /// Fail on the first one.
+    #[default]
+    Strict,
+    /// Replace them with `?`.
+    Replace,
+    /// Replace them with the closest looking characters, or `?` if there's
+    /// none.
+    Transliterate,
+}
+
+impl Policy {
+    /// Find a policy by its name (i.e., `strict`, `replace`, or
+    /// `transliterate`), ignoring case.
+    ///
+    /// # Arguments
+    ///
+    /// * `name`: The name of the policy.
+    ///
+    #[must_use]
+    pub fn get(name: &str) -> Option<Policy> {
+        return match name.to_lowercase().as_str() {
+            "strict" => Some(Policy::Strict),
+            "replace" => Some(Policy::Replace),
+            "transliterate" => Some(Policy::Transliterate),
+            _ => None,
+        };
+    }
+}
+
+/// A character that had to be replaced while encoding.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Substitution {
+    /// The original character.
+    pub char: char,
+    /// Where the character was found, counted in characters (not bytes).
+    pub position: usize,
+    /// What was written instead, as UTF-8.
+    pub replacement: String,
+}
+
+impl Display for Substitution {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        return write!(f, "{} (U+{:X}) was replaced with {}", self.char, self.char as u32, self.replacement);
+    }
+}
+
+/// The result of a lossy encoding.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Encoded {
+    /// The encoded bytes.
+    pub bytes: Vec<u8>,
+    /// Every character that was replaced, in order.
+    pub substitutions: Vec<Substitution>,
+}
+
+impl CodePage {
+    /// Convert a string to bytes in this code page, replacing the characters
+    /// that don't exist in it as the policy says.
+    ///
+    /// When transliterating, decomposed characters (e.g., `e` followed by a
+    /// combining acute accent) are composed first, and aren't reported unless
+    /// they still need replacing.
+    ///
+    /// # Arguments
+    ///
+    /// * `utf8`: The string to convert.
+    /// * `policy`: What to do with the characters that don't exist.
+    ///
+    /// # Errors
+    ///
+    /// Fails when there's no equivalent character and the policy is
+    /// [`Policy::Strict`].
+    ///
+    pub fn encode_lossy(self, utf8: &str, policy: Policy) -> Result<Encoded, EncodingError> {
+        let chars = utf8.chars().collect::<Vec<_>>();
+        let mut encoded = Encoded::default();
+        let mut position = 0;
+        while position < chars.len() {
+            let cluster = if policy == Policy::Transliterate {
+                let marks = chars[position + 1..].iter().take_while(|r#char| return is_combining_mark(**r#char));
+                &chars[position..=position + marks.count()]
+            } else {
+                &chars[position..=position]
+            };
+
+            for r#char in cluster.iter().copied().nfc() {
+                if let Some(byte) = self.encode_char(r#char) {
+                    encoded.bytes.push(byte);
+                    continue;
+                }
+
+                let replacement = match policy {
+                    Policy::Strict => return Err(EncodingError { r#char, position, code_page: self }),
+                    Policy::Replace => String::from("?"),
+                    Policy::Transliterate => self.transliterate(r#char).unwrap_or_else(|| return String::from("?")),
+                };
+                encoded.bytes.extend(replacement.chars().filter_map(|r#char| return self.encode_char(r#char)));
+                encoded.substitutions.push(Substitution { r#char, position, replacement });
+            }
+
+            position += cluster.len();
+        }
+
+        return Ok(encoded);
+    }
+
+    /// Find the closest looking characters that exist in this code page.
+    ///
+    /// Tries a known lookalike first, and then the compatibility
+    /// decomposition without any accents (e.g., `ő` becomes `o`, and `…`
+    /// becomes `...`).
+    ///
+    fn transliterate(self, r#char: char) -> Option<String> {
+        let encodable = |string: &str| return string.chars().all(|r#char| return self.encode_char(r#char).is_some());
+
+        if let Some(lookalike) = lookalike(r#char).filter(|lookalike| return encodable(lookalike)) {
+            return Some(lookalike.to_owned());
+        }
+
+        let decomposed = r#char
+            .nfkd()
+            .filter(|r#char| return !is_combining_mark(*r#char))
+            .map(|r#char| {
+                return if self.encode_char(r#char).is_some() {
+                    Some(String::from(r#char))
+                } else {
+                    lookalike(r#char).filter(|lookalike| return encodable(lookalike)).map(str::to_owned)
+                };
+            })
+            .collect::<Option<String>>()?;
+
+        return (!decomposed.is_empty()).then_some(decomposed);
+    }
+}
+
+/// A replacement for characters that are commonly typed but missing from DOS
+/// code pages, mostly typographic punctuation and box drawing.
+#[rustfmt::skip]
+fn lookalike(r#char: char) -> Option<&'static str> {
+    return Some(match r#char {
+        // Quotes.
+        '‘' | '’' | '‚' | '‛' | '′' => "'",
+        '“' | '”' | '„' | '‟' | '″' | '«' | '»' => "\"",
+        '‹' => "<",
+        '›' => ">",
+        // Dashes, hyphens, and spaces.
+        '‐' | '‑' | '‒' | '–' | '—' | '―' | '−' | '⁃' => "-",
+        '\u{2000}'..='\u{200A}' | '\u{202F}' | '\u{205F}' | '\u{3000}' => " ",
+        '\u{200B}' | '\u{200C}' | '\u{200D}' | '\u{2060}' | '\u{FEFF}' => "",
+        '⁄' | '∕' | '╱' => "/",
+        '∖' | '╲' => "\\",
+        '×' | '✗' | '✘' => "x",
+        '…' => "...",
+        // Symbols.
+        '©' => "(C)",
+        '®' => "(R)",
+        '™' => "TM",
+        '€' => "EUR",
+        '✓' | '✔' => "√",
+        '←' => "◄",
+        '→' => "►",
+        '▪' | '◼' | '◾' => "■",
+        '●' | '◦' | '‣' | '⁌' | '⁍' => "•",
+        // Box drawing, heavy lines becoming double ones.
+        '╴' | '╶' | '╼' | '╾' | '┄' | '┈' | '╌' => "─",
+        '╵' | '╷' | '╽' | '╿' | '┆' | '┊' | '╎' => "│",
+        '━' | '┅' | '┉' | '╍' => "═",
+        '┃' | '┇' | '┋' | '╏' => "║",
+        '╭' | '┍' | '┎' => "┌",
+        '╮' | '┑' | '┒' => "┐",
+        '╰' | '┕' | '┖' => "└",
+        '╯' | '┙' | '┚' => "┘",
+        '┏' => "╔",
+        '┓' => "╗",
+        '┗' => "╚",
+        '┛' => "╝",
+        '┣' => "╠",
+        '┫' => "╣",
+        '┳' => "╦",
+        '┻' => "╩",
+        '╋' => "╬",
+        '╳' => "X",
+        // Blocks.
+        '▔' => "▀",
+        '▁' | '▂' | '▃' | '▅' => "▄",
+        '▆' | '▇' => "█",
+        '▏' | '▎' | '▍' | '▋' => "▌",
+        '▕' | '▊' | '▉' => "▐",
+        _ => return None,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn get() {
+        assert_eq!(Policy::get("strict"), Some(Policy::Strict));
+        assert_eq!(Policy::get("Replace"), Some(Policy::Replace));
+        assert_eq!(Policy::get("TRANSLITERATE"), Some(Policy::Transliterate));
+        assert_eq!(Policy::get("foo"), None);
+    }
+
+    #[test]
+    fn strict() {
+        assert_eq!(
+            CodePage::Cp437.encode_lossy("ok", Policy::Strict),
+            Ok(Encoded { bytes: b"ok".to_vec(), substitutions: vec![] })
+        );
+        assert_eq!(
+            CodePage::Cp437.encode_lossy("ok🚫", Policy::Strict),
+            Err(EncodingError { r#char: '🚫', position: 2, code_page: CodePage::Cp437 }),
+        );
+    }
+
+    #[test]
+    fn replace() {
+        assert_eq!(
+            CodePage::Cp437.encode_lossy("a“b”", Policy::Replace),
+            Ok(Encoded {
+                bytes: b"a?b?".to_vec(),
+                substitutions: vec![
+                    Substitution { r#char: '“', position: 1, replacement: String::from("?") },
+                    Substitution { r#char: '”', position: 3, replacement: String::from("?") },
+                ],
+            }),
+        );
+    }
+
+    #[test]
+    fn punctuation() -> Result<(), EncodingError> {
+        let encoded = CodePage::Cp437.encode_lossy("“It’s—fine…”", Policy::Transliterate)?;
+
+        assert_eq!(encoded.bytes, b"\"It's-fine...\"");
+        assert_eq!(
+            encoded.substitutions.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            [
+                "“ (U+201C) was replaced with \"",
+                "’ (U+2019) was replaced with '",
+                "— (U+2014) was replaced with -",
+                "… (U+2026) was replaced with ...",
+                "” (U+201D) was replaced with \"",
+            ],
+        );
+
+        return Ok(());
+    }
+
+    #[test]
+    fn normalisation() -> Result<(), EncodingError> {
+        let encoded = CodePage::Cp437.encode_lossy("Cafe\u{301} Ő ﬁ Ａ", Policy::Transliterate)?;
+
+        assert_eq!(encoded.bytes, b"Caf\x82 O fi A");
+        assert_eq!(
+            encoded.substitutions,
+            [
+                Substitution { r#char: 'Ő', position: 6, replacement: String::from("O") },
+                Substitution { r#char: 'ﬁ', position: 8, replacement: String::from("fi") },
+                Substitution { r#char: 'Ａ', position: 10, replacement: String::from("A") },
+            ],
+        );
+
+        return Ok(());
+    }
+
+    #[test]
+    fn lookalikes() -> Result<(), EncodingError> {
+        let encoded = CodePage::Cp437.encode_lossy("╭━╮✓→", Policy::Transliterate)?;
+
+        assert_eq!(CodePage::Cp437.decode(&encoded.bytes), "┌═┐√►");
+        assert_eq!(encoded.substitutions.len(), 5);
+
+        return Ok(());
+    }
+
+    #[test]
+    fn code_page() -> Result<(), EncodingError> {
+        let encoded = CodePage::Cp866.encode_lossy("«Привет»", Policy::Transliterate)?;
+
+        assert_eq!(CodePage::Cp866.decode(&encoded.bytes), "\"Привет\"");
+        assert_eq!(encoded.substitutions.len(), 2);
+
+        return Ok(());
+    }
+
+    #[test]
+    fn unknown() -> Result<(), EncodingError> {
+        let encoded = CodePage::Cp437.encode_lossy("🚫", Policy::Transliterate)?;
+
+        assert_eq!(encoded.bytes, b"?");
+        assert_eq!(encoded.substitutions, [Substitution { r#char: '🚫', position: 0, replacement: String::from("?") }],);
+
+        return Ok(());
+    }
+}
@@ -3,23 +3,34 @@
 //! See <https://web.archive.org/web/20250427042053id_/https://www.acid.org/info/sauce/sauce.htm>
 //!
 
-use chrono::NaiveDate;
+use chrono::{NaiveDate, ParseError};
 use std::{
-    array::TryFromSliceError,
+    error::Error,
+    fmt::{self, Display, Formatter},
     fs::File,
-    io::{Read as _, Seek as _, SeekFrom},
-    str,
+    io::{self, Cursor, Read, Seek, SeekFrom, Write},
+    iter::once,
+    ops::Range,
+    str::{self, FromStr},
 };
 use ttf_parser::Face;
 
 use crate::{
-    fonts,
-    prelude::{to_utf8, CP437_TO_UTF8},
+    canvas::{Canvas, Extent},
+    code_page::CodePage,
+    fonts::{self, BitmapFont, FontName, SAUCE_FONTS},
+    prelude::{to_cp437, to_utf8, xbin, EncodingError, CP437_TO_UTF8},
 };
 
 /// A structure representing a file's metadata.
+///
+/// With the `serde` feature, dates are (de)serialised in ISO format, types by
+/// name, and flags symbolically (see [`SauceFlags`]). Missing fields default
+/// to those in [`Meta::default`].
+///
 #[doc(alias = "Sauce")]
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default))]
 pub struct Meta {
     /// The image's title.
     pub title: String,
@@ -29,23 +40,43 @@ pub struct Meta {
     #[doc(alias = "team")]
     pub group: String,
     /// The image creation date, in the YYYYMMDD format.
+    #[cfg_attr(feature = "serde", serde(with = "serde_date"))]
     pub date: String,
     /// The size of the file, sans this metadata.
     pub size: u32,
     /// The type of this file.
     ///
     /// Only supported values are:
-    /// * `(0, 0)` → `None` (effectively, `Character/ANSI`)
-    /// * `(1, 0)` → `Character/ASCII`
-    /// * `(1, 1)` → `Character/ANSI`
-    ///
-    /// See <https://web.archive.org/web/20250427042053id_/https://www.acid.org/info/sauce/sauce.htm#FileType>
+    /// * [`FileType::None`] (effectively, [`FileType::Ansi`])
+    /// * [`FileType::Ascii`]
+    /// * [`FileType::Ansi`]
+    /// * [`FileType::BinaryText`]
+    /// * [`FileType::XBin`]
     ///
-    pub r#type: (u8, u8),
+    #[doc(alias = "DataType")]
+    #[doc(alias = "FileType")]
+    pub r#type: FileType,
     /// Width of the image.
+    ///
+    /// Its meaning depends on the type, see [`Meta::info`].
+    ///
+    #[doc(alias = "TInfo1")]
     pub width: u16,
     /// Height of the image.
+    ///
+    /// Its meaning depends on the type, see [`Meta::info`].
+    ///
+    #[doc(alias = "TInfo2")]
     pub height: u16,
+    /// A third type dependent value, e.g. the colour depth of a bitmap.
+    ///
+    /// See [`Meta::info`].
+    ///
+    #[doc(alias = "TInfo3")]
+    pub tinfo3: u16,
+    /// A fourth type dependent value, unused by all known types.
+    #[doc(alias = "TInfo4")]
+    pub tinfo4: u16,
     /// A bitfield of flags that define how to process an image.
     ///
     /// See <https://web.archive.org/web/20250427042053id_/https://www.acid.org/info/sauce/sauce.htm#ANSiFlags>
@@ -57,17 +88,671 @@ pub struct Meta {
     #[doc(alias = "B")]
     #[doc(alias = "iCE colour")]
     #[doc(alias = "non-blink mode")]
+    #[doc(alias = "TFlags")]
+    #[cfg_attr(feature = "serde", serde(with = "serde_flags"))]
     pub flags: u8,
     /// The name of the font this image uses.
     ///
     /// Only IBM VGA is supported.
     ///
+    #[doc(alias = "TInfoS")]
     pub font: String,
     /// A list of comments on this image.
     #[doc(alias = "comments")]
     pub notes: Vec<String>,
 }
 
+/// The kind of data a file holds, as the first half of its type.
+///
+/// See <https://web.archive.org/web/20250427042053id_/https://www.acid.org/info/sauce/sauce.htm#DataType>
+///
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DataType {
+    /// Undefined.
+    None,
+    /// Text based formats.
+    Character,
+    /// Raster images and animations.
+    Bitmap,
+    /// Vector graphics.
+    Vector,
+    /// Music and sound.
+    Audio,
+    /// Raw VGA text mode memory.
+    BinaryText,
+    /// XBin images.
+    XBin,
+    /// Compressed archives.
+    Archive,
+    /// Programs.
+    Executable,
+    /// Anything else.
+    Unknown(u8),
+}
+
+/// Every known data type, along with its raw value and name.
+const DATA_TYPES: [(DataType, u8, &str); 9] = [
+    (DataType::None, 0, "None"),
+    (DataType::Character, 1, "Character"),
+    (DataType::Bitmap, 2, "Bitmap"),
+    (DataType::Vector, 3, "Vector"),
+    (DataType::Audio, 4, "Audio"),
+    (DataType::BinaryText, 5, "BinaryText"),
+    (DataType::XBin, 6, "XBin"),
+    (DataType::Archive, 7, "Archive"),
+    (DataType::Executable, 8, "Executable"),
+];
+
+impl From<u8> for DataType {
+    fn from(value: u8) -> DataType {
+        return DATA_TYPES
+            .iter()
+            .find(|(_, raw, _)| return *raw == value)
+            .map_or(DataType::Unknown(value), |(data_type, _, _)| return *data_type);
+    }
+}
+
+impl From<DataType> for u8 {
+    fn from(value: DataType) -> u8 {
+        return match value {
+            DataType::Unknown(raw) => raw,
+            _ => DATA_TYPES
+                .iter()
+                .find(|(data_type, _, _)| return *data_type == value)
+                .map_or(0, |(_, raw, _)| return *raw),
+        };
+    }
+}
+
+impl Display for DataType {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        return match self {
+            DataType::Unknown(raw) => write!(f, "Unknown {raw}"),
+            _ => write!(
+                f,
+                "{}",
+                DATA_TYPES
+                    .iter()
+                    .find(|(data_type, _, _)| return data_type == self)
+                    .map_or("", |(_, _, name)| return name)
+            ),
+        };
+    }
+}
+
+impl FromStr for DataType {
+    type Err = TypeError;
+
+    fn from_str(value: &str) -> Result<DataType, TypeError> {
+        if let Some(raw) = value.strip_prefix("Unknown ").and_then(|raw| return raw.parse::<u8>().ok()) {
+            return Ok(DataType::from(raw));
+        }
+
+        return DATA_TYPES
+            .iter()
+            .find(|(_, _, name)| return name.eq_ignore_ascii_case(value))
+            .map(|(data_type, _, _)| return *data_type)
+            .ok_or_else(|| return TypeError(value.to_string()));
+    }
+}
+
+serde_as_string!(DataType);
+
+/// The format of a file, as the whole of its type.
+///
+/// See <https://web.archive.org/web/20250427042053id_/https://www.acid.org/info/sauce/sauce.htm#FileType>
+///
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FileType {
+    /// Undefined.
+    None,
+    /// Plain text.
+    Ascii,
+    /// Text with ANSI escape sequences.
+    Ansi,
+    /// Text with ANSI escape sequences, meant to be played as an animation.
+    AnsiMation,
+    /// RIPscript vector graphics.
+    RipScript,
+    /// Text with PCBoard colour codes.
+    PcBoard,
+    /// Text with Avatar colour codes.
+    Avatar,
+    /// HTML.
+    Html,
+    /// Source code.
+    Source,
+    /// TundraDraw images.
+    TundraDraw,
+    /// GIF images.
+    Gif,
+    /// PCX images.
+    Pcx,
+    /// LBM/IFF images.
+    LbmIff,
+    /// TGA images.
+    Tga,
+    /// FLI animations.
+    Fli,
+    /// FLC animations.
+    Flc,
+    /// BMP images.
+    Bmp,
+    /// GL animations.
+    Gl,
+    /// DL animations.
+    Dl,
+    /// WPG raster images.
+    WpgBitmap,
+    /// PNG images.
+    Png,
+    /// JPEG images.
+    Jpg,
+    /// MPEG videos.
+    Mpg,
+    /// AVI videos.
+    Avi,
+    /// DXF drawings.
+    Dxf,
+    /// DWG drawings.
+    Dwg,
+    /// WPG vector images.
+    WpgVector,
+    /// 3D Studio models.
+    ThreeDs,
+    /// ProTracker modules.
+    Mod,
+    /// Composer 669 modules.
+    Composer669,
+    /// ScreamTracker 2 modules.
+    Stm,
+    /// ScreamTracker 3 modules.
+    S3m,
+    /// MultiTracker modules.
+    Mtm,
+    /// Farandole modules.
+    Far,
+    /// UltraTracker modules.
+    Ult,
+    /// DSMI modules.
+    Amf,
+    /// Delusion Digital Music Format modules.
+    Dmf,
+    /// Oktalyser modules.
+    Okt,
+    /// AdLib ROL music.
+    Rol,
+    /// Creative Music Files.
+    Cmf,
+    /// MIDI music.
+    Midi,
+    /// SAdT music.
+    Sadt,
+    /// Creative Voice Files.
+    Voc,
+    /// WAV sounds.
+    Wav,
+    /// Raw 8 bit mono samples.
+    Smp8,
+    /// Raw 8 bit stereo samples.
+    Smp8s,
+    /// Raw 16 bit mono samples.
+    Smp16,
+    /// Raw 16 bit stereo samples.
+    Smp16s,
+    /// 8 bit Gravis patches.
+    Patch8,
+    /// 16 bit Gravis patches.
+    Patch16,
+    /// FastTracker 2 modules.
+    Xm,
+    /// HSC-Tracker modules.
+    Hsc,
+    /// Impulse Tracker modules.
+    It,
+    /// BinaryText, with half its width, or `0` if undefined.
+    BinaryText(u8),
+    /// XBin images.
+    XBin,
+    /// ZIP archives.
+    Zip,
+    /// ARJ archives.
+    Arj,
+    /// LHA archives.
+    Lzh,
+    /// ARC archives.
+    Arc,
+    /// Tarballs.
+    Tar,
+    /// ZOO archives.
+    Zoo,
+    /// RAR archives.
+    Rar,
+    /// UltraCompressor II archives.
+    Uc2,
+    /// PAK archives.
+    Pak,
+    /// SQZ archives.
+    Sqz,
+    /// Programs.
+    Executable,
+    /// Anything else, as its raw data type and file type.
+    Unknown(u8, u8),
+}
+
+/// Every known file type, along with its raw value and name.
+///
+/// BinaryText is left out, since its file type is a width instead.
+///
+const FILE_TYPES: [(FileType, (u8, u8), &str); 65] = [
+    (FileType::None, (0, 0), "None"),
+    (FileType::Ascii, (1, 0), "Character/ASCII"),
+    (FileType::Ansi, (1, 1), "Character/ANSi"),
+    (FileType::AnsiMation, (1, 2), "Character/ANSiMation"),
+    (FileType::RipScript, (1, 3), "Character/RIPScript"),
+    (FileType::PcBoard, (1, 4), "Character/PCBoard"),
+    (FileType::Avatar, (1, 5), "Character/Avatar"),
+    (FileType::Html, (1, 6), "Character/HTML"),
+    (FileType::Source, (1, 7), "Character/Source"),
+    (FileType::TundraDraw, (1, 8), "Character/TundraDraw"),
+    (FileType::Gif, (2, 0), "Bitmap/GIF"),
+    (FileType::Pcx, (2, 1), "Bitmap/PCX"),
+    (FileType::LbmIff, (2, 2), "Bitmap/LBM"),
+    (FileType::Tga, (2, 3), "Bitmap/TGA"),
+    (FileType::Fli, (2, 4), "Bitmap/FLI"),
+    (FileType::Flc, (2, 5), "Bitmap/FLC"),
+    (FileType::Bmp, (2, 6), "Bitmap/BMP"),
+    (FileType::Gl, (2, 7), "Bitmap/GL"),
+    (FileType::Dl, (2, 8), "Bitmap/DL"),
+    (FileType::WpgBitmap, (2, 9), "Bitmap/WPG"),
+    (FileType::Png, (2, 10), "Bitmap/PNG"),
+    (FileType::Jpg, (2, 11), "Bitmap/JPG"),
+    (FileType::Mpg, (2, 12), "Bitmap/MPG"),
+    (FileType::Avi, (2, 13), "Bitmap/AVI"),
+    (FileType::Dxf, (3, 0), "Vector/DXF"),
+    (FileType::Dwg, (3, 1), "Vector/DWG"),
+    (FileType::WpgVector, (3, 2), "Vector/WPG"),
+    (FileType::ThreeDs, (3, 3), "Vector/3DS"),
+    (FileType::Mod, (4, 0), "Audio/MOD"),
+    (FileType::Composer669, (4, 1), "Audio/669"),
+    (FileType::Stm, (4, 2), "Audio/STM"),
+    (FileType::S3m, (4, 3), "Audio/S3M"),
+    (FileType::Mtm, (4, 4), "Audio/MTM"),
+    (FileType::Far, (4, 5), "Audio/FAR"),
+    (FileType::Ult, (4, 6), "Audio/ULT"),
+    (FileType::Amf, (4, 7), "Audio/AMF"),
+    (FileType::Dmf, (4, 8), "Audio/DMF"),
+    (FileType::Okt, (4, 9), "Audio/OKT"),
+    (FileType::Rol, (4, 10), "Audio/ROL"),
+    (FileType::Cmf, (4, 11), "Audio/CMF"),
+    (FileType::Midi, (4, 12), "Audio/MID"),
+    (FileType::Sadt, (4, 13), "Audio/SADT"),
+    (FileType::Voc, (4, 14), "Audio/VOC"),
+    (FileType::Wav, (4, 15), "Audio/WAV"),
+    (FileType::Smp8, (4, 16), "Audio/SMP8"),
+    (FileType::Smp8s, (4, 17), "Audio/SMP8S"),
+    (FileType::Smp16, (4, 18), "Audio/SMP16"),
+    (FileType::Smp16s, (4, 19), "Audio/SMP16S"),
+    (FileType::Patch8, (4, 20), "Audio/PATCH8"),
+    (FileType::Patch16, (4, 21), "Audio/PATCH16"),
+    (FileType::Xm, (4, 22), "Audio/XM"),
+    (FileType::Hsc, (4, 23), "Audio/HSC"),
+    (FileType::It, (4, 24), "Audio/IT"),
+    (FileType::XBin, (6, 0), "XBin"),
+    (FileType::Zip, (7, 0), "Archive/ZIP"),
+    (FileType::Arj, (7, 1), "Archive/ARJ"),
+    (FileType::Lzh, (7, 2), "Archive/LZH"),
+    (FileType::Arc, (7, 3), "Archive/ARC"),
+    (FileType::Tar, (7, 4), "Archive/TAR"),
+    (FileType::Zoo, (7, 5), "Archive/ZOO"),
+    (FileType::Rar, (7, 6), "Archive/RAR"),
+    (FileType::Uc2, (7, 7), "Archive/UC2"),
+    (FileType::Pak, (7, 8), "Archive/PAK"),
+    (FileType::Sqz, (7, 9), "Archive/SQZ"),
+    (FileType::Executable, (8, 0), "Executable"),
+];
+
+impl FileType {
+    /// The kind of data this type of file holds.
+    #[must_use]
+    pub fn data_type(self) -> DataType {
+        return DataType::from(<(u8, u8)>::from(self).0);
+    }
+}
+
+impl From<(u8, u8)> for FileType {
+    fn from(value: (u8, u8)) -> FileType {
+        if value.0 == 5 {
+            return FileType::BinaryText(value.1);
+        }
+
+        return FILE_TYPES
+            .iter()
+            .find(|(_, raw, _)| return *raw == value)
+            .map_or(FileType::Unknown(value.0, value.1), |(file_type, _, _)| return *file_type);
+    }
+}
+
+impl From<FileType> for (u8, u8) {
+    fn from(value: FileType) -> (u8, u8) {
+        return match value {
+            FileType::BinaryText(half_width) => (5, half_width),
+            FileType::Unknown(data_type, file_type) => (data_type, file_type),
+            _ => FILE_TYPES
+                .iter()
+                .find(|(file_type, _, _)| return *file_type == value)
+                .map_or((0, 0), |(_, raw, _)| return *raw),
+        };
+    }
+}
+
+impl Display for FileType {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        return match self {
+            FileType::BinaryText(0) => write!(f, "BinaryText"),
+            FileType::BinaryText(half_width) => write!(f, "BinaryText/{}", u16::from(*half_width) * 2),
+            FileType::Unknown(data_type, file_type) => {
+                write!(f, "{}/Unknown {file_type}", DataType::from(*data_type))
+            },
+            _ => write!(
+                f,
+                "{}",
+                FILE_TYPES
+                    .iter()
+                    .find(|(file_type, _, _)| return file_type == self)
+                    .map_or("", |(_, _, name)| return name),
+            ),
+        };
+    }
+}
+
+impl FromStr for FileType {
+    type Err = TypeError;
+
+    fn from_str(value: &str) -> Result<FileType, TypeError> {
+        let error = || return TypeError(value.to_string());
+        if value.eq_ignore_ascii_case("BinaryText") {
+            return Ok(FileType::BinaryText(0));
+        } else if let Some(width) = value.to_lowercase().strip_prefix("binarytext/") {
+            let width = width.parse::<u16>().map_err(|_| return error())?;
+            if width % 2 != 0 {
+                return Err(error());
+            }
+
+            #[expect(clippy::integer_division, reason = "Intentional")]
+            return u8::try_from(width / 2).map(FileType::BinaryText).map_err(|_| return error());
+        } else if let Some((data_type, file_type)) = value.split_once("/Unknown ") {
+            let file_type = file_type.parse::<u8>().map_err(|_| return error())?;
+            return Ok(FileType::from((u8::from(data_type.parse::<DataType>()?), file_type)));
+        }
+
+        return FILE_TYPES
+            .iter()
+            .find(|(_, _, name)| return name.eq_ignore_ascii_case(value))
+            .map(|(file_type, _, _)| return *file_type)
+            .ok_or_else(error);
+    }
+}
+
+serde_as_string!(FileType);
+
+/// A type name that couldn't be understood.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TypeError(pub String);
+
+impl Display for TypeError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        return write!(f, "Unknown type: {}", self.0);
+    }
+}
+
+impl Error for TypeError {}
+
+/// How wide each character is drawn.
+///
+/// See <https://web.archive.org/web/20250427042053id_/https://www.acid.org/info/sauce/sauce.htm#ANSiFlagsLS>
+///
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LetterSpacing {
+    /// Undefined, effectively [`LetterSpacing::NinePixel`].
+    Legacy,
+    /// 8 pixel wide fonts.
+    EightPixel,
+    /// 9 pixel wide fonts.
+    NinePixel,
+    /// Not a valid value.
+    Invalid,
+}
+
+/// The shape of each pixel.
+///
+/// See <https://web.archive.org/web/20250427042053id_/https://www.acid.org/info/sauce/sauce.htm#ANSiFlagsAR>
+///
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AspectRatio {
+    /// Undefined, effectively [`AspectRatio::Stretch`].
+    Legacy,
+    /// Stretched vertically, like on a CRT.
+    Stretch,
+    /// Square pixels, like on modern displays.
+    Square,
+    /// Not a valid value.
+    Invalid,
+}
+
+/// Every letter spacing value, along with its name.
+const LETTER_SPACINGS: [(LetterSpacing, &str); 4] = [
+    (LetterSpacing::Legacy, "legacy-ls"),
+    (LetterSpacing::EightPixel, "8px"),
+    (LetterSpacing::NinePixel, "9px"),
+    (LetterSpacing::Invalid, "invalid-ls"),
+];
+
+/// Every symbolic aspect ratio value, along with its name.
+const ASPECT_RATIOS: [(AspectRatio, &str); 4] = [
+    (AspectRatio::Legacy, "legacy-ar"),
+    (AspectRatio::Stretch, "stretch-ar"),
+    (AspectRatio::Square, "square-ar"),
+    (AspectRatio::Invalid, "invalid-ar"),
+];
+
+/// The flags of some metadata, interpreted.
+///
+/// Also known as `ANSiFlags`, laid out as `000ARLSB`.
+///
+/// See <https://web.archive.org/web/20250427042053id_/https://www.acid.org/info/sauce/sauce.htm#ANSiFlags>
+///
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SauceFlags {
+    /// Whether the blink bit brightens the background instead (`B`).
+    #[doc(alias = "non-blink mode")]
+    pub ice_colours: bool,
+    /// How wide each character is drawn (`LS`).
+    pub letter_spacing: LetterSpacing,
+    /// The shape of each pixel (`AR`).
+    pub aspect_ratio: AspectRatio,
+    /// The top 3 bits, which should always be `0`.
+    pub reserved: u8,
+}
+
+impl From<u8> for SauceFlags {
+    fn from(value: u8) -> SauceFlags {
+        return SauceFlags {
+            ice_colours: value & 1 == 1,
+            letter_spacing: LETTER_SPACINGS[usize::from((value >> 1) & 3)].0,
+            aspect_ratio: ASPECT_RATIOS[usize::from((value >> 3) & 3)].0,
+            reserved: value >> 5,
+        };
+    }
+}
+
+impl From<SauceFlags> for u8 {
+    #[expect(clippy::cast_possible_truncation, reason = "Range is [0,3]")]
+    fn from(flags: SauceFlags) -> u8 {
+        let ls = LETTER_SPACINGS.iter().position(|(ls, _)| return *ls == flags.letter_spacing).unwrap_or(0) as u8;
+        let ar = ASPECT_RATIOS.iter().position(|(ar, _)| return *ar == flags.aspect_ratio).unwrap_or(0) as u8;
+        return (flags.reserved << 5) | (ar << 3) | (ls << 1) | u8::from(flags.ice_colours);
+    }
+}
+
+impl Display for LetterSpacing {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        return write!(
+            f,
+            "{}",
+            LETTER_SPACINGS.iter().find(|(ls, _)| return ls == self).map_or("", |(_, name)| return name)
+        );
+    }
+}
+
+impl Display for AspectRatio {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        return write!(
+            f,
+            "{}",
+            ASPECT_RATIOS.iter().find(|(ar, _)| return ar == self).map_or("", |(_, name)| return name)
+        );
+    }
+}
+
+impl FromStr for LetterSpacing {
+    type Err = FlagsError;
+
+    fn from_str(value: &str) -> Result<LetterSpacing, FlagsError> {
+        return LETTER_SPACINGS
+            .iter()
+            .find(|(_, name)| return name.eq_ignore_ascii_case(value))
+            .map(|(ls, _)| return *ls)
+            .ok_or_else(|| return FlagsError(value.to_string()));
+    }
+}
+
+impl FromStr for AspectRatio {
+    type Err = FlagsError;
+
+    fn from_str(value: &str) -> Result<AspectRatio, FlagsError> {
+        return ASPECT_RATIOS
+            .iter()
+            .find(|(_, name)| return name.eq_ignore_ascii_case(value))
+            .map(|(ar, _)| return *ar)
+            .ok_or_else(|| return FlagsError(value.to_string()));
+    }
+}
+
+impl Display for SauceFlags {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        return write!(
+            f,
+            "{},{},{}",
+            if self.ice_colours { "ice" } else { "blink" },
+            self.letter_spacing,
+            self.aspect_ratio
+        );
+    }
+}
+
+impl FromStr for SauceFlags {
+    type Err = FlagsError;
+
+    /// Parse a comma separated list of flags, e.g. `ice,9px,legacy-ar`.
+    ///
+    /// Anything not listed is left as `0`.
+    ///
+    fn from_str(value: &str) -> Result<SauceFlags, FlagsError> {
+        let mut flags = SauceFlags::from(0);
+        for name in value.split(',').map(str::trim).filter(|name| return !name.is_empty()) {
+            if name.eq_ignore_ascii_case("ice") {
+                flags.ice_colours = true;
+            } else if name.eq_ignore_ascii_case("blink") {
+                flags.ice_colours = false;
+            } else if let Ok(ls) = name.parse::<LetterSpacing>() {
+                flags.letter_spacing = ls;
+            } else {
+                flags.aspect_ratio = name.parse::<AspectRatio>()?;
+            }
+        }
+
+        return Ok(flags);
+    }
+}
+
+serde_as_string!(LetterSpacing);
+serde_as_string!(AspectRatio);
+
+/// A flag name that couldn't be understood.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FlagsError(pub String);
+
+impl Display for FlagsError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        return write!(f, "Unknown flag: {}", self.0);
+    }
+}
+
+impl Error for FlagsError {}
+
+/// The type dependent fields of some metadata, interpreted.
+///
+/// See [`Meta::info`].
+///
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TypeInfo {
+    /// Text, as ASCII, ANSi, ANSiMation, PCBoard, Avatar or TundraDraw.
+    Character {
+        /// Width, in characters.
+        width: u16,
+        /// Height, in lines.
+        height: u16,
+        /// Rendering flags, see [`Meta::flags`].
+        flags: SauceFlags,
+        /// Font name, if any.
+        font: Option<String>,
+    },
+    /// RIPscript vector graphics.
+    RIPScript {
+        /// Width, in pixels.
+        width: u16,
+        /// Height, in pixels.
+        height: u16,
+        /// Number of colours.
+        colours: u16,
+    },
+    /// A raster image.
+    Bitmap {
+        /// Width, in pixels.
+        width: u16,
+        /// Height, in pixels.
+        height: u16,
+        /// Colour depth, in bits per pixel.
+        depth: u16,
+    },
+    /// Audio.
+    Audio {
+        /// Sample rate, for raw sample formats.
+        sample_rate: Option<u16>,
+    },
+    /// BinaryText.
+    BinaryText {
+        /// Width, in characters, as encoded in the file type.
+        width: u16,
+        /// Rendering flags, see [`Meta::flags`].
+        flags: SauceFlags,
+        /// Font name, if any.
+        font: Option<String>,
+    },
+    /// XBin.
+    XBin {
+        /// Width, in characters.
+        width: u16,
+        /// Height, in lines.
+        height: u16,
+    },
+    /// A type without any such fields.
+    None,
+}
+
 /// A minimal meta.
 ///
 /// Sets all defaults as interpreted when undefined.
@@ -81,9 +766,11 @@ impl Default for Meta {
             group: String::new(),
             date: String::new(),
             size: 0,
-            r#type: (1, 1),
+            r#type: FileType::Ansi,
             width: 80,
             height: 25,
+            tinfo3: 0,
+            tinfo4: 0,
             flags: 0x0D,
             font: String::from("IBM VGA"),
             notes: vec![],
@@ -119,13 +806,13 @@ impl Meta {
         return if self.group.is_empty() { None } else { Some(&self.group) };
     }
 
-    /// Wrap the date in an [`Option`].
+    /// Parse the date, if it's set and valid.
     ///
     /// See [`date` field](#structfield.date)
     ///
     #[must_use]
-    pub fn date(&self) -> Option<&String> {
-        return if self.date.is_empty() { None } else { Some(&self.date) };
+    pub fn date(&self) -> Option<NaiveDate> {
+        return NaiveDate::parse_from_str(&self.date, "%Y%m%d").ok();
     }
 
     /// Fetch the size.
@@ -138,15 +825,15 @@ impl Meta {
         return self.size;
     }
 
-    /// Fetch the type if `type != (0, 0)`, otherwise the default.
+    /// Fetch the type if `type != None`, otherwise the default.
     ///
     /// See [`type` field](#structfield.type)
     ///
     /// See [`Meta::default`]
     ///
     #[must_use]
-    pub fn r#type(&self) -> (u8, u8) {
-        return if self.r#type == (0, 0) { Meta::default().r#type } else { self.r#type };
+    pub fn r#type(&self) -> FileType {
+        return if self.r#type == FileType::None { Meta::default().r#type } else { self.r#type };
     }
 
     /// Fetch the width if `width > 0`, otherwise the default.
@@ -171,6 +858,39 @@ impl Meta {
         return if self.height > 0 { self.height } else { Meta::default().height };
     }
 
+    /// Interpret the type dependent fields, according to the type.
+    ///
+    /// See <https://web.archive.org/web/20250427042053id_/https://www.acid.org/info/sauce/sauce.htm#FileType>
+    ///
+    #[must_use]
+    pub fn info(&self) -> TypeInfo {
+        let font = self.font().cloned();
+        return match (self.r#type.data_type(), self.r#type) {
+            (
+                _,
+                FileType::Ascii
+                | FileType::Ansi
+                | FileType::AnsiMation
+                | FileType::PcBoard
+                | FileType::Avatar
+                | FileType::TundraDraw,
+            ) => TypeInfo::Character { width: self.width, height: self.height, flags: self.flags(), font },
+            (_, FileType::RipScript) => {
+                TypeInfo::RIPScript { width: self.width, height: self.height, colours: self.tinfo3 }
+            },
+            (DataType::Bitmap, _) => TypeInfo::Bitmap { width: self.width, height: self.height, depth: self.tinfo3 },
+            (_, FileType::Smp8 | FileType::Smp8s | FileType::Smp16 | FileType::Smp16s) => {
+                TypeInfo::Audio { sample_rate: Some(self.width) }
+            },
+            (DataType::Audio, _) => TypeInfo::Audio { sample_rate: None },
+            (_, FileType::BinaryText(half_width)) => {
+                TypeInfo::BinaryText { width: u16::from(half_width) * 2, flags: self.flags(), font }
+            },
+            (_, FileType::XBin) => TypeInfo::XBin { width: self.width, height: self.height },
+            _ => TypeInfo::None,
+        };
+    }
+
     /// Get both the width and the height.
     ///
     /// See [`width` method](#method.width)
@@ -183,13 +903,14 @@ impl Meta {
         return (self.width(), self.height());
     }
 
-    /// Fetch the flags, split into `(AR, LS, B)`.
+    /// Fetch the flags, interpreted.
     ///
     /// See [`flags` field](#structfield.flags)
     ///
+    #[inline]
     #[must_use]
-    pub fn flags(&self) -> (u8, u8, u8) {
-        return ((self.flags >> 3) & 3, (self.flags >> 1) & 3, self.flags & 1);
+    pub fn flags(&self) -> SauceFlags {
+        return SauceFlags::from(self.flags);
     }
 
     /// Fetch the font if `font != ""`, otherwise the default.
@@ -201,8 +922,33 @@ impl Meta {
         return if self.font.is_empty() { None } else { Some(&self.font) };
     }
 
+    /// Look the font up in the list of fonts from the SAUCE spec.
+    ///
+    /// An empty font means the default one (IBM VGA), and unknown fonts
+    /// give `None`.
+    ///
+    /// See [`font` field](#structfield.font)
+    ///
+    #[must_use]
+    pub fn font_name(&self) -> Option<FontName> {
+        return if self.font.is_empty() { Some(FontName::default()) } else { FontName::parse(&self.font) };
+    }
+
+    /// Code page of the contents, going by the font's.
+    ///
+    /// See [`CodePage::from_font`].
+    ///
+    /// See [`font` field](#structfield.font)
+    ///
+    #[must_use]
+    pub fn code_page(&self) -> CodePage {
+        return CodePage::from_font(&self.font);
+    }
+
     /// Font face, in OTB format.
     ///
+    /// Only IBM VGA is available in this format, regardless of the font.
+    ///
     /// See [`font` field](#structfield.font)
     ///
     #[must_use]
@@ -210,8 +956,26 @@ impl Meta {
         return if self.font_width() == 8 { &fonts::VGA_8X16 as &Face } else { &fonts::VGA_9X16 as &Face };
     }
 
+    /// Font face, as a bitmap font indexed by character in the font's code
+    /// page.
+    ///
+    /// Fonts whose glyphs aren't bundled fall back to IBM VGA in their code
+    /// page, see [`FontName::bitmap`] and [`struct@fonts::VGA_BITMAPS`].
+    ///
+    /// See [`font` field](#structfield.font)
+    ///
+    #[must_use]
+    pub fn font_bitmap(&self) -> &'static BitmapFont {
+        return self
+            .font_name()
+            .and_then(|name| return name.bitmap(self.font_width()))
+            .unwrap_or_else(|| return &fonts::VGA_BITMAPS[&(self.code_page(), self.font_width())]);
+    }
+
     /// Font face, in WOFF format.
     ///
+    /// Only IBM VGA is available in this format, regardless of the font.
+    ///
     /// See [`font` field](#structfield.font)
     ///
     #[must_use]
@@ -246,9 +1010,9 @@ impl Meta {
     ///
     #[must_use]
     pub fn aspect_ratio(&self) -> (u8, u8) {
-        return if self.flags().0 == 0b10 {
+        return if self.flags().aspect_ratio == AspectRatio::Square {
             (1, 1)
-        } else if self.flags().1 == 0b01 {
+        } else if self.flags().letter_spacing == LetterSpacing::EightPixel {
             (5, 6)
         } else {
             (20, 27)
@@ -257,18 +1021,29 @@ impl Meta {
 
     /// Font width.
     ///
+    /// 9 pixel wide fonts are drawn 8 pixels wide when the letter spacing
+    /// says so. Unknown fonts are taken to be IBM VGA.
+    ///
     /// See [`flags` field](#structfield.flags)
     ///
+    /// See [`font` field](#structfield.font)
+    ///
     #[must_use]
     pub fn font_width(&self) -> u8 {
-        return if self.flags().1 == 0b01 { 8 } else { 9 };
+        let (width, _) = self.font_name().unwrap_or_default().font().size();
+        return if width == 9 && self.flags().letter_spacing == LetterSpacing::EightPixel { 8 } else { width };
     }
 
     /// Font height.
-    #[inline]
+    ///
+    /// Unknown fonts are taken to be IBM VGA.
+    ///
+    /// See [`font` field](#structfield.font)
+    ///
     #[must_use]
     pub fn font_height(&self) -> u8 {
-        return 16;
+        let (_, height) = self.font_name().unwrap_or_default().font().size();
+        return height;
     }
 
     /// Font dimensions.
@@ -282,144 +1057,715 @@ impl Meta {
     pub fn font_size(&self) -> (u8, u8) {
         return (self.font_width(), self.font_height());
     }
-}
 
-/// Get a file's metadata via its path.
-///
-/// # Arguments
-///
-/// * `path`: Path pointing to file. Can be relative to cwd.
-///
-/// # Errors
-///
-/// Fails when there's problems reading the file.
-///
-#[inline]
-pub fn get(path: &str) -> Result<Option<Meta>, String> {
-    return read(&mut File::open(path).map_err(|err| return err.to_string())?);
-}
+    /// Serialise this metadata, as it would be appended to a file.
+    ///
+    /// See [`write`]
+    ///
+    /// # Errors
+    ///
+    /// Fails when a field can't be encoded into its allotted space.
+    ///
+    pub fn to_bytes(&self) -> Result<Vec<u8>, MetaError> {
+        let mut bytes = Vec::with_capacity(self.notes.len() * 64 + 134);
+        write(&mut bytes, self)?;
 
-/// Get a file's metadata via a file reference.
-///
-/// # Arguments
-///
-/// * `file`: File to read.
-///
-/// # Errors
-///
-/// Fails when there's problems reading the file.
-///
-pub fn read(file: &mut File) -> Result<Option<Meta>, String> {
-    return read_raw(file).map(|maybe_raw| {
-        return maybe_raw
-            .map(|raw| {
-                return Ok(Meta {
-                    title: to_utf8(&(raw[raw.len() - 121..raw.len() - 86])).trim_matches('\x20').to_string(),
-                    author: to_utf8(&(raw[raw.len() - 86..raw.len() - 66])).trim_matches('\x20').to_string(),
-                    group: to_utf8(&(raw[raw.len() - 66..raw.len() - 46])).trim_matches('\x20').to_string(),
-                    date: to_utf8(&(raw[raw.len() - 46..raw.len() - 38])).trim_matches('\x20').to_string(),
-                    size: u32::from_le_bytes(
-                        raw[raw.len() - 38..raw.len() - 34]
-                            .try_into()
-                            .map_err(|err: TryFromSliceError| return err.to_string())?,
-                    ),
-                    r#type: (raw[raw.len() - 34], raw[raw.len() - 33]),
-                    width: u16::from_le_bytes(
-                        raw[raw.len() - 32..raw.len() - 30]
-                            .try_into()
-                            .map_err(|err: TryFromSliceError| return err.to_string())?,
-                    ),
-                    height: u16::from_le_bytes(
-                        raw[raw.len() - 30..raw.len() - 28]
-                            .try_into()
-                            .map_err(|err: TryFromSliceError| return err.to_string())?,
-                    ),
-                    flags: raw[raw.len() - 23],
-                    font: to_utf8(&(raw[raw.len() - 22..])).trim_matches('\x00').to_string(),
-                    notes: (0..raw[raw.len() - 24] as usize)
-                        .rev()
-                        .map(|i| {
-                            let offset = raw.len() - (i + 3) * 64;
-                            return to_utf8(&(raw[offset..offset + 64])).trim_matches('\x20').to_string();
-                        })
-                        .collect(),
-                });
-            })
-            .transpose();
-    })?;
-}
+        return Ok(bytes);
+    }
 
-/// Get a human readable type name.
-///
-/// # Arguments
-///
-/// * `type`: The type to get the name for.
-///
-#[must_use]
-pub fn type_name(r#type: (u8, u8)) -> String {
-    return match r#type {
-        (0, _) => String::from("None"),
-        (1, 0) => String::from("Character/ASCII"),
-        (1, 1) => String::from("Character/ANSi"),
-        (1, 2) => String::from("Character/ANSiMation"),
-        (1, 3) => String::from("Character/RIPScript"),
-        (1, 4) => String::from("Character/PCBoard"),
-        (1, 5) => String::from("Character/Avatar"),
-        (1, 6) => String::from("Character/HTML"),
-        (1, 7) => String::from("Character/Source"),
-        (1, 8) => String::from("Character/TundraDraw"),
-        (1, _) => format!("Character/Unknown {}", r#type.1),
-        (2, _) => String::from("Bitmap"),
-        (3, _) => String::from("Vector"),
-        (4, _) => String::from("Audio"),
-        (5, _) => String::from("BinaryText"),
-        (6, _) => String::from("XBin"),
-        (7, _) => String::from("Archive"),
-        (8, _) => String::from("Executable"),
-        _ => format!("Unknown {}/Unknown {}", r#type.0, r#type.1),
-    };
-}
+    /// Append this metadata to the end of a file.
+    ///
+    /// Note that this does not check whether the file already has some
+    /// metadata.
+    ///
+    /// See [`write`]
+    ///
+    /// # Arguments
+    ///
+    /// * `file`: File to write to.
+    ///
+    /// # Errors
+    ///
+    /// Fails when a field can't be encoded into its allotted space, or when
+    /// there's problems writing to the file.
+    ///
+    pub fn append_to(&self, file: &mut File) -> Result<(), MetaError> {
+        let bytes = self.to_bytes()?;
+        file.seek(SeekFrom::End(0))?;
+        file.write_all(&bytes)?;
 
-/// Check that a given file's metadata is valid and supported.
-///
-/// # Arguments
-///
-/// * `meta`: The metadata to check.
-///
-#[expect(clippy::missing_errors_doc, reason = "That's like the whole purpose of this function")]
-pub fn check(meta: Option<&Meta>) -> Result<(), String> {
-    check_title(meta)?;
-    check_author(meta)?;
-    check_group(meta)?;
-    check_date(meta)?;
-    check_type(meta)?;
-    check_flags(meta)?;
-    check_font(meta)?;
-    check_notes(meta)?;
+        return Ok(());
+    }
 
-    return Ok(());
+    /// Replace the metadata at the end of a file, leaving its contents
+    /// untouched.
+    ///
+    /// Any existing metadata (i.e., the EOF marker, the comments, and the
+    /// SAUCE record) is dropped first.
+    ///
+    /// See [`write`]
+    ///
+    /// # Arguments
+    ///
+    /// * `file`: File to write to, opened for both reading and writing.
+    ///
+    /// # Errors
+    ///
+    /// Fails when a field can't be encoded into its allotted space (in which
+    /// case the file is left as is), or when there's problems reading or
+    /// writing the file.
+    ///
+    pub fn replace_in(&self, file: &mut File) -> Result<(), MetaError> {
+        let bytes = self.to_bytes()?;
+        let end = file.seek(SeekFrom::End(0))? - read_raw(file)?.map_or(0, |raw| return raw.len() as u64);
+        file.set_len(end)?;
+        file.seek(SeekFrom::Start(end))?;
+        file.write_all(&bytes)?;
+
+        return Ok(());
+    }
 }
 
-/// Check that the title is valid.
-///
-/// # Arguments
-///
-/// * `meta`: The metadata to check.
-///
-#[expect(clippy::missing_errors_doc, reason = "That's like the whole purpose of this function")]
-pub fn check_title(meta: Option<&Meta>) -> Result<(), String> {
-    return meta.as_ref().map_or(Ok(()), |m| return check_str(&m.title, "Title", 35));
+/// Something went wrong while reading or writing metadata.
+#[derive(Debug)]
+pub enum MetaError {
+    /// The underlying source couldn't be read from or written to.
+    Io(io::Error),
+    /// A field contains characters that can't be encoded as CP437.
+    Encoding(EncodingError),
+    /// A field doesn't fit in the record.
+    Check(CheckError),
 }
 
-/// Check that the author is valid.
-///
-/// # Arguments
-///
-/// * `meta`: The metadata to check.
-///
-#[expect(clippy::missing_errors_doc, reason = "That's like the whole purpose of this function")]
-pub fn check_author(meta: Option<&Meta>) -> Result<(), String> {
-    return meta.as_ref().map_or(Ok(()), |m| return check_str(&m.author, "Author", 20));
+impl Display for MetaError {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        return match self {
+            MetaError::Io(err) => err.fmt(f),
+            MetaError::Encoding(err) => err.fmt(f),
+            MetaError::Check(err) => err.fmt(f),
+        };
+    }
+}
+
+impl Error for MetaError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        return match self {
+            MetaError::Io(err) => Some(err),
+            MetaError::Encoding(err) => Some(err),
+            MetaError::Check(err) => Some(err),
+        };
+    }
+}
+
+impl From<io::Error> for MetaError {
+    #[inline]
+    fn from(err: io::Error) -> MetaError {
+        return MetaError::Io(err);
+    }
+}
+
+impl From<EncodingError> for MetaError {
+    #[inline]
+    fn from(err: EncodingError) -> MetaError {
+        return MetaError::Encoding(err);
+    }
+}
+
+impl From<CheckError> for MetaError {
+    #[inline]
+    fn from(err: CheckError) -> MetaError {
+        return MetaError::Check(err);
+    }
+}
+
+/// A metadata field that failed a check.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CheckError {
+    /// The field that failed.
+    pub field: Field,
+    /// Why it failed.
+    pub reason: Reason,
+}
+
+impl CheckError {
+    /// How serious this problem is.
+    ///
+    /// Values that are valid but not supported by these tools are only
+    /// warnings, as are contents that can still be drawn, just not quite as
+    /// intended. A wrong size is an error though, since it's trusted when
+    /// reading the contents.
+    ///
+    #[must_use]
+    pub fn severity(&self) -> Severity {
+        return match (self.field, &self.reason) {
+            (Field::Size, Reason::Mismatch { .. }) => Severity::Error,
+            (
+                _,
+                Reason::Unsupported(_) | Reason::Mismatch { .. } | Reason::EscapeSequence | Reason::EarlyEof { .. },
+            ) => Severity::Warning,
+            (_, Reason::Blink) => Severity::Info,
+            _ => Severity::Error,
+        };
+    }
+}
+
+impl Display for CheckError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let field = &self.field;
+        return match &self.reason {
+            Reason::TooLong { max, got } => write!(f, "{field} is too long (expected <={max}, got {got})"),
+            Reason::TooMany { max, got } => {
+                write!(f, "Too many {} (expected <= {max}, got {got})", field.to_string().to_lowercase())
+            },
+            Reason::WrongLength { expected, got } => {
+                write!(f, "{field} length is wrong (expected ={expected}, got {got})")
+            },
+            Reason::WrongFormat(err) => write!(f, "{field} format is wrong ({err})"),
+            Reason::ControlCharacter(r#char) => {
+                write!(f, "{field} contains illegal characters (0x{:02X} is a control character)", *r#char as u32)
+            },
+            Reason::IllegalCharacter(r#char) => write!(
+                f,
+                "{field} contains illegal characters ({} (U+{:X}) is not a valid CP437 character)",
+                r#char, *r#char as u32,
+            ),
+            Reason::Unsupported(value) => write!(f, "{field} is unsupported ({value})"),
+            Reason::Unknown(value) => write!(f, "{field} is unknown ({value})"),
+            Reason::LetterSpacing => write!(f, "Invalid letter spacing"),
+            Reason::AspectRatio => write!(f, "Invalid aspect ratio"),
+            Reason::Invalid => write!(f, "Invalid {}", field.to_string().to_lowercase()),
+            Reason::Blink => write!(f, "{field} enable blinking text instead of iCE colours"),
+            Reason::Mismatch { declared, actual } => {
+                write!(f, "{field} doesn't match the contents (declared {declared}, actual {actual})")
+            },
+            Reason::EscapeSequence => write!(f, "{field} contains escape sequences, but the type is plain text"),
+            Reason::EarlyEof { offset } => write!(f, "{field} contains an EOF marker before its end (at 0x{offset:X})"),
+            Reason::Duplicate => write!(f, "{field} ends with another SAUCE record"),
+        };
+    }
+}
+
+impl Error for CheckError {}
+
+/// The metadata fields that can be checked.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Field {
+    /// See [`Meta::title`].
+    Title,
+    /// See [`Meta::author`].
+    Author,
+    /// See [`Meta::group`].
+    Group,
+    /// See [`Meta::date`].
+    Date,
+    /// See [`Meta::size`].
+    Size,
+    /// See [`Meta::type`].
+    Type,
+    /// See [`Meta::width`].
+    Width,
+    /// See [`Meta::height`].
+    Height,
+    /// See [`Meta::flags`].
+    Flags,
+    /// See [`Meta::font`].
+    Font,
+    /// See [`Meta::notes`].
+    Notes,
+    /// A single entry of [`Meta::notes`].
+    Note {
+        /// The index of the note.
+        index: usize,
+        /// How many notes there are, used to pad the index.
+        count: usize,
+    },
+    /// The contents of the file, sans metadata.
+    Data,
+}
+
+impl Display for Field {
+    #[expect(clippy::cast_possible_truncation, reason = "Range is [0,3]")]
+    #[expect(clippy::cast_sign_loss, reason = "Range is [0,3]")]
+    #[expect(clippy::cast_precision_loss, reason = "Range is [0,3]")]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        return match self {
+            Field::Title => write!(f, "Title"),
+            Field::Author => write!(f, "Author"),
+            Field::Group => write!(f, "Group"),
+            Field::Date => write!(f, "Date"),
+            Field::Size => write!(f, "Size"),
+            Field::Type => write!(f, "Type"),
+            Field::Width => write!(f, "Width"),
+            Field::Height => write!(f, "Height"),
+            Field::Flags => write!(f, "Flags"),
+            Field::Font => write!(f, "Font"),
+            Field::Notes => write!(f, "Notes"),
+            Field::Note { index, count } => {
+                write!(f, "Notes[{:0width$}]", index, width = (*count as f32).log10().ceil() as usize)
+            },
+            Field::Data => write!(f, "Data"),
+        };
+    }
+}
+
+/// Why a metadata field failed a check.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Reason {
+    /// The value doesn't fit in the field.
+    TooLong {
+        /// The maximum allowed length.
+        max: usize,
+        /// The actual length.
+        got: usize,
+    },
+    /// There's more entries than the record can hold.
+    TooMany {
+        /// The maximum allowed count.
+        max: usize,
+        /// The actual count.
+        got: usize,
+    },
+    /// The value is not of the exact expected length.
+    WrongLength {
+        /// The expected length.
+        expected: usize,
+        /// The actual length.
+        got: usize,
+    },
+    /// The value can't be parsed.
+    WrongFormat(ParseError),
+    /// The value contains a control character.
+    ControlCharacter(char),
+    /// The value contains a character with no CP437 equivalent.
+    IllegalCharacter(char),
+    /// The value is valid, but not supported by these tools.
+    Unsupported(String),
+    /// The value is not one of the known ones.
+    Unknown(String),
+    /// The letter spacing bits are set to a reserved value.
+    LetterSpacing,
+    /// The aspect ratio bits are set to a reserved value.
+    AspectRatio,
+    /// The value is invalid.
+    Invalid,
+    /// Blinking text is enabled instead of iCE colours, so only 8 background
+    /// colours are available.
+    Blink,
+    /// The value disagrees with the actual contents of the file.
+    Mismatch {
+        /// The value in the record.
+        declared: usize,
+        /// The value implied by the contents.
+        actual: usize,
+    },
+    /// The contents use escape sequences, but the type says they're plain
+    /// text.
+    EscapeSequence,
+    /// The contents have an EOF marker before their actual end, so anything
+    /// after it gets lost.
+    EarlyEof {
+        /// Where the marker is.
+        offset: usize,
+    },
+    /// The contents end with another metadata record, usually from a tool
+    /// that appended a new one instead of replacing the old one.
+    Duplicate,
+}
+
+impl Reason {
+    /// Tie this reason to a field.
+    #[inline]
+    #[must_use]
+    pub fn at(self, field: Field) -> CheckError {
+        return CheckError { field, reason: self };
+    }
+
+    /// A short, stable identifier for this reason, meant for machines.
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        return match self {
+            Reason::TooLong { .. } => "too-long",
+            Reason::TooMany { .. } => "too-many",
+            Reason::WrongLength { .. } => "wrong-length",
+            Reason::WrongFormat(_) => "wrong-format",
+            Reason::ControlCharacter(_) => "control-character",
+            Reason::IllegalCharacter(_) => "illegal-character",
+            Reason::Unsupported(_) => "unsupported",
+            Reason::Unknown(_) => "unknown",
+            Reason::LetterSpacing => "letter-spacing",
+            Reason::AspectRatio => "aspect-ratio",
+            Reason::Invalid => "invalid",
+            Reason::Blink => "blink",
+            Reason::Mismatch { .. } => "mismatch",
+            Reason::EscapeSequence => "escape-sequence",
+            Reason::EarlyEof { .. } => "early-eof",
+            Reason::Duplicate => "duplicate",
+        };
+    }
+}
+
+/// How serious an [`Issue`] is.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Severity {
+    /// Nothing wrong, but worth knowing.
+    Info,
+    /// The value is valid, but it won't be handled properly.
+    Warning,
+    /// The value is corrupt.
+    Error,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        return match self {
+            Severity::Info => write!(f, "info"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        };
+    }
+}
+
+/// A single problem found by [`validate`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Issue {
+    /// How serious the problem is.
+    pub severity: Severity,
+    /// The problem itself.
+    pub error: CheckError,
+}
+
+impl Issue {
+    /// The field with the problem.
+    #[inline]
+    #[must_use]
+    pub fn field(&self) -> Field {
+        return self.error.field;
+    }
+
+    /// A short, stable identifier for the problem. See [`Reason::code`].
+    #[inline]
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        return self.error.reason.code();
+    }
+}
+
+impl From<CheckError> for Issue {
+    #[inline]
+    fn from(error: CheckError) -> Issue {
+        return Issue { severity: error.severity(), error };
+    }
+}
+
+impl Display for Issue {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        return write!(f, "{}[{}]: {}", self.severity, self.code(), self.error);
+    }
+}
+
+/// Every problem found in some metadata, as returned by [`validate`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Report {
+    /// The problems, in the same order as the record's fields.
+    pub issues: Vec<Issue>,
+}
+
+impl Report {
+    /// The first issue that should make the check fail, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `strict`: Whether warnings count as failures too.
+    ///
+    #[must_use]
+    pub fn failure(&self, strict: bool) -> Option<&Issue> {
+        let threshold = if strict { Severity::Warning } else { Severity::Error };
+        return self.issues.iter().find(|issue| return issue.severity >= threshold);
+    }
+
+    /// Whether there's no issue that should make the check fail.
+    ///
+    /// # Arguments
+    ///
+    /// * `strict`: Whether warnings count as failures too.
+    ///
+    #[inline]
+    #[must_use]
+    pub fn is_ok(&self, strict: bool) -> bool {
+        return self.failure(strict).is_none();
+    }
+}
+
+/// Get a file's metadata via its path.
+///
+/// # Arguments
+///
+/// * `path`: Path pointing to file. Can be relative to cwd.
+///
+/// # Errors
+///
+/// Fails when there's problems reading the file.
+///
+#[inline]
+pub fn get(path: &str) -> Result<Option<Meta>, MetaError> {
+    return read(&mut File::open(path)?);
+}
+
+/// Get the metadata of a file, or of any other seekable source such as an
+/// in-memory [`Cursor`].
+///
+/// # Arguments
+///
+/// * `reader`: Source to read.
+///
+/// # Errors
+///
+/// Fails when there's problems reading the source.
+///
+#[inline]
+pub fn read<R: Read + Seek>(reader: &mut R) -> Result<Option<Meta>, MetaError> {
+    return Ok(read_raw(reader)?.map(|raw| return decode(&raw)));
+}
+
+/// Get the metadata of some bytes already held in memory, along with the
+/// boundaries of the actual contents (i.e., without the EOF marker, the
+/// comments, nor the SAUCE record).
+///
+/// # Arguments
+///
+/// * `bytes`: The bytes to parse.
+///
+/// # Errors
+///
+/// Fails when the metadata is malformed.
+///
+pub fn parse(bytes: &[u8]) -> Result<(Option<Meta>, Range<usize>), MetaError> {
+    return match read_raw(&mut Cursor::new(bytes))? {
+        Some(raw) => Ok((Some(decode(&raw)), 0..bytes.len() - raw.len())),
+        None => Ok((None, 0..bytes.len())),
+    };
+}
+
+/// Write some metadata, including the EOF marker and the comments block.
+///
+/// The output can be read back with [`read`].
+///
+/// # Arguments
+///
+/// * `writer`: Where to write the metadata to.
+/// * `meta`: The metadata to write.
+///
+/// # Errors
+///
+/// Fails when a field can't be encoded into its allotted space, or when
+/// there's problems writing.
+///
+pub fn write<W: Write>(writer: &mut W, meta: &Meta) -> Result<(), MetaError> {
+    let mut raw = Vec::with_capacity(meta.notes.len() * 64 + 134);
+    raw.push(0x1A);
+    if !meta.notes.is_empty() {
+        raw.extend_from_slice(b"COMNT");
+        for (i, note) in meta.notes.iter().enumerate() {
+            raw.extend(encode_str(note, Field::Note { index: i, count: meta.notes.len() }, 64, b' ')?);
+        }
+    }
+
+    raw.extend_from_slice(b"SAUCE00");
+    raw.extend(encode_str(&meta.title, Field::Title, 35, b' ')?);
+    raw.extend(encode_str(&meta.author, Field::Author, 20, b' ')?);
+    raw.extend(encode_str(&meta.group, Field::Group, 20, b' ')?);
+    raw.extend(encode_str(&meta.date, Field::Date, 8, b' ')?);
+    raw.extend_from_slice(&meta.size.to_le_bytes());
+    let (data_type, file_type) = meta.r#type.into();
+    raw.extend_from_slice(&[data_type, file_type]);
+    raw.extend_from_slice(&meta.width.to_le_bytes());
+    raw.extend_from_slice(&meta.height.to_le_bytes());
+    raw.extend_from_slice(&meta.tinfo3.to_le_bytes());
+    raw.extend_from_slice(&meta.tinfo4.to_le_bytes());
+    raw.push(u8::try_from(meta.notes.len()).map_err(|_| {
+        return Reason::TooMany { max: 255, got: meta.notes.len() }.at(Field::Notes);
+    })?);
+    raw.push(meta.flags);
+    raw.extend(encode_str(&meta.font, Field::Font, 22, b'\0')?);
+
+    return Ok(writer.write_all(&raw)?);
+}
+
+/// Check that a given file's metadata is valid and supported.
+///
+/// This stops at the first problem, and treats unsupported values as fatal.
+/// See [`validate`] to get every problem along with how serious it is.
+///
+/// # Arguments
+///
+/// * `meta`: The metadata to check.
+///
+#[expect(clippy::missing_errors_doc, reason = "That's like the whole purpose of this function")]
+pub fn check(meta: Option<&Meta>) -> Result<(), CheckError> {
+    check_title(meta)?;
+    check_author(meta)?;
+    check_group(meta)?;
+    check_date(meta)?;
+    check_type(meta)?;
+    check_flags(meta)?;
+    check_font(meta)?;
+    check_notes(meta)?;
+
+    return Ok(());
+}
+
+/// Run every check on a given file's metadata, and collect all problems found.
+///
+/// # Arguments
+///
+/// * `meta`: The metadata to check.
+///
+#[must_use]
+pub fn validate(meta: Option<&Meta>) -> Report {
+    let mut issues = vec![];
+    if let Some(m) = meta {
+        for check in [check_title, check_author, check_group, check_date, check_type] {
+            issues.extend(check(meta).err().map(Issue::from));
+        }
+
+        issues.extend(flag_errors(m).into_iter().map(Issue::from));
+        if !m.flags().ice_colours && matches!(m.r#type, FileType::Ascii | FileType::Ansi | FileType::BinaryText(_)) {
+            issues.push(Reason::Blink.at(Field::Flags).into());
+        }
+
+        issues.extend(check_font(meta).and_then(|()| return check_font_support(meta)).err().map(Issue::from));
+        if m.notes.len() > 255 {
+            issues.push(Reason::TooMany { max: 255, got: m.notes.len() }.at(Field::Notes).into());
+        }
+        for i in 0..m.notes.len() {
+            issues.extend(check_note(meta, i).err().map(Issue::from));
+        }
+    }
+
+    return Report { issues };
+}
+
+/// Run the checks that need the actual contents of a file, i.e. that its
+/// metadata agrees with what's really there.
+///
+/// # Arguments
+///
+/// * `meta`: The metadata to check, as read from `bytes`.
+/// * `bytes`: The whole file, metadata included.
+///
+#[must_use]
+pub fn validate_content(meta: Option<&Meta>, bytes: &[u8]) -> Report {
+    let mut issues = vec![];
+    let data = match read_raw(&mut Cursor::new(bytes)) {
+        Ok(Some(raw)) => &bytes[..bytes.len() - raw.len()],
+        _ => bytes,
+    };
+    let is_text = !xbin::is_xbin(data)
+        && meta.map_or(true, |m| return matches!(m.r#type, FileType::None | FileType::Ascii | FileType::Ansi));
+
+    if let Some(m) = meta {
+        if m.size as usize != data.len() {
+            issues.push(Reason::Mismatch { declared: m.size as usize, actual: data.len() }.at(Field::Size).into());
+        }
+
+        if is_text {
+            let (width, height) = m.dimensions();
+            let extent = extent(data, width);
+            if extent.breaks && extent.width != width {
+                issues.push(
+                    Reason::Mismatch { declared: usize::from(width), actual: usize::from(extent.width) }
+                        .at(Field::Width)
+                        .into(),
+                );
+            }
+            if extent.height > height {
+                issues.push(
+                    Reason::Mismatch { declared: usize::from(height), actual: usize::from(extent.height) }
+                        .at(Field::Height)
+                        .into(),
+                );
+            }
+        }
+
+        if m.r#type == FileType::Ascii && data.contains(&0x1B) {
+            issues.push(Reason::EscapeSequence.at(Field::Data).into());
+        }
+    }
+
+    if is_text {
+        if let Some(offset) = data.iter().position(|byte| return *byte == 0x1A) {
+            if data[offset..].iter().any(|byte| return *byte != 0x1A) {
+                issues.push(Reason::EarlyEof { offset }.at(Field::Data).into());
+            }
+        }
+    }
+
+    if meta.is_some() && matches!(read_raw(&mut Cursor::new(data)), Ok(Some(_))) {
+        issues.push(Reason::Duplicate.at(Field::Data).into());
+    }
+
+    return Report { issues };
+}
+
+/// Guess the type and dimensions of some contents that have no metadata.
+///
+/// Contents with escape sequences are taken to be ANSI, which wraps at 80
+/// columns like a DOS screen would, unless the cursor gets explicitly moved
+/// past that. Plain text is taken to be ASCII, with its lines as wide as they
+/// are, except when there's no line breaks at all and it needs to wrap.
+///
+/// # Arguments
+///
+/// * `data`: The contents of the file, sans metadata.
+///
+#[must_use]
+pub fn estimate(data: &[u8]) -> Meta {
+    let default = Meta::default();
+    let unwrapped = extent(data, u16::MAX);
+    let extent = if unwrapped.escapes || !unwrapped.breaks {
+        extent(data, unwrapped.reach.max(default.width))
+    } else {
+        unwrapped
+    };
+
+    return Meta {
+        size: u32::try_from(data.len()).unwrap_or(u32::MAX),
+        r#type: if extent.escapes { FileType::Ansi } else { FileType::Ascii },
+        width: extent.width,
+        height: extent.height,
+        ..default
+    };
+}
+
+/// Measure some text contents up to their EOF character, if any, see
+/// [`Canvas::measure`].
+fn extent(data: &[u8], width: u16) -> Extent {
+    return Canvas::measure(data.split(|byte| return *byte == 0x1A).next().unwrap_or_default(), width);
+}
+
+/// Check that the title is valid.
+///
+/// # Arguments
+///
+/// * `meta`: The metadata to check.
+///
+#[expect(clippy::missing_errors_doc, reason = "That's like the whole purpose of this function")]
+pub fn check_title(meta: Option<&Meta>) -> Result<(), CheckError> {
+    return meta.as_ref().map_or(Ok(()), |m| return check_str(&m.title, Field::Title, 35));
+}
+
+/// Check that the author is valid.
+///
+/// # Arguments
+///
+/// * `meta`: The metadata to check.
+///
+#[expect(clippy::missing_errors_doc, reason = "That's like the whole purpose of this function")]
+pub fn check_author(meta: Option<&Meta>) -> Result<(), CheckError> {
+    return meta.as_ref().map_or(Ok(()), |m| return check_str(&m.author, Field::Author, 20));
 }
 
 /// Check that the group is valid.
@@ -429,8 +1775,8 @@ pub fn check_author(meta: Option<&Meta>) -> Result<(), String> {
 /// * `meta`: The metadata to check.
 ///
 #[expect(clippy::missing_errors_doc, reason = "That's like the whole purpose of this function")]
-pub fn check_group(meta: Option<&Meta>) -> Result<(), String> {
-    return meta.as_ref().map_or(Ok(()), |m| return check_str(&m.group, "Group", 20));
+pub fn check_group(meta: Option<&Meta>) -> Result<(), CheckError> {
+    return meta.as_ref().map_or(Ok(()), |m| return check_str(&m.group, Field::Group, 20));
 }
 
 /// Check that the date is valid.
@@ -440,13 +1786,13 @@ pub fn check_group(meta: Option<&Meta>) -> Result<(), String> {
 /// * `meta`: The metadata to check.
 ///
 #[expect(clippy::missing_errors_doc, reason = "That's like the whole purpose of this function")]
-pub fn check_date(meta: Option<&Meta>) -> Result<(), String> {
+pub fn check_date(meta: Option<&Meta>) -> Result<(), CheckError> {
     if let Some(m) = meta {
         if !m.date.is_empty() {
             if m.date.len() != 8 {
-                return Err(format!("Date length is wrong (expected =8, got {})", m.date.len()));
+                return Err(Reason::WrongLength { expected: 8, got: m.date.len() }.at(Field::Date));
             } else if let Err(err) = NaiveDate::parse_from_str(&m.date, "%Y%m%d") {
-                return Err(format!("Date format is wrong ({err})"));
+                return Err(Reason::WrongFormat(err).at(Field::Date));
             }
         }
     }
@@ -456,15 +1802,23 @@ pub fn check_date(meta: Option<&Meta>) -> Result<(), String> {
 
 /// Check that the type is valid and supported.
 ///
+/// Types that aren't in the spec are errors, while those that are but can't
+/// be drawn by these tools are only unsupported.
+///
 /// # Arguments
 ///
 /// * `meta`: The metadata to check.
 ///
 #[expect(clippy::missing_errors_doc, reason = "That's like the whole purpose of this function")]
-pub fn check_type(meta: Option<&Meta>) -> Result<(), String> {
+pub fn check_type(meta: Option<&Meta>) -> Result<(), CheckError> {
     if let Some(m) = meta {
-        if ![0, 1].contains(&m.r#type.0) || ![0, 1].contains(&m.r#type.1) {
-            return Err(format!("Type is unsupported ({})", type_name(m.r#type)));
+        if let FileType::Unknown(..) = m.r#type {
+            return Err(Reason::Unknown(m.r#type.to_string()).at(Field::Type));
+        } else if !matches!(
+            m.r#type,
+            FileType::None | FileType::Ascii | FileType::Ansi | FileType::BinaryText(_) | FileType::XBin
+        ) {
+            return Err(Reason::Unsupported(m.r#type.to_string()).at(Field::Type));
         }
     }
 
@@ -478,36 +1832,59 @@ pub fn check_type(meta: Option<&Meta>) -> Result<(), String> {
 /// * `meta`: The metadata to check.
 ///
 #[expect(clippy::missing_errors_doc, reason = "That's like the whole purpose of this function")]
-pub fn check_flags(meta: Option<&Meta>) -> Result<(), String> {
+pub fn check_flags(meta: Option<&Meta>) -> Result<(), CheckError> {
+    return meta.map_or(Ok(()), |m| return flag_errors(m).into_iter().next().map_or(Ok(()), Err));
+}
+
+/// Every problem with the flags, as they're independent of each other.
+fn flag_errors(meta: &Meta) -> Vec<CheckError> {
+    let flags = meta.flags();
+    let mut errors = vec![];
+    if flags.letter_spacing == LetterSpacing::Invalid {
+        errors.push(Reason::LetterSpacing.at(Field::Flags));
+    }
+    if flags.aspect_ratio == AspectRatio::Invalid {
+        errors.push(Reason::AspectRatio.at(Field::Flags));
+    }
+    if flags.reserved != 0 {
+        errors.push(Reason::Invalid.at(Field::Flags));
+    }
+
+    return errors;
+}
+
+/// Check that the font is valid.
+///
+/// The font must be one of the list in the SAUCE spec (see [`FontName`]),
+/// whether its glyphs are bundled or not (see [`FontName::bitmap`]).
+///
+/// # Arguments
+///
+/// * `meta`: The metadata to check.
+///
+#[expect(clippy::missing_errors_doc, reason = "That's like the whole purpose of this function")]
+pub fn check_font(meta: Option<&Meta>) -> Result<(), CheckError> {
     if let Some(m) = meta {
-        if m.flags & 0x01 == 0x00 {
-            // Only intended to support iCE colours
-            return Err(String::from("Blink mode is unsupported"));
-        } else if m.flags & 0x06 == 0x06 {
-            return Err(String::from("Invalid letter spacing"));
-        } else if m.flags & 0x18 == 0x18 {
-            return Err(String::from("Invalid aspect ratio"));
-        } else if m.flags > 0x1F {
-            return Err(String::from("Invalid flags"));
-        }
+        m.font_name().ok_or_else(|| return Reason::Unknown(m.font.clone()).at(Field::Font))?;
     }
 
     return Ok(());
 }
 
-/// Check that the font is valid and supported.
+/// Check that the font's glyphs are bundled, so that it can be drawn as is.
+///
+/// Fonts that aren't are still valid (see [`check_font`]), but they're drawn
+/// with IBM VGA instead (see [`FontName::bitmap`]).
 ///
 /// # Arguments
 ///
 /// * `meta`: The metadata to check.
 ///
 #[expect(clippy::missing_errors_doc, reason = "That's like the whole purpose of this function")]
-pub fn check_font(meta: Option<&Meta>) -> Result<(), String> {
+pub fn check_font_support(meta: Option<&Meta>) -> Result<(), CheckError> {
     if let Some(m) = meta {
-        if !["IBM VGA", "IBM VGA 437", ""].contains(&m.font.as_str()) {
-            // IBM VGA is by far the most common font, haven't even tried to
-            // support any others.
-            return Err(format!("Font is unsupported ({})", m.font));
+        if m.font_name().and_then(|name| return name.bitmap(m.font_width())).is_none() {
+            return Err(Reason::Unsupported(m.font.clone()).at(Field::Font));
         }
     }
 
@@ -521,10 +1898,10 @@ pub fn check_font(meta: Option<&Meta>) -> Result<(), String> {
 /// * `meta`: The metadata to check.
 ///
 #[expect(clippy::missing_errors_doc, reason = "That's like the whole purpose of this function")]
-pub fn check_notes(meta: Option<&Meta>) -> Result<(), String> {
+pub fn check_notes(meta: Option<&Meta>) -> Result<(), CheckError> {
     if let Some(m) = meta {
         if m.notes.len() > 255 {
-            return Err(format!("Too many notes (expected <= 255, got {})", m.notes.len()));
+            return Err(Reason::TooMany { max: 255, got: m.notes.len() }.at(Field::Notes));
         }
 
         for i in 0..m.notes.len() {
@@ -542,50 +1919,225 @@ pub fn check_notes(meta: Option<&Meta>) -> Result<(), String> {
 /// * `meta`: The metadata to check.
 /// * `i`: The index of the note.
 ///
-#[expect(clippy::cast_possible_truncation, reason = "Range is [0,3]")]
-#[expect(clippy::cast_sign_loss, reason = "Range is [0,3]")]
-#[expect(clippy::cast_precision_loss, reason = "Range is [0,3]")]
 #[expect(clippy::missing_errors_doc, reason = "That's like the whole purpose of this function")]
-pub fn check_note(meta: Option<&Meta>, i: usize) -> Result<(), String> {
+pub fn check_note(meta: Option<&Meta>, i: usize) -> Result<(), CheckError> {
     if let Some(m) = meta {
-        check_str(
-            &m.notes[i],
-            &format!("Notes[{:0width$}]", i, width = (m.notes.len() as f32).log10().ceil() as usize),
-            64,
-        )?;
+        check_str(&m.notes[i], Field::Note { index: i, count: m.notes.len() }, 64)?;
     }
 
     return Ok(());
 }
 
-fn check_str(string: &str, name: &str, max_length: usize) -> Result<(), String> {
-    if string.len() > max_length {
-        return Err(format!("{} is too long (expected <={}, got {})", name, max_length, string.len()));
-    }
-
-    return string.chars().try_for_each(|r#char| {
-        return check_char(r#char).map_err(|msg| return format!("{name} contains illegal characters ({msg})"));
-    });
-}
-
-fn check_char(r#char: char) -> Result<(), String> {
+/// Repair whatever [`check`] complains about, as far as possible.
+///
+/// * Strings get their illegal characters replaced (control characters become
+///   spaces, and anything else without a CP437 equivalent becomes `?`), and
+///   are then truncated to fit.
+/// * Dates in other common formats are converted, and unparseable ones are
+///   cleared.
+/// * Reserved flag values are reset to their legacy defaults.
+/// * Notes past the 255th are dropped.
+/// * The size is replaced with the real one.
+///
+/// Values that are valid but unsupported (e.g., the type) are left untouched.
+///
+/// # Arguments
+///
+/// * `meta`: The metadata to repair.
+/// * `size`: The actual size of the contents, sans metadata.
+///
+#[must_use]
+pub fn fix(meta: &Meta, size: u32) -> Meta {
+    let mut flags = meta.flags();
+    if flags.letter_spacing == LetterSpacing::Invalid {
+        flags.letter_spacing = LetterSpacing::Legacy;
+    }
+    if flags.aspect_ratio == AspectRatio::Invalid {
+        flags.aspect_ratio = AspectRatio::Legacy;
+    }
+    flags.reserved = 0;
+
+    return Meta {
+        title: fix_str(&meta.title, 35),
+        author: fix_str(&meta.author, 20),
+        group: fix_str(&meta.group, 20),
+        date: fix_date(&meta.date),
+        size,
+        flags: flags.into(),
+        font: fix_font(&meta.font),
+        notes: meta.notes.iter().take(255).map(|note| return fix_str(note, 64)).collect(),
+        ..meta.clone()
+    };
+}
+
+/// Replace any illegal characters, and truncate to fit.
+fn fix_str(string: &str, max_length: usize) -> String {
+    return string
+        .chars()
+        .map(|r#char| {
+            return match check_char(r#char) {
+                Ok(()) => r#char,
+                Err(Reason::ControlCharacter(_)) => ' ',
+                Err(_) => '?',
+            };
+        })
+        .take(max_length)
+        .collect();
+}
+
+/// Match the font against the known ones, ignoring case and spacing, or clear
+/// it if there's no match.
+fn fix_font(font: &str) -> String {
+    if font.is_empty() || FontName::parse(font).is_some() {
+        return font.to_owned();
+    }
+
+    let words = font.split_whitespace().collect::<Vec<_>>().join(" ");
+    return SAUCE_FONTS
+        .iter()
+        .flat_map(|font| {
+            return once(font.name().to_owned())
+                .chain(font.code_pages().iter().map(|code_page| return format!("{} {code_page}", font.name())));
+        })
+        .find(|name| return name.eq_ignore_ascii_case(&words))
+        .unwrap_or_default();
+}
+
+/// Convert a date into the YYYYMMDD format, or clear it if it can't be parsed.
+fn fix_date(date: &str) -> String {
+    let date = date.trim();
+    if date.is_empty() || check_date(Some(&Meta { date: date.to_owned(), ..Default::default() })).is_ok() {
+        return date.to_owned();
+    }
+
+    return ["%Y-%m-%d", "%Y/%m/%d", "%Y.%m.%d", "%m/%d/%y", "%m/%d/%Y"]
+        .iter()
+        .find_map(|format| return NaiveDate::parse_from_str(date, format).ok())
+        .map_or_else(String::new, |date| return date.format("%Y%m%d").to_string());
+}
+
+fn check_str(string: &str, field: Field, max_length: usize) -> Result<(), CheckError> {
+    // Every character is a single byte once encoded, so count those instead
+    // of the UTF-8 bytes.
+    let length = string.chars().count();
+    if length > max_length {
+        return Err(Reason::TooLong { max: max_length, got: length }.at(field));
+    }
+
+    return string.chars().try_for_each(|r#char| return check_char(r#char).map_err(|reason| return reason.at(field)));
+}
+
+fn check_char(r#char: char) -> Result<(), Reason> {
     if ['\x00', '\x0A', '\x0D', '\x1A', '\x1B'].contains(&r#char) {
-        return Err(format!("0x{:02X} is a control character", r#char as u8));
+        return Err(Reason::ControlCharacter(r#char));
     } else if !CP437_TO_UTF8.contains(&r#char) {
-        return Err(format!("{} (U+{:X}) is not a valid CP437 character", r#char, r#char as u32));
+        return Err(Reason::IllegalCharacter(r#char));
     }
 
     return Ok(());
 }
 
-fn read_raw(file: &mut File) -> Result<Option<Vec<u8>>, String> {
-    if file.metadata().map_err(|err| return err.to_string())?.len() < 129 {
+fn encode_str(string: &str, field: Field, length: usize, padding: u8) -> Result<Vec<u8>, MetaError> {
+    let mut bytes = to_cp437(string)?;
+    if bytes.len() > length {
+        return Err(Reason::TooLong { max: length, got: bytes.len() }.at(field).into());
+    }
+    bytes.resize(length, padding);
+
+    return Ok(bytes);
+}
+
+/// Decode a raw metadata block, as found by [`read_raw`].
+fn decode(raw: &[u8]) -> Meta {
+    return Meta {
+        title: to_utf8(&(raw[raw.len() - 121..raw.len() - 86])).trim_matches('\x20').to_string(),
+        author: to_utf8(&(raw[raw.len() - 86..raw.len() - 66])).trim_matches('\x20').to_string(),
+        group: to_utf8(&(raw[raw.len() - 66..raw.len() - 46])).trim_matches('\x20').to_string(),
+        date: to_utf8(&(raw[raw.len() - 46..raw.len() - 38])).trim_matches('\x20').to_string(),
+        size: u32::from_le_bytes(raw[raw.len() - 38..raw.len() - 34].try_into().expect("Fixed size")),
+        r#type: FileType::from((raw[raw.len() - 34], raw[raw.len() - 33])),
+        width: u16::from_le_bytes(raw[raw.len() - 32..raw.len() - 30].try_into().expect("Fixed size")),
+        height: u16::from_le_bytes(raw[raw.len() - 30..raw.len() - 28].try_into().expect("Fixed size")),
+        tinfo3: u16::from_le_bytes(raw[raw.len() - 28..raw.len() - 26].try_into().expect("Fixed size")),
+        tinfo4: u16::from_le_bytes(raw[raw.len() - 26..raw.len() - 24].try_into().expect("Fixed size")),
+        flags: raw[raw.len() - 23],
+        font: to_utf8(&(raw[raw.len() - 22..])).trim_matches('\x00').to_string(),
+        notes: (0..raw[raw.len() - 24] as usize)
+            .rev()
+            .map(|i| {
+                let offset = raw.len() - (i + 3) * 64;
+                return to_utf8(&(raw[offset..offset + 64])).trim_matches('\x20').to_string();
+            })
+            .collect(),
+    };
+}
+
+/// (De)serialise dates in ISO format, passing anything unparseable as is.
+#[cfg(feature = "serde")]
+mod serde_date {
+    use chrono::NaiveDate;
+    use serde::{Deserialize as _, Deserializer, Serializer};
+
+    /// `YYYYMMDD` → `YYYY-MM-DD`.
+    pub fn serialize<S: Serializer>(date: &str, serializer: S) -> Result<S::Ok, S::Error> {
+        return match NaiveDate::parse_from_str(date, "%Y%m%d") {
+            Ok(date) => serializer.collect_str(&date.format("%Y-%m-%d")),
+            Err(_) => serializer.serialize_str(date),
+        };
+    }
+
+    /// `YYYY-MM-DD` → `YYYYMMDD`.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+        let date = String::deserialize(deserializer)?;
+        return Ok(match NaiveDate::parse_from_str(&date, "%Y-%m-%d") {
+            Ok(date) => date.format("%Y%m%d").to_string(),
+            Err(_) => date,
+        });
+    }
+}
+
+/// (De)serialise flags symbolically, falling back to a number when reserved
+/// bits are set.
+#[cfg(feature = "serde")]
+mod serde_flags {
+    use serde::{de::Error as _, Deserialize, Deserializer, Serializer};
+
+    use super::SauceFlags;
+
+    /// Either form of the flags.
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Flags {
+        /// E.g., `13`.
+        Raw(u8),
+        /// E.g., `"ice,9px,stretch-ar"`.
+        Symbolic(String),
+    }
+
+    /// Bitfield → names.
+    #[expect(clippy::trivially_copy_pass_by_ref, reason = "Signature required by serde")]
+    pub fn serialize<S: Serializer>(flags: &u8, serializer: S) -> Result<S::Ok, S::Error> {
+        let typed = SauceFlags::from(*flags);
+        return if typed.reserved == 0 { serializer.collect_str(&typed) } else { serializer.serialize_u8(*flags) };
+    }
+
+    /// Names (or a number) → bitfield.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u8, D::Error> {
+        return match Flags::deserialize(deserializer)? {
+            Flags::Raw(flags) => Ok(flags),
+            Flags::Symbolic(flags) => flags.parse::<SauceFlags>().map(u8::from).map_err(D::Error::custom),
+        };
+    }
+}
+
+fn read_raw<R: Read + Seek>(reader: &mut R) -> Result<Option<Vec<u8>>, io::Error> {
+    if reader.seek(SeekFrom::End(0))? < 129 {
         return Ok(None);
     }
 
     let mut sauce = vec![0; 128];
-    file.seek(SeekFrom::End(-128)).map_err(|err| return err.to_string())?;
-    file.read_exact(&mut sauce).map_err(|err| return err.to_string())?;
+    reader.seek(SeekFrom::End(-128))?;
+    reader.read_exact(&mut sauce)?;
 
     if &sauce[..7] != "SAUCE00".as_bytes() {
         return Ok(None);
@@ -593,9 +2145,9 @@ fn read_raw(file: &mut File) -> Result<Option<Vec<u8>>, String> {
 
     let offset = sauce[104] as usize * 64 + (if sauce[104] > 0 { 134 } else { 129 });
     #[expect(clippy::cast_possible_wrap, reason = "Range is [0,16454]")]
-    file.seek(SeekFrom::End(-(offset as i64))).map_err(|err| return err.to_string())?;
+    reader.seek(SeekFrom::End(-(offset as i64)))?;
     let mut raw = vec![0; offset];
-    file.read_exact(&mut raw).map_err(|err| return err.to_string())?;
+    reader.read_exact(&mut raw)?;
     if raw[0] != 0x1A || (offset > 129 && &raw[1..6] != "COMNT".as_bytes()) {
         return Ok(None);
     }
@@ -617,83 +2169,557 @@ mod tests {
         assert_eq!(meta.group(), None);
         assert_eq!(meta.date(), None);
         assert_eq!(meta.size(), 0);
-        assert_eq!(meta.r#type(), (1, 1));
+        assert_eq!(meta.r#type(), FileType::Ansi);
         assert_eq!(meta.dimensions(), (80, 25));
-        assert_eq!(meta.flags(), (0b01, 0b10, 0b1));
+        assert_eq!(
+            meta.flags(),
+            SauceFlags {
+                ice_colours: true,
+                letter_spacing: LetterSpacing::NinePixel,
+                aspect_ratio: AspectRatio::Stretch,
+                reserved: 0,
+            }
+        );
         assert_eq!(meta.font(), Some(&String::from("IBM VGA")));
         assert_eq!(meta.notes(), &Vec::<String>::new());
     }
 
     #[test]
-    fn none() -> Result<(), String> {
+    fn none() -> Result<(), MetaError> {
         let meta = get("res/test/simple.ans")?;
         assert!(meta.is_none());
 
         return Ok(());
     }
 
-    #[test]
-    fn some() -> Result<(), String> {
-        let meta = get("res/test/meta.ans")?;
-        assert!(meta.is_some());
-        let meta = meta.unwrap();
-        assert_eq!(meta.title(), Some(&String::from("TITLE")));
-        assert_eq!(meta.author(), Some(&String::from("AUTHOR")));
-        assert_eq!(meta.group(), Some(&String::from("GROUP")));
-        assert_eq!(meta.date(), Some(&String::from("19700101")));
-        assert_eq!(meta.size(), 416);
-        assert_eq!(meta.r#type(), (1, 1));
-        assert_eq!(meta.dimensions(), (32, 8));
-        assert_eq!(meta.flags(), (0, 0, 1));
-        assert_eq!(meta.font(), Some(&String::from("IBM VGA")));
-        assert_eq!(meta.notes(), &Vec::<String>::new());
+    #[test]
+    fn some() -> Result<(), MetaError> {
+        let meta = get("res/test/meta.ans")?;
+        assert!(meta.is_some());
+        let meta = meta.unwrap();
+        assert_eq!(meta.title(), Some(&String::from("TITLE")));
+        assert_eq!(meta.author(), Some(&String::from("AUTHOR")));
+        assert_eq!(meta.group(), Some(&String::from("GROUP")));
+        assert_eq!(meta.date(), NaiveDate::from_ymd_opt(1970, 1, 1));
+        assert_eq!(meta.size(), 416);
+        assert_eq!(meta.r#type(), FileType::Ansi);
+        assert_eq!(meta.dimensions(), (32, 8));
+        assert_eq!(
+            meta.flags(),
+            SauceFlags {
+                ice_colours: true,
+                letter_spacing: LetterSpacing::Legacy,
+                aspect_ratio: AspectRatio::Legacy,
+                reserved: 0,
+            }
+        );
+        assert_eq!(meta.font(), Some(&String::from("IBM VGA")));
+        assert_eq!(meta.notes(), &Vec::<String>::new());
+
+        return Ok(());
+    }
+
+    #[test]
+    fn notes() -> Result<(), MetaError> {
+        let meta = get("res/test/comments.ans")?;
+        assert!(meta.is_some());
+        let meta = meta.unwrap();
+        assert_eq!(meta.title(), Some(&String::from("TITLE")));
+        assert_eq!(meta.author(), Some(&String::from("AUTHOR")));
+        assert_eq!(meta.group(), Some(&String::from("GROUP")));
+        assert_eq!(meta.date(), NaiveDate::from_ymd_opt(1970, 1, 1));
+        assert_eq!(meta.size(), 416);
+        assert_eq!(meta.r#type(), FileType::Ansi);
+        assert_eq!(meta.dimensions(), (32, 8));
+        assert_eq!(
+            meta.flags(),
+            SauceFlags {
+                ice_colours: true,
+                letter_spacing: LetterSpacing::Legacy,
+                aspect_ratio: AspectRatio::Legacy,
+                reserved: 0,
+            }
+        );
+        assert_eq!(meta.font(), Some(&String::from("IBM VGA")));
+        assert_eq!(meta.notes(), &vec!["Lorem", "ipsum", "dolor", "sit", "amet"]);
+
+        return Ok(());
+    }
+
+    #[test]
+    fn empty() -> Result<(), MetaError> {
+        let meta = get("res/test/empty.ans")?;
+        assert!(meta.is_none());
+
+        return Ok(());
+    }
+
+    #[test]
+    fn no_data() -> Result<(), MetaError> {
+        let meta = get("res/test/no_data.ans")?;
+        assert!(meta.is_some());
+        let meta = meta.unwrap();
+        assert_eq!(meta.size(), 0);
+
+        return Ok(());
+    }
+
+    #[test]
+    fn one_hundred_twenty_eight_bytes() -> Result<(), MetaError> {
+        let meta = get("res/test/128_bytes.ans")?;
+        assert!(meta.is_none());
+
+        return Ok(());
+    }
+
+    #[test]
+    fn tinfo() -> Result<(), MetaError> {
+        let meta = Meta { tinfo3: 0x1234, tinfo4: 0x5678, ..Default::default() };
+        let bytes = meta.to_bytes()?;
+        assert_eq!(&bytes[bytes.len() - 28..bytes.len() - 24], &[0x34, 0x12, 0x78, 0x56]);
+        assert_eq!(parse(&bytes)?.0, Some(meta));
+
+        return Ok(());
+    }
+
+    mod types {
+        use super::*;
+
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn data_type() {
+            for value in u8::MIN..=u8::MAX {
+                let data_type = DataType::from(value);
+                assert_eq!(u8::from(data_type), value);
+                assert_eq!(data_type.to_string().parse::<DataType>(), Ok(data_type));
+            }
+
+            assert_eq!(DataType::from(1).to_string(), "Character");
+            assert_eq!(DataType::from(9).to_string(), "Unknown 9");
+            assert_eq!("binarytext".parse::<DataType>(), Ok(DataType::BinaryText));
+            assert_eq!("foo".parse::<DataType>(), Err(TypeError(String::from("foo"))));
+        }
+
+        #[test]
+        fn file_type() {
+            for value in (u8::MIN..=u8::MAX).flat_map(|data_type| {
+                return (u8::MIN..=u8::MAX).map(move |file_type| return (data_type, file_type));
+            }) {
+                let file_type = FileType::from(value);
+                assert_eq!(<(u8, u8)>::from(file_type), value);
+                assert_eq!(u8::from(file_type.data_type()), value.0);
+                assert_eq!(file_type.to_string().parse::<FileType>(), Ok(file_type));
+            }
+
+            assert_eq!(FileType::Ansi.to_string(), "Character/ANSi");
+            assert_eq!(FileType::Gif.to_string(), "Bitmap/GIF");
+            assert_eq!(FileType::Unknown(1, 99).to_string(), "Character/Unknown 99");
+            assert_eq!(FileType::Unknown(255, 255).to_string(), "Unknown 255/Unknown 255");
+            assert_eq!("character/ascii".parse::<FileType>(), Ok(FileType::Ascii));
+            assert_eq!("foo".parse::<FileType>(), Err(TypeError(String::from("foo"))));
+        }
+
+        #[test]
+        fn binary_text() {
+            assert_eq!(FileType::BinaryText(0).to_string(), "BinaryText");
+            assert_eq!(FileType::BinaryText(80).to_string(), "BinaryText/160");
+            assert_eq!("BinaryText".parse::<FileType>(), Ok(FileType::BinaryText(0)));
+            assert_eq!("BinaryText/160".parse::<FileType>(), Ok(FileType::BinaryText(80)));
+            assert_eq!("BinaryText/3".parse::<FileType>(), Err(TypeError(String::from("BinaryText/3"))));
+            assert_eq!("BinaryText/512".parse::<FileType>(), Err(TypeError(String::from("BinaryText/512"))));
+        }
+    }
+
+    mod flags {
+        use super::*;
+
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn round_trip() {
+            for value in u8::MIN..=u8::MAX {
+                assert_eq!(u8::from(SauceFlags::from(value)), value);
+            }
+        }
+
+        #[test]
+        fn display() {
+            assert_eq!(SauceFlags::from(0x00).to_string(), "blink,legacy-ls,legacy-ar");
+            assert_eq!(SauceFlags::from(0x0D).to_string(), "ice,9px,stretch-ar");
+            assert_eq!(SauceFlags::from(0x13).to_string(), "ice,8px,square-ar");
+            assert_eq!(SauceFlags::from(0x1E).to_string(), "blink,invalid-ls,invalid-ar");
+        }
+
+        #[test]
+        fn parse() {
+            for value in 0x00..=0x1F {
+                assert_eq!(SauceFlags::from(value).to_string().parse::<SauceFlags>(), Ok(SauceFlags::from(value)));
+            }
+
+            assert_eq!("".parse::<SauceFlags>(), Ok(SauceFlags::from(0x00)));
+            assert_eq!("ice,9px,legacy-ar".parse::<SauceFlags>(), Ok(SauceFlags::from(0x05)));
+            assert_eq!(" 8PX , Square-AR ".parse::<SauceFlags>(), Ok(SauceFlags::from(0x12)));
+            assert_eq!("ice,blink".parse::<SauceFlags>(), Ok(SauceFlags::from(0x00)));
+            assert_eq!("ice,foo".parse::<SauceFlags>(), Err(FlagsError(String::from("foo"))));
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    mod serde {
+        use super::*;
+
+        use pretty_assertions::assert_eq;
+        use serde_json::{from_str, from_value, json, to_value, Error};
+
+        #[test]
+        fn meta() -> Result<(), Error> {
+            let meta = Meta {
+                title: String::from("TITLE"),
+                date: String::from("19700101"),
+                r#type: FileType::BinaryText(40),
+                notes: vec![String::from("NOTE")],
+                ..Default::default()
+            };
+            let value = to_value(&meta)?;
+            assert_eq!(
+                value,
+                json!({
+                    "title": "TITLE",
+                    "author": "",
+                    "group": "",
+                    "date": "1970-01-01",
+                    "size": 0,
+                    "type": "BinaryText/80",
+                    "width": 80,
+                    "height": 25,
+                    "tinfo3": 0,
+                    "tinfo4": 0,
+                    "flags": "ice,9px,stretch-ar",
+                    "font": "IBM VGA",
+                    "notes": ["NOTE"],
+                })
+            );
+            assert_eq!(from_value::<Meta>(value)?, meta);
+
+            return Ok(());
+        }
+
+        #[test]
+        fn partial() -> Result<(), Error> {
+            assert_eq!(
+                from_str::<Meta>(r#"{ "title": "TITLE", "flags": 1 }"#)?,
+                Meta { title: String::from("TITLE"), flags: 0x01, ..Default::default() }
+            );
+
+            return Ok(());
+        }
+
+        #[test]
+        fn raw() -> Result<(), Error> {
+            let meta = Meta { date: String::from("XXXXXXXX"), flags: 0xFF, ..Default::default() };
+            let value = to_value(&meta)?;
+            assert_eq!(value["date"], json!("XXXXXXXX"));
+            assert_eq!(value["flags"], json!(255));
+            assert_eq!(from_value::<Meta>(value)?, meta);
+
+            return Ok(());
+        }
+
+        #[test]
+        fn invalid() {
+            assert_eq!(
+                from_str::<Meta>(r#"{ "type": "foo" }"#).unwrap_err().to_string(),
+                "Unknown type: foo at line 1 column 17"
+            );
+            assert_eq!(
+                from_str::<Meta>(r#"{ "flags": "foo" }"#).unwrap_err().to_string(),
+                "Unknown flag: foo at line 1 column 18"
+            );
+        }
+
+        #[test]
+        fn typed() -> Result<(), Error> {
+            assert_eq!(to_value(DataType::XBin)?, json!("XBin"));
+            assert_eq!(to_value(FileType::Unknown(1, 99))?, json!("Character/Unknown 99"));
+            assert_eq!(
+                to_value(SauceFlags::from(0x13))?,
+                json!({ "ice_colours": true, "letter_spacing": "8px", "aspect_ratio": "square-ar", "reserved": 0 })
+            );
+            assert_eq!(
+                to_value(TypeInfo::XBin { width: 80, height: 25 })?,
+                json!({ "XBin": { "width": 80, "height": 25 } })
+            );
+            assert_eq!(from_value::<FileType>(json!("Bitmap/GIF"))?, FileType::Gif);
+
+            return Ok(());
+        }
+    }
+
+    mod info {
+        use super::*;
+
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn character() {
+            assert_eq!(
+                Meta::default().info(),
+                TypeInfo::Character {
+                    width: 80,
+                    height: 25,
+                    flags: SauceFlags::from(0x0D),
+                    font: Some(String::from("IBM VGA"))
+                },
+            );
+        }
+
+        #[test]
+        fn rip_script() {
+            assert_eq!(
+                Meta { r#type: FileType::RipScript, width: 640, height: 350, tinfo3: 16, ..Default::default() }.info(),
+                TypeInfo::RIPScript { width: 640, height: 350, colours: 16 },
+            );
+        }
+
+        #[test]
+        fn bitmap() {
+            assert_eq!(
+                Meta { r#type: FileType::Gif, width: 320, height: 200, tinfo3: 8, ..Default::default() }.info(),
+                TypeInfo::Bitmap { width: 320, height: 200, depth: 8 },
+            );
+        }
+
+        #[test]
+        fn audio() {
+            assert_eq!(
+                Meta { r#type: FileType::Smp8, width: 44100, ..Default::default() }.info(),
+                TypeInfo::Audio { sample_rate: Some(44100) },
+            );
+            assert_eq!(
+                Meta { r#type: FileType::Mod, width: 44100, ..Default::default() }.info(),
+                TypeInfo::Audio { sample_rate: None }
+            );
+        }
+
+        #[test]
+        fn binary_text() {
+            assert_eq!(
+                Meta { r#type: FileType::BinaryText(40), font: String::new(), ..Default::default() }.info(),
+                TypeInfo::BinaryText { width: 80, flags: SauceFlags::from(0x0D), font: None },
+            );
+        }
+
+        #[test]
+        fn xbin() {
+            assert_eq!(
+                Meta { r#type: FileType::XBin, width: 16, height: 4, ..Default::default() }.info(),
+                TypeInfo::XBin { width: 16, height: 4 },
+            );
+        }
+
+        #[test]
+        fn none() {
+            assert_eq!(Meta { r#type: FileType::Html, ..Default::default() }.info(), TypeInfo::None);
+            assert_eq!(Meta { r#type: FileType::Zip, ..Default::default() }.info(), TypeInfo::None);
+        }
+    }
+
+    mod parse {
+        use super::*;
+
+        use pretty_assertions::assert_eq;
+        use std::fs::read as read_file;
+
+        #[test]
+        fn cursor() -> Result<(), MetaError> {
+            let bytes = read_file("res/test/meta.ans")?;
+            assert_eq!(read(&mut Cursor::new(&bytes))?, get("res/test/meta.ans")?);
+
+            return Ok(());
+        }
+
+        #[test]
+        fn none() -> Result<(), MetaError> {
+            let bytes = read_file("res/test/simple.ans")?;
+            assert_eq!(parse(&bytes)?, (None, 0..bytes.len()));
+
+            return Ok(());
+        }
+
+        #[test]
+        fn some() -> Result<(), MetaError> {
+            let bytes = read_file("res/test/meta.ans")?;
+            let (meta, range) = parse(&bytes)?;
+            assert_eq!(meta, get("res/test/meta.ans")?);
+            assert_eq!(range, 0..bytes.len() - 129);
+            assert_eq!(bytes[range.end], 0x1A);
+
+            return Ok(());
+        }
+
+        #[test]
+        fn notes() -> Result<(), MetaError> {
+            let bytes = read_file("res/test/comments.ans")?;
+            let (meta, range) = parse(&bytes)?;
+            assert_eq!(meta, get("res/test/comments.ans")?);
+            assert_eq!(range, 0..bytes.len() - 134 - 64 * meta.unwrap().notes.len());
+
+            return Ok(());
+        }
+
+        #[test]
+        fn no_data() -> Result<(), MetaError> {
+            let bytes = read_file("res/test/no_data.ans")?;
+            assert_eq!(parse(&bytes)?.1, 0..0);
+
+            return Ok(());
+        }
+    }
+
+    mod write {
+        use super::*;
+
+        use pretty_assertions::assert_eq;
+        use proptest::{collection::vec, prelude::*, sample::select};
+        use tempfile::tempfile;
+
+        #[test]
+        fn some() -> Result<(), MetaError> {
+            let meta = get("res/test/meta.ans")?.unwrap();
+            let raw = read_raw(&mut File::open("res/test/meta.ans")?)?;
+            assert_eq!(Some(meta.to_bytes()?), raw);
+
+            return Ok(());
+        }
+
+        #[test]
+        fn notes() -> Result<(), MetaError> {
+            let meta = get("res/test/comments.ans")?.unwrap();
+            let raw = read_raw(&mut File::open("res/test/comments.ans")?)?;
+            assert_eq!(Some(meta.to_bytes()?), raw);
+
+            return Ok(());
+        }
+
+        #[test]
+        fn append() -> Result<(), MetaError> {
+            let mut file = tempfile()?;
+            file.write_all(b"foo")?;
+            let meta = Meta { title: String::from("TITLE"), size: 3, ..Default::default() };
+            meta.append_to(&mut file)?;
+            assert_eq!(read(&mut file)?, Some(meta));
+
+            return Ok(());
+        }
+
+        #[test]
+        fn replace() -> Result<(), MetaError> {
+            let mut file = tempfile()?;
+            file.write_all(b"foo")?;
+            Meta { notes: vec![String::from("NOTE")], size: 3, ..Default::default() }.append_to(&mut file)?;
+            let meta = Meta { title: String::from("TITLE"), size: 3, ..Default::default() };
+            meta.replace_in(&mut file)?;
+            assert_eq!(read(&mut file)?, Some(meta.clone()));
+
+            let mut bytes = vec![];
+            file.rewind()?;
+            file.read_to_end(&mut bytes)?;
+            assert_eq!(bytes, [b"foo".as_slice(), &meta.to_bytes()?].concat());
+
+            return Ok(());
+        }
 
-        return Ok(());
-    }
+        #[test]
+        fn replace_none() -> Result<(), MetaError> {
+            let mut file = tempfile()?;
+            file.write_all(b"foo")?;
+            let meta = Meta { title: String::from("TITLE"), size: 3, ..Default::default() };
+            meta.replace_in(&mut file)?;
+            assert_eq!(read(&mut file)?, Some(meta));
+            assert_eq!(file.metadata()?.len(), 3 + 129);
 
-    #[test]
-    fn notes() -> Result<(), String> {
-        let meta = get("res/test/comments.ans")?;
-        assert!(meta.is_some());
-        let meta = meta.unwrap();
-        assert_eq!(meta.title(), Some(&String::from("TITLE")));
-        assert_eq!(meta.author(), Some(&String::from("AUTHOR")));
-        assert_eq!(meta.group(), Some(&String::from("GROUP")));
-        assert_eq!(meta.date(), Some(&String::from("19700101")));
-        assert_eq!(meta.size(), 416);
-        assert_eq!(meta.r#type(), (1, 1));
-        assert_eq!(meta.dimensions(), (32, 8));
-        assert_eq!(meta.flags(), (0, 0, 1));
-        assert_eq!(meta.font(), Some(&String::from("IBM VGA")));
-        assert_eq!(meta.notes(), &vec!["Lorem", "ipsum", "dolor", "sit", "amet"]);
+            return Ok(());
+        }
 
-        return Ok(());
-    }
+        #[test]
+        fn long() {
+            let result = Meta { author: String::from("x").repeat(21), ..Default::default() }.to_bytes();
+            assert!(result.is_err());
+            assert_eq!(result.unwrap_err().to_string(), "Author is too long (expected <=20, got 21)");
+        }
 
-    #[test]
-    fn empty() -> Result<(), String> {
-        let meta = get("res/test/empty.ans")?;
-        assert!(meta.is_none());
+        #[test]
+        fn invalid() {
+            let result = Meta { title: String::from("🚫"), ..Default::default() }.to_bytes();
+            assert!(result.is_err());
+            assert_eq!(result.unwrap_err().to_string(), "🚫 (U+1F6AB) is not a valid CP437 character");
+        }
 
-        return Ok(());
-    }
+        #[test]
+        fn too_many_notes() {
+            let result = Meta { notes: vec![String::new(); 256], ..Default::default() }.to_bytes();
+            assert!(result.is_err());
+            assert_eq!(result.unwrap_err().to_string(), "Too many notes (expected <= 255, got 256)");
+        }
 
-    #[test]
-    fn no_data() -> Result<(), String> {
-        let meta = get("res/test/no_data.ans")?;
-        assert!(meta.is_some());
-        let meta = meta.unwrap();
-        assert_eq!(meta.size(), 0);
+        fn text(length: usize) -> impl Strategy<Value = String> {
+            let chars = CP437_TO_UTF8.iter().copied().filter(|r#char| return check_char(*r#char).is_ok());
+            return vec(select(chars.collect::<Vec<char>>()), 0..=length).prop_map(|chars| {
+                return chars.into_iter().collect::<String>().trim_matches('\x20').to_string();
+            });
+        }
 
-        return Ok(());
-    }
+        fn meta() -> impl Strategy<Value = Meta> {
+            return (
+                (text(35), text(20), text(20), text(8)),
+                (any::<u32>(), any::<(u8, u8)>().prop_map(FileType::from), any::<(u16, u16, u16, u16)>(), any::<u8>()),
+                (text(22), vec(text(64), 0..8)),
+            )
+                .prop_map(
+                    |(
+                        (title, author, group, date),
+                        (size, r#type, (width, height, tinfo3, tinfo4), flags),
+                        (font, notes),
+                    )| {
+                        return Meta {
+                            title,
+                            author,
+                            group,
+                            date,
+                            size,
+                            r#type,
+                            width,
+                            height,
+                            tinfo3,
+                            tinfo4,
+                            flags,
+                            font,
+                            notes,
+                        };
+                    },
+                );
+        }
 
-    #[test]
-    fn one_hundred_twenty_eight_bytes() -> Result<(), String> {
-        let meta = get("res/test/128_bytes.ans")?;
-        assert!(meta.is_none());
+        proptest! {
+            #[test]
+            fn round_trip(meta in meta(), content in vec(any::<u8>(), 0..256)) {
+                let mut file = tempfile().map_err(|err| return TestCaseError::fail(err.to_string()))?;
+                file.write_all(&content).map_err(|err| return TestCaseError::fail(err.to_string()))?;
+                meta.append_to(&mut file).map_err(|err| return TestCaseError::fail(err.to_string()))?;
+                prop_assert_eq!(read(&mut file).map_err(|err| return TestCaseError::fail(err.to_string()))?, Some(meta));
+            }
 
-        return Ok(());
+            #[test]
+            fn raw_round_trip(meta in meta()) {
+                let bytes = meta.to_bytes().map_err(|err| return TestCaseError::fail(err.to_string()))?;
+                prop_assert_eq!(bytes.len(), meta.notes.len() * 64 + if meta.notes.is_empty() { 129 } else { 134 });
+
+                let mut file = tempfile().map_err(|err| return TestCaseError::fail(err.to_string()))?;
+                file.write_all(&bytes).map_err(|err| return TestCaseError::fail(err.to_string()))?;
+                prop_assert_eq!(read_raw(&mut file).map_err(|err| return TestCaseError::fail(err.to_string()))?, Some(bytes));
+            }
+        }
     }
 
     mod raw {
@@ -702,16 +2728,16 @@ mod tests {
         use pretty_assertions::assert_eq;
 
         #[test]
-        fn none() -> Result<(), String> {
-            let meta = read_raw(&mut File::open("res/test/simple.ans").map_err(|err| return err.to_string())?)?;
+        fn none() -> Result<(), MetaError> {
+            let meta = read_raw(&mut File::open("res/test/simple.ans")?)?;
             assert!(meta.is_none());
 
             return Ok(());
         }
 
         #[test]
-        fn some() -> Result<(), String> {
-            let meta = read_raw(&mut File::open("res/test/meta.ans").map_err(|err| return err.to_string())?)?;
+        fn some() -> Result<(), MetaError> {
+            let meta = read_raw(&mut File::open("res/test/meta.ans")?)?;
             assert!(meta.is_some());
             assert_eq!(
                 meta.unwrap(),
@@ -737,8 +2763,8 @@ mod tests {
         }
 
         #[test]
-        fn notes() -> Result<(), String> {
-            let meta = read_raw(&mut File::open("res/test/comments.ans").map_err(|err| return err.to_string())?)?;
+        fn notes() -> Result<(), MetaError> {
+            let meta = read_raw(&mut File::open("res/test/comments.ans")?)?;
             assert!(meta.is_some());
             assert_eq!(
                 meta.unwrap(),
@@ -770,16 +2796,16 @@ mod tests {
         }
 
         #[test]
-        fn empty() -> Result<(), String> {
-            let meta = read_raw(&mut File::open("res/test/empty.ans").map_err(|err| return err.to_string())?)?;
+        fn empty() -> Result<(), MetaError> {
+            let meta = read_raw(&mut File::open("res/test/empty.ans")?)?;
             assert!(meta.is_none());
 
             return Ok(());
         }
 
         #[test]
-        fn no_data() -> Result<(), String> {
-            let meta = read_raw(&mut File::open("res/test/no_data.ans").map_err(|err| return err.to_string())?)?;
+        fn no_data() -> Result<(), MetaError> {
+            let meta = read_raw(&mut File::open("res/test/no_data.ans")?)?;
             assert!(meta.is_some());
             assert_eq!(
                 meta.unwrap(),
@@ -805,8 +2831,8 @@ mod tests {
         }
 
         #[test]
-        fn one_hundred_twenty_eight_bytes() -> Result<(), String> {
-            let meta = read_raw(&mut File::open("res/test/128_bytes.ans").map_err(|err| return err.to_string())?)?;
+        fn one_hundred_twenty_eight_bytes() -> Result<(), MetaError> {
+            let meta = read_raw(&mut File::open("res/test/128_bytes.ans")?)?;
             assert!(meta.is_none());
 
             return Ok(());
@@ -820,12 +2846,12 @@ mod tests {
             use super::*;
 
             #[test]
-            fn none() -> Result<(), String> {
+            fn none() -> Result<(), CheckError> {
                 return check(None);
             }
 
             #[test]
-            fn some() -> Result<(), String> {
+            fn some() -> Result<(), CheckError> {
                 return check(Some(&Meta::default()));
             }
         }
@@ -834,7 +2860,7 @@ mod tests {
             use super::*;
 
             #[test]
-            fn valid() -> Result<(), String> {
+            fn valid() -> Result<(), CheckError> {
                 return check_date(Some(&Meta { date: String::from("19700101"), ..Default::default() }));
             }
 
@@ -855,22 +2881,22 @@ mod tests {
             use pretty_assertions::assert_eq;
 
             #[test]
-            fn b_0() {
-                assert!(check_flags(Some(&Meta { flags: 0x00, ..Default::default() })).is_err());
+            fn b_0() -> Result<(), CheckError> {
+                return check_flags(Some(&Meta { flags: 0x00, ..Default::default() }));
             }
 
             #[test]
-            fn ls_00() -> Result<(), String> {
+            fn ls_00() -> Result<(), CheckError> {
                 return check_flags(Some(&Meta { flags: 0x01, ..Default::default() }));
             }
 
             #[test]
-            fn ls_01() -> Result<(), String> {
+            fn ls_01() -> Result<(), CheckError> {
                 return check_flags(Some(&Meta { flags: 0x03, ..Default::default() }));
             }
 
             #[test]
-            fn ls_10() -> Result<(), String> {
+            fn ls_10() -> Result<(), CheckError> {
                 return check_flags(Some(&Meta { flags: 0x05, ..Default::default() }));
             }
 
@@ -880,17 +2906,17 @@ mod tests {
             }
 
             #[test]
-            fn ar_00() -> Result<(), String> {
+            fn ar_00() -> Result<(), CheckError> {
                 return check_flags(Some(&Meta { flags: 0x01, ..Default::default() }));
             }
 
             #[test]
-            fn ar_01() -> Result<(), String> {
+            fn ar_01() -> Result<(), CheckError> {
                 return check_flags(Some(&Meta { flags: 0x09, ..Default::default() }));
             }
 
             #[test]
-            fn ar_10() -> Result<(), String> {
+            fn ar_10() -> Result<(), CheckError> {
                 return check_flags(Some(&Meta { flags: 0x11, ..Default::default() }));
             }
 
@@ -928,6 +2954,20 @@ mod tests {
             fn font_size_9x16() {
                 assert_eq!((Meta { flags: 0x01, ..Default::default() }).font_size(), (9, 16));
             }
+
+            #[test]
+            fn font_size_registry() {
+                let size = |font: &str, flags: u8| {
+                    return (Meta { font: String::from(font), flags, ..Default::default() }).font_size();
+                };
+                assert_eq!(size("IBM VGA50 850", 0x01), (9, 8));
+                assert_eq!(size("IBM VGA50 850", 0x03), (8, 8));
+                assert_eq!(size("IBM EGA", 0x01), (8, 14));
+                assert_eq!(size("IBM VGA25G", 0x01), (8, 19));
+                assert_eq!(size("Amiga Topaz 1+", 0x01), (8, 8));
+                assert_eq!(size("", 0x01), (9, 16));
+                assert_eq!(size("X", 0x03), (8, 16));
+            }
         }
 
         mod font {
@@ -936,13 +2976,77 @@ mod tests {
             use pretty_assertions::assert_eq;
 
             #[test]
-            fn valid() -> Result<(), String> {
+            fn valid() -> Result<(), CheckError> {
                 return check_font(Some(&Meta { font: String::from("IBM VGA"), ..Default::default() }));
             }
 
+            #[test]
+            fn code_page() -> Result<(), CheckError> {
+                return check_font(Some(&Meta { font: String::from("IBM VGA 437"), ..Default::default() }));
+            }
+
+            #[test]
+            fn empty() -> Result<(), CheckError> {
+                return check_font(Some(&Meta { font: String::new(), ..Default::default() }));
+            }
+
             #[test]
             fn invalid() {
-                assert!(check_font(Some(&Meta { font: String::from("X"), ..Default::default() })).is_err());
+                assert_eq!(
+                    check_font(Some(&Meta { font: String::from("X"), ..Default::default() })),
+                    Err(Reason::Unknown(String::from("X")).at(Field::Font)),
+                );
+            }
+
+            #[test]
+            fn not_bundled() -> Result<(), CheckError> {
+                check_font(Some(&Meta { font: String::from("Amiga Topaz 1+"), ..Default::default() }))?;
+                return check_font(Some(&Meta { font: String::from("IBM VGA 850"), ..Default::default() }));
+            }
+
+            #[test]
+            fn support() {
+                let support = |font: &str| {
+                    return check_font_support(Some(&Meta { font: String::from(font), ..Default::default() }));
+                };
+                assert_eq!(support("IBM VGA"), Ok(()));
+                assert_eq!(support(""), Ok(()));
+                assert_eq!(
+                    support("Amiga Topaz 1+"),
+                    Err(Reason::Unsupported(String::from("Amiga Topaz 1+")).at(Field::Font)),
+                );
+                assert_eq!(
+                    support("IBM VGA MIK"),
+                    Err(Reason::Unsupported(String::from("IBM VGA MIK")).at(Field::Font))
+                );
+                assert_eq!(support("IBM VGA 866"), Ok(()));
+            }
+
+            #[test]
+            fn font_name() {
+                let name = |font: &str| {
+                    return (Meta { font: String::from(font), ..Default::default() }).font_name().map(|name| {
+                        return (name.font().name(), name.code_page());
+                    });
+                };
+                assert_eq!(name(""), Some(("IBM VGA", Some("437"))));
+                assert_eq!(name("IBM VGA50 MIK"), Some(("IBM VGA50", Some("MIK"))));
+                assert_eq!(name("Amiga MicroKnight+"), Some(("Amiga MicroKnight+", None)));
+                assert_eq!(name("IBM VGA50 999"), None);
+            }
+
+            #[test]
+            fn font_bitmap() {
+                let bitmap = |font: &str, flags: u8| {
+                    return (Meta { font: String::from(font), flags, ..Default::default() }).font_bitmap();
+                };
+                let vga = |code_page: CodePage, width: u8| return &fonts::VGA_BITMAPS[&(code_page, width)];
+                assert_eq!(bitmap("IBM VGA", 0x03), vga(CodePage::Cp437, 8));
+                assert_eq!(bitmap("IBM VGA", 0x01), vga(CodePage::Cp437, 9));
+                assert_eq!(bitmap("IBM EGA", 0x01), vga(CodePage::Cp437, 8));
+                assert_eq!(bitmap("IBM VGA 866", 0x01), vga(CodePage::Cp866, 9));
+                assert_eq!(bitmap("IBM VGA 850", 0x03), vga(CodePage::Cp850, 8));
+                assert_eq!(bitmap("X", 0x01), vga(CodePage::Cp437, 9));
             }
 
             #[test]
@@ -966,12 +3070,12 @@ mod tests {
             use super::*;
 
             #[test]
-            fn empty() -> Result<(), String> {
+            fn empty() -> Result<(), CheckError> {
                 return check_notes(Some(&Meta { notes: vec![], ..Default::default() }));
             }
 
             #[test]
-            fn not_empty() -> Result<(), String> {
+            fn not_empty() -> Result<(), CheckError> {
                 return check_notes(Some(&Meta { notes: vec![String::new()], ..Default::default() }));
             }
 
@@ -987,36 +3091,42 @@ mod tests {
             use pretty_assertions::assert_eq;
 
             #[test]
-            fn valid() -> Result<(), String> {
-                return check_str(&String::from("string"), "name", 99);
+            fn valid() -> Result<(), CheckError> {
+                return check_str(&String::from("string"), Field::Title, 99);
             }
 
             #[test]
-            fn valid_non_ascii() -> Result<(), String> {
-                return check_str(&String::from("░"), "name", 99);
+            fn valid_non_ascii() -> Result<(), CheckError> {
+                return check_str(&String::from("░"), Field::Title, 99);
             }
 
             #[test]
             fn long() {
-                let result = check_str(&String::from("string"), "name", 0);
+                let result = check_str(&String::from("string"), Field::Title, 0);
                 assert!(result.is_err());
-                assert_eq!(result.unwrap_err(), "name is too long (expected <=0, got 6)");
+                let err = result.unwrap_err();
+                assert_eq!(err, CheckError { field: Field::Title, reason: Reason::TooLong { max: 0, got: 6 } });
+                assert_eq!(err.to_string(), "Title is too long (expected <=0, got 6)");
             }
 
             #[test]
             fn control() {
-                let result = check_str(&String::from("\0"), "name", 99);
+                let result = check_str(&String::from("\0"), Field::Title, 99);
                 assert!(result.is_err());
-                assert_eq!(result.unwrap_err(), "name contains illegal characters (0x00 is a control character)");
+                let err = result.unwrap_err();
+                assert_eq!(err, CheckError { field: Field::Title, reason: Reason::ControlCharacter('\0') });
+                assert_eq!(err.to_string(), "Title contains illegal characters (0x00 is a control character)");
             }
 
             #[test]
             fn invalid() {
-                let result = check_str(&String::from("🚫"), "name", 99);
+                let result = check_str(&String::from("🚫"), Field::Title, 99);
                 assert!(result.is_err());
+                let err = result.unwrap_err();
+                assert_eq!(err, CheckError { field: Field::Title, reason: Reason::IllegalCharacter('🚫') });
                 assert_eq!(
-                    result.unwrap_err(),
-                    "name contains illegal characters (🚫 (U+1F6AB) is not a valid CP437 character)",
+                    err.to_string(),
+                    "Title contains illegal characters (🚫 (U+1F6AB) is not a valid CP437 character)"
                 );
             }
         }
@@ -1024,90 +3134,449 @@ mod tests {
         mod r#type {
             use super::*;
 
+            use pretty_assertions::assert_eq;
+
             #[test]
-            fn none() -> Result<(), String> {
-                return check_type(Some(&Meta { r#type: (0, 0), ..Default::default() }));
+            fn none() -> Result<(), CheckError> {
+                return check_type(Some(&Meta { r#type: FileType::None, ..Default::default() }));
             }
 
             #[test]
-            fn ascii() -> Result<(), String> {
-                return check_type(Some(&Meta { r#type: (1, 0), ..Default::default() }));
+            fn ascii() -> Result<(), CheckError> {
+                return check_type(Some(&Meta { r#type: FileType::Ascii, ..Default::default() }));
             }
 
             #[test]
-            fn ansi() -> Result<(), String> {
-                return check_type(Some(&Meta { r#type: (1, 1), ..Default::default() }));
+            fn ansi() -> Result<(), CheckError> {
+                return check_type(Some(&Meta { r#type: FileType::Ansi, ..Default::default() }));
             }
 
             #[test]
             fn bitmap() {
-                assert!(check_type(Some(&Meta { r#type: (2, 0), ..Default::default() })).is_err());
+                assert!(check_type(Some(&Meta { r#type: FileType::Gif, ..Default::default() })).is_err());
             }
 
             #[test]
             fn vector() {
-                assert!(check_type(Some(&Meta { r#type: (3, 0), ..Default::default() })).is_err());
+                assert!(check_type(Some(&Meta { r#type: FileType::Dxf, ..Default::default() })).is_err());
             }
 
             #[test]
             fn audio() {
-                assert!(check_type(Some(&Meta { r#type: (4, 0), ..Default::default() })).is_err());
+                assert!(check_type(Some(&Meta { r#type: FileType::Mod, ..Default::default() })).is_err());
+            }
+
+            #[test]
+            fn binary_test() -> Result<(), CheckError> {
+                return check_type(Some(&Meta { r#type: FileType::BinaryText(0), ..Default::default() }));
+            }
+
+            #[test]
+            fn binary_test_width() -> Result<(), CheckError> {
+                return check_type(Some(&Meta { r#type: FileType::BinaryText(80), ..Default::default() }));
+            }
+
+            #[test]
+            fn xbin() -> Result<(), CheckError> {
+                return check_type(Some(&Meta { r#type: FileType::XBin, ..Default::default() }));
             }
 
             #[test]
-            fn binary_test() {
-                assert!(check_type(Some(&Meta { r#type: (5, 0), ..Default::default() })).is_err());
+            fn xbin_unknown() {
+                assert_eq!(
+                    check_type(Some(&Meta { r#type: FileType::Unknown(6, 1), ..Default::default() })),
+                    Err(Reason::Unknown(String::from("XBin/Unknown 1")).at(Field::Type)),
+                );
             }
 
             #[test]
-            fn xbin() {
-                assert!(check_type(Some(&Meta { r#type: (6, 0), ..Default::default() })).is_err());
+            fn unknown() {
+                assert_eq!(
+                    check_type(Some(&Meta { r#type: FileType::Unknown(255, 255), ..Default::default() })),
+                    Err(Reason::Unknown(String::from("Unknown 255/Unknown 255")).at(Field::Type)),
+                );
             }
 
             #[test]
             fn archive() {
-                assert!(check_type(Some(&Meta { r#type: (7, 0), ..Default::default() })).is_err());
+                assert!(check_type(Some(&Meta { r#type: FileType::Zip, ..Default::default() })).is_err());
             }
 
             #[test]
             fn executable() {
-                assert!(check_type(Some(&Meta { r#type: (8, 0), ..Default::default() })).is_err());
+                assert!(check_type(Some(&Meta { r#type: FileType::Executable, ..Default::default() })).is_err());
             }
 
             #[test]
             fn ansimation() {
-                assert!(check_type(Some(&Meta { r#type: (1, 2), ..Default::default() })).is_err());
+                assert!(check_type(Some(&Meta { r#type: FileType::AnsiMation, ..Default::default() })).is_err());
             }
 
             #[test]
             fn rip_script() {
-                assert!(check_type(Some(&Meta { r#type: (1, 3), ..Default::default() })).is_err());
+                assert!(check_type(Some(&Meta { r#type: FileType::RipScript, ..Default::default() })).is_err());
             }
 
             #[test]
             fn pcboard() {
-                assert!(check_type(Some(&Meta { r#type: (1, 4), ..Default::default() })).is_err());
+                assert!(check_type(Some(&Meta { r#type: FileType::PcBoard, ..Default::default() })).is_err());
             }
 
             #[test]
             fn avatar() {
-                assert!(check_type(Some(&Meta { r#type: (1, 5), ..Default::default() })).is_err());
+                assert!(check_type(Some(&Meta { r#type: FileType::Avatar, ..Default::default() })).is_err());
             }
 
             #[test]
             fn html() {
-                assert!(check_type(Some(&Meta { r#type: (1, 6), ..Default::default() })).is_err());
+                assert!(check_type(Some(&Meta { r#type: FileType::Html, ..Default::default() })).is_err());
             }
 
             #[test]
             fn source() {
-                assert!(check_type(Some(&Meta { r#type: (1, 7), ..Default::default() })).is_err());
+                assert!(check_type(Some(&Meta { r#type: FileType::Source, ..Default::default() })).is_err());
             }
 
             #[test]
             fn tundra_draw() {
-                assert!(check_type(Some(&Meta { r#type: (1, 8), ..Default::default() })).is_err());
+                assert!(check_type(Some(&Meta { r#type: FileType::TundraDraw, ..Default::default() })).is_err());
             }
         }
     }
+
+    mod validate {
+        use super::*;
+
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn none() {
+            assert_eq!(validate(None), Report::default());
+        }
+
+        #[test]
+        fn some() {
+            assert_eq!(validate(Some(&Meta::default())), Report::default());
+        }
+
+        #[test]
+        fn all() {
+            let report = validate(Some(&Meta {
+                title: String::from("\0"),
+                date: String::from("X"),
+                r#type: FileType::Ansi,
+                flags: 0xFE,
+                font: String::from("X"),
+                notes: vec![String::new(), String::from("🚫")],
+                ..Default::default()
+            }));
+            assert_eq!(
+                report
+                    .issues
+                    .iter()
+                    .map(|issue| return (issue.severity, issue.field(), issue.code()))
+                    .collect::<Vec<_>>(),
+                vec![
+                    (Severity::Error, Field::Title, "control-character"),
+                    (Severity::Error, Field::Date, "wrong-length"),
+                    (Severity::Error, Field::Flags, "letter-spacing"),
+                    (Severity::Error, Field::Flags, "aspect-ratio"),
+                    (Severity::Error, Field::Flags, "invalid"),
+                    (Severity::Info, Field::Flags, "blink"),
+                    (Severity::Error, Field::Font, "unknown"),
+                    (Severity::Error, Field::Note { index: 1, count: 2 }, "illegal-character"),
+                ],
+            );
+            assert_eq!(report.failure(false), report.issues.first());
+        }
+
+        #[test]
+        fn warnings() {
+            let report =
+                validate(Some(&Meta { r#type: FileType::Gif, font: String::from("IBM EGA"), ..Default::default() }));
+            assert_eq!(report.issues.len(), 2);
+            assert!(report.is_ok(false));
+            assert!(!report.is_ok(true));
+            assert_eq!(report.failure(true), report.issues.first());
+            assert_eq!(report.issues[1].to_string(), "warning[unsupported]: Font is unsupported (IBM EGA)",);
+        }
+
+        #[test]
+        fn blink() {
+            let report = validate(Some(&Meta { r#type: FileType::BinaryText(80), flags: 0x00, ..Default::default() }));
+            assert_eq!(report.issues, vec![Reason::Blink.at(Field::Flags).into()]);
+            assert!(report.is_ok(true));
+        }
+    }
+
+    mod validate_content {
+        use super::*;
+
+        use pretty_assertions::assert_eq;
+        use std::fs;
+
+        fn file(data: &[u8], meta: &Meta) -> Result<Vec<u8>, MetaError> {
+            let mut bytes = data.to_vec();
+            write(&mut bytes, &Meta { size: u32::try_from(data.len()).unwrap_or_default(), ..meta.clone() })?;
+
+            return Ok(bytes);
+        }
+
+        fn codes(report: &Report) -> Vec<(Field, &'static str)> {
+            return report.issues.iter().map(|issue| return (issue.field(), issue.code())).collect();
+        }
+
+        #[test]
+        fn ok() -> Result<(), MetaError> {
+            let bytes = fs::read("res/test/meta.ans")?;
+            assert_eq!(validate_content(get("res/test/meta.ans")?.as_ref(), &bytes), Report::default());
+
+            return Ok(());
+        }
+
+        #[test]
+        fn no_meta() {
+            assert_eq!(validate_content(None, b"Hello\r\n\x1A\x1A"), Report::default());
+        }
+
+        #[test]
+        fn size() -> Result<(), MetaError> {
+            let meta = Meta { size: 1, width: 5, height: 1, ..Default::default() };
+            let mut bytes = b"Hello".to_vec();
+            write(&mut bytes, &meta)?;
+            let report = validate_content(Some(&meta), &bytes);
+            assert_eq!(report.issues, vec![Reason::Mismatch { declared: 1, actual: 5 }.at(Field::Size).into()]);
+            assert_eq!(report.issues[0].severity, Severity::Error);
+
+            return Ok(());
+        }
+
+        #[test]
+        fn width() -> Result<(), MetaError> {
+            let meta = Meta { width: 80, height: 2, ..Default::default() };
+            let bytes = file(b"Hello\r\nWorld!\r\n", &meta)?;
+            let report = validate_content(read(&mut Cursor::new(&bytes))?.as_ref(), &bytes);
+            assert_eq!(report.issues, vec![Reason::Mismatch { declared: 80, actual: 6 }.at(Field::Width).into()]);
+            assert_eq!(report.issues[0].severity, Severity::Warning);
+
+            return Ok(());
+        }
+
+        #[test]
+        fn wrap() -> Result<(), MetaError> {
+            let meta = Meta { width: 4, height: 3, ..Default::default() };
+            let bytes = file(b"HelloWorld", &meta)?;
+            assert_eq!(validate_content(read(&mut Cursor::new(&bytes))?.as_ref(), &bytes), Report::default());
+
+            return Ok(());
+        }
+
+        #[test]
+        fn height() -> Result<(), MetaError> {
+            let meta = Meta { width: 5, height: 1, ..Default::default() };
+            let bytes = file(b"Hello\r\n\x1B[2BWorld", &meta)?;
+            let report = validate_content(read(&mut Cursor::new(&bytes))?.as_ref(), &bytes);
+            // The line fills the width, so it wraps before the line break
+            assert_eq!(report.issues, vec![Reason::Mismatch { declared: 1, actual: 5 }.at(Field::Height).into()]);
+
+            return Ok(());
+        }
+
+        #[test]
+        fn cursor() -> Result<(), MetaError> {
+            let meta = Meta { width: 8, height: 2, ..Default::default() };
+            let bytes = file(b"\x1B[2;1HHello\x1B[3C!\x1B[1;1H\r\n", &meta)?;
+            assert_eq!(validate_content(read(&mut Cursor::new(&bytes))?.as_ref(), &bytes), Report::default());
+
+            return Ok(());
+        }
+
+        #[test]
+        fn escape_sequence() -> Result<(), MetaError> {
+            let meta = Meta { r#type: FileType::Ascii, width: 5, height: 1, ..Default::default() };
+            let bytes = file(b"\x1B[1mHello", &meta)?;
+            let report = validate_content(read(&mut Cursor::new(&bytes))?.as_ref(), &bytes);
+            assert_eq!(codes(&report), vec![(Field::Data, "escape-sequence")]);
+
+            return Ok(());
+        }
+
+        #[test]
+        fn early_eof() -> Result<(), MetaError> {
+            let meta = Meta { width: 5, height: 1, ..Default::default() };
+            let bytes = file(b"Hello\x1AWorld", &meta)?;
+            let report = validate_content(read(&mut Cursor::new(&bytes))?.as_ref(), &bytes);
+            assert_eq!(report.issues, vec![Reason::EarlyEof { offset: 5 }.at(Field::Data).into()]);
+
+            return Ok(());
+        }
+
+        #[test]
+        fn binary() -> Result<(), MetaError> {
+            let meta = Meta { r#type: FileType::BinaryText(1), ..Default::default() };
+            let bytes = file(b"\x1A\x1B\x1A\x1B", &meta)?;
+            assert_eq!(validate_content(read(&mut Cursor::new(&bytes))?.as_ref(), &bytes), Report::default());
+
+            return Ok(());
+        }
+
+        #[test]
+        fn duplicate() -> Result<(), MetaError> {
+            let meta = Meta { width: 5, height: 1, ..Default::default() };
+            let mut data = b"Hello".to_vec();
+            write(&mut data, &meta)?;
+            let bytes = file(&data, &meta)?;
+            let report = validate_content(read(&mut Cursor::new(&bytes))?.as_ref(), &bytes);
+            assert_eq!(codes(&report), vec![(Field::Data, "early-eof"), (Field::Data, "duplicate")]);
+
+            return Ok(());
+        }
+    }
+
+    mod fix {
+        use super::*;
+
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn valid() {
+            let meta = Meta { title: String::from("TITLE"), date: String::from("19700101"), ..Default::default() };
+            assert_eq!(fix(&meta, meta.size), meta);
+        }
+
+        #[test]
+        fn strings() {
+            let meta = fix(
+                &Meta {
+                    title: String::from("Title\0with\x1Bcontrol\ncharacters"),
+                    author: String::from("Émoji 🚫"),
+                    group: "X".repeat(21),
+                    font: String::from("IBM\rVGA"),
+                    ..Default::default()
+                },
+                0,
+            );
+            assert_eq!(meta.title, "Title with control characters");
+            assert_eq!(meta.author, "Émoji ?");
+            assert_eq!(meta.group, "X".repeat(20));
+            assert_eq!(meta.font, "IBM VGA");
+            assert_eq!(check(Some(&meta)), Ok(()));
+        }
+
+        #[test]
+        fn font() {
+            let font = |font: &str| return fix(&Meta { font: String::from(font), ..Default::default() }, 0).font;
+            assert_eq!(font(""), "");
+            assert_eq!(font("IBM EGA43 850"), "IBM EGA43 850");
+            assert_eq!(font(" ibm  vga50 kam "), "IBM VGA50 KAM");
+            assert_eq!(font("amiga topaz 2+"), "Amiga Topaz 2+");
+            assert_eq!(font("IBM FOO"), "");
+        }
+
+        #[test]
+        fn long_title() {
+            let meta = fix(&Meta { title: "░".repeat(36), ..Default::default() }, 0);
+            assert_eq!(meta.title, "░".repeat(35));
+            assert_eq!(check_title(Some(&meta)), Ok(()));
+        }
+
+        #[test]
+        fn date() {
+            let date = |date: &str| return fix(&Meta { date: String::from(date), ..Default::default() }, 0).date;
+            assert_eq!(date(""), "");
+            assert_eq!(date("19700101"), "19700101");
+            assert_eq!(date(" 1970-01-02 "), "19700102");
+            assert_eq!(date("1970/01/03"), "19700103");
+            assert_eq!(date("1970.01.04"), "19700104");
+            assert_eq!(date("01/05/1970"), "19700105");
+            assert_eq!(date("01/06/70"), "19700106");
+            assert_eq!(date("19700230"), "");
+            assert_eq!(date("XXXXXXXX"), "");
+        }
+
+        #[test]
+        fn flags() {
+            assert_eq!(fix(&Meta { flags: 0xFF, ..Default::default() }, 0).flags, 0x01);
+            assert_eq!(fix(&Meta { flags: 0x0D, ..Default::default() }, 0).flags, 0x0D);
+        }
+
+        #[test]
+        fn notes() {
+            let meta = fix(&Meta { notes: vec![String::from("\0"); 256], ..Default::default() }, 0);
+            assert_eq!(meta.notes, vec![String::from(" "); 255]);
+            assert_eq!(check_notes(Some(&meta)), Ok(()));
+        }
+
+        #[test]
+        fn size() {
+            assert_eq!(fix(&Meta { size: 1, ..Default::default() }, 2).size, 2);
+        }
+    }
+
+    mod estimate {
+        use super::*;
+
+        use pretty_assertions::assert_eq;
+        use std::fs;
+
+        fn estimated(data: &[u8]) -> (FileType, u16, u16) {
+            let meta = estimate(data);
+            return (meta.r#type, meta.width, meta.height);
+        }
+
+        #[test]
+        fn empty() {
+            assert_eq!(estimate(b""), Meta { r#type: FileType::Ascii, width: 0, height: 0, ..Default::default() });
+        }
+
+        #[test]
+        fn ascii() {
+            assert_eq!(estimated(b"Hello\r\nWorld!\r\n"), (FileType::Ascii, 6, 2));
+            assert_eq!(estimated(format!("{}\n", "X".repeat(100)).as_bytes()), (FileType::Ascii, 100, 1));
+        }
+
+        #[test]
+        fn ascii_wrap() {
+            assert_eq!(estimated("X".repeat(100).as_bytes()), (FileType::Ascii, 80, 2));
+        }
+
+        #[test]
+        fn ansi() {
+            assert_eq!(estimated(b"\x1B[1mHello\r\nWorld!"), (FileType::Ansi, 6, 2));
+        }
+
+        #[test]
+        fn ansi_wrap() {
+            // Lines that fill the screen don't need a line break, and get an
+            // empty one after them when they have it, same as when drawn
+            let data = format!("\x1B[0m{}{}\r\n{}", "X".repeat(80), "Y".repeat(80), "Z".repeat(10));
+            assert_eq!(estimated(data.as_bytes()), (FileType::Ansi, 80, 4));
+        }
+
+        #[test]
+        fn ansi_wide() {
+            assert_eq!(estimated(b"\x1B[160GX\r\n\x1B[2;1HX"), (FileType::Ansi, 160, 2));
+            assert_eq!(estimated(b"X\x1B[98CX"), (FileType::Ansi, 100, 1));
+        }
+
+        #[test]
+        fn eof() {
+            assert_eq!(estimated(b"Hello\x1AWorld!\r\n"), (FileType::Ascii, 5, 1));
+        }
+
+        #[test]
+        fn huge() {
+            assert_eq!(estimated(b"\x1B[65535CX\x1B[65535BX\x1B[65535EX"), (FileType::Ansi, 65535, 65535));
+            assert_eq!(estimated(b"\x1B[99999CX"), (FileType::Ansi, 1, 1));
+        }
+
+        #[test]
+        fn file() -> Result<(), MetaError> {
+            let data = fs::read("res/test/simple.ans")?;
+            assert_eq!(estimate(&data).size, 416);
+            assert_eq!(estimated(&data), (FileType::Ansi, 80, 4));
+
+            return Ok(());
+        }
+    }
 }
@@ -1,4 +1,13 @@
+#[macro_use]
+mod serde_string;
+
+pub mod binary_text;
+pub mod canvas;
+pub mod code_page;
 pub mod colour;
 pub mod cp437;
 pub mod fonts;
+pub mod lossy;
 pub mod meta;
+pub mod stream;
+pub mod xbin;
@@ -0,0 +1,23 @@
+//! Shared helpers to (de)serialise types through their string form.
+
+/// Implement [`serde::Serialize`] through [`Display`](std::fmt::Display) and
+/// [`serde::Deserialize`] through [`FromStr`](std::str::FromStr).
+macro_rules! serde_as_string {
+    ($type:ty) => {
+        #[cfg(feature = "serde")]
+        impl ::serde::Serialize for $type {
+            fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                return serializer.collect_str(self);
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl<'de> ::serde::Deserialize<'de> for $type {
+            fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                return <String as ::serde::Deserialize>::deserialize(deserializer)?
+                    .parse()
+                    .map_err(::serde::de::Error::custom);
+            }
+        }
+    };
+}
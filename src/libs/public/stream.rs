@@ -0,0 +1,648 @@
+//! Incremental CP437 to/from UTF-8, for files too big to hold in memory.
+//!
+//! [`Cp437Decoder`] wraps a reader of CP437 bytes and reads as UTF-8, while
+//! [`Cp437Encoder`] wraps a writer and writes UTF-8 as CP437 bytes. Both work
+//! on small chunks at a time, so memory usage doesn't grow with the input.
+
+use std::{
+    collections::VecDeque,
+    io::{self, ErrorKind, Read, Write},
+    mem, str,
+};
+use unicode_normalization::char::is_combining_mark;
+
+use crate::{
+    lossy::Policy,
+    prelude::{meta, CodePage},
+};
+
+/// How many bytes are read or written at a time.
+const CHUNK: usize = 1 << 12; // 4k chunks
+
+/// The biggest metadata there can be: the EOF marker, a comments block with
+/// 255 notes, and the record itself.
+const MAX_TRAILER: usize = 1 + 5 + 255 * 64 + 128;
+
+/// The most combining characters kept around waiting to be composed.
+const MAX_CLUSTER: usize = 32;
+
+/// Reads CP437 (or any other code page) bytes from another reader as UTF-8.
+///
+/// ```
+/// use cp437_tools::prelude::Cp437Decoder;
+/// use std::io::Read as _;
+///
+/// let mut utf8 = String::new();
+/// Cp437Decoder::new(&b"\x01\xDB"[..]).read_to_string(&mut utf8)?;
+/// assert_eq!(utf8, "☺█");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+///
+#[derive(Debug)]
+pub struct Cp437Decoder<R: Read> {
+    /// Where the bytes come from.
+    inner: R,
+    /// The code page the bytes are in.
+    code_page: CodePage,
+    /// Where in an escape sequence we are, when stripping them.
+    escape: Option<Escape>,
+    /// The last bytes read, when stripping the metadata.
+    trailer: Option<Trailer>,
+    /// Decoded bytes, yet to be read.
+    decoded: Vec<u8>,
+    /// How many of the decoded bytes have been read already.
+    offset: usize,
+    /// Whether the inner reader is done.
+    eof: bool,
+}
+
+impl<R: Read> Cp437Decoder<R> {
+    /// Wrap a reader, decoding its bytes as CP437 and keeping everything.
+    ///
+    /// # Arguments
+    ///
+    /// * `inner`: The reader to wrap.
+    ///
+    #[must_use]
+    pub fn new(inner: R) -> Cp437Decoder<R> {
+        return Cp437Decoder {
+            inner,
+            code_page: CodePage::Cp437,
+            escape: None,
+            trailer: None,
+            decoded: Vec::with_capacity(CHUNK * 3),
+            offset: 0,
+            eof: false,
+        };
+    }
+
+    /// Set the code page the bytes are in.
+    ///
+    /// # Arguments
+    ///
+    /// * `code_page`: The code page.
+    ///
+    #[inline]
+    pub fn set_code_page(&mut self, code_page: CodePage) {
+        self.code_page = code_page;
+    }
+
+    /// Set whether to drop ANSI escape sequences, or pass them through.
+    ///
+    /// # Arguments
+    ///
+    /// * `strip`: Whether to drop them.
+    ///
+    #[inline]
+    pub fn set_strip_escapes(&mut self, strip: bool) {
+        self.escape = strip.then(Escape::default);
+    }
+
+    /// Set whether to drop the metadata at the end, or pass it through.
+    ///
+    /// Since there's no telling where the metadata starts until the end is
+    /// reached, the last few kilobytes are held back until then.
+    ///
+    /// # Arguments
+    ///
+    /// * `strip`: Whether to drop it.
+    ///
+    #[inline]
+    pub fn set_strip_sauce(&mut self, strip: bool) {
+        self.trailer = strip.then(Trailer::default);
+    }
+
+    /// Unwrap the inner reader.
+    ///
+    /// Anything that was read from it, but not decoded yet, is lost.
+    ///
+    #[inline]
+    #[must_use]
+    pub fn into_inner(self) -> R {
+        return self.inner;
+    }
+
+    /// Read and decode the next chunk.
+    fn fill(&mut self) -> io::Result<()> {
+        self.decoded.clear();
+        self.offset = 0;
+
+        let mut chunk = [0; CHUNK];
+        let count = match self.inner.read(&mut chunk) {
+            Ok(count) => count,
+            Err(err) if err.kind() == ErrorKind::Interrupted => return Ok(()),
+            Err(err) => return Err(err),
+        };
+
+        if count == 0 {
+            self.eof = true;
+            for byte in self.trailer.as_mut().map(Trailer::finish).unwrap_or_default() {
+                self.decode(byte);
+            }
+        }
+
+        for byte in &chunk[..count] {
+            let released = match &mut self.trailer {
+                Some(trailer) => trailer.push(*byte),
+                None => Some(*byte),
+            };
+            if let Some(byte) = released {
+                self.decode(byte);
+            }
+        }
+
+        return Ok(());
+    }
+
+    /// Decode a single byte, unless it's part of an escape sequence that
+    /// should be dropped.
+    fn decode(&mut self, byte: u8) {
+        if self.escape.as_mut().is_some_and(|escape| return escape.skip(byte)) {
+            return;
+        }
+
+        let mut utf8 = [0; 4];
+        self.decoded.extend_from_slice(self.code_page.table()[usize::from(byte)].encode_utf8(&mut utf8).as_bytes());
+    }
+}
+
+impl<R: Read> Read for Cp437Decoder<R> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.offset == self.decoded.len() {
+            if self.eof {
+                return Ok(0);
+            }
+            self.fill()?;
+        }
+
+        let count = buf.len().min(self.decoded.len() - self.offset);
+        buf[..count].copy_from_slice(&self.decoded[self.offset..self.offset + count]);
+        self.offset += count;
+
+        return Ok(count);
+    }
+}
+
+/// Writes UTF-8 to another writer as CP437 (or any other code page) bytes.
+///
+/// Some bytes may be held back (e.g., a character split across writes), so
+/// [`Cp437Encoder::finish`] has to be called once done.
+///
+/// ```
+/// use cp437_tools::prelude::Cp437Encoder;
+/// use std::io::Write as _;
+///
+/// let mut encoder = Cp437Encoder::new(vec![]);
+/// encoder.write_all("☺█".as_bytes())?;
+/// assert_eq!(encoder.finish()?, b"\x01\xDB");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+///
+#[derive(Debug)]
+pub struct Cp437Encoder<W: Write> {
+    /// Where the bytes go to.
+    inner: W,
+    /// The code page to encode to.
+    code_page: CodePage,
+    /// What to do with characters that don't exist in the code page.
+    policy: Policy,
+    /// Where in an escape sequence we are, when stripping them.
+    escape: Option<Escape>,
+    /// The last bytes encoded, when stripping the metadata.
+    trailer: Option<Trailer>,
+    /// The start of a character split across writes.
+    partial: Vec<u8>,
+    /// A character and its combining marks, when transliterating.
+    cluster: Vec<char>,
+    /// How many characters came before the cluster.
+    position: usize,
+    /// How many characters have been replaced so far.
+    substitutions: usize,
+    /// Encoded bytes, yet to be written.
+    encoded: Vec<u8>,
+}
+
+impl<W: Write> Cp437Encoder<W> {
+    /// Wrap a writer, encoding to CP437 and keeping everything.
+    ///
+    /// Characters that don't exist in CP437 fail the write, as in
+    /// [`Policy::Strict`].
+    ///
+    /// # Arguments
+    ///
+    /// * `inner`: The writer to wrap.
+    ///
+    #[must_use]
+    pub fn new(inner: W) -> Cp437Encoder<W> {
+        return Cp437Encoder {
+            inner,
+            code_page: CodePage::Cp437,
+            policy: Policy::Strict,
+            escape: None,
+            trailer: None,
+            partial: Vec::with_capacity(4),
+            cluster: Vec::with_capacity(MAX_CLUSTER),
+            position: 0,
+            substitutions: 0,
+            encoded: Vec::with_capacity(CHUNK),
+        };
+    }
+
+    /// Set the code page to encode to.
+    ///
+    /// # Arguments
+    ///
+    /// * `code_page`: The code page.
+    ///
+    #[inline]
+    pub fn set_code_page(&mut self, code_page: CodePage) {
+        self.code_page = code_page;
+    }
+
+    /// Set what to do with characters that don't exist in the code page.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy`: What to do with them.
+    ///
+    #[inline]
+    pub fn set_policy(&mut self, policy: Policy) {
+        self.policy = policy;
+    }
+
+    /// Set whether to drop ANSI escape sequences, or pass them through.
+    ///
+    /// # Arguments
+    ///
+    /// * `strip`: Whether to drop them.
+    ///
+    #[inline]
+    pub fn set_strip_escapes(&mut self, strip: bool) {
+        self.escape = strip.then(Escape::default);
+    }
+
+    /// Set whether to drop the metadata at the end, or pass it through.
+    ///
+    /// Since there's no telling where the metadata starts until the end is
+    /// reached, the last few kilobytes are held back until
+    /// [`Cp437Encoder::finish`] is called.
+    ///
+    /// # Arguments
+    ///
+    /// * `strip`: Whether to drop it.
+    ///
+    #[inline]
+    pub fn set_strip_sauce(&mut self, strip: bool) {
+        self.trailer = strip.then(Trailer::default);
+    }
+
+    /// How many characters have been replaced so far, as per the policy.
+    #[inline]
+    #[must_use]
+    pub fn substitutions(&self) -> usize {
+        return self.substitutions;
+    }
+
+    /// Write out everything that was held back, and unwrap the inner writer.
+    ///
+    /// # Errors
+    ///
+    /// Fails when the input ended in the middle of a character, when the last
+    /// character can't be encoded, or when there's problems writing.
+    ///
+    pub fn finish(mut self) -> io::Result<W> {
+        if !self.partial.is_empty() {
+            return Err(io::Error::new(ErrorKind::InvalidData, "Incomplete UTF-8 character"));
+        }
+
+        self.encode_cluster()?;
+        for byte in self.trailer.as_mut().map(Trailer::finish).unwrap_or_default() {
+            self.encoded.push(byte);
+        }
+        self.inner.write_all(&self.encoded)?;
+        self.inner.flush()?;
+
+        return Ok(self.inner);
+    }
+
+    /// Take in a single byte, encoding it once it completes a character.
+    fn push_byte(&mut self, byte: u8) -> io::Result<()> {
+        self.partial.push(byte);
+        let r#char = match str::from_utf8(&self.partial) {
+            Ok(utf8) => utf8.chars().next(),
+            Err(err) if err.error_len().is_none() => return Ok(()),
+            Err(_) => return Err(io::Error::new(ErrorKind::InvalidData, "Invalid UTF-8 character")),
+        };
+        self.partial.clear();
+
+        return r#char.map_or(Ok(()), |r#char| return self.push_char(r#char));
+    }
+
+    /// Take in a single character, unless it's part of an escape sequence
+    /// that should be dropped.
+    ///
+    /// When transliterating, characters are kept until it's clear no more
+    /// combining marks follow them.
+    ///
+    fn push_char(&mut self, r#char: char) -> io::Result<()> {
+        if let Some(escape) = &mut self.escape {
+            let skip = if let Some(byte) = u8::try_from(r#char).ok().filter(u8::is_ascii) {
+                escape.skip(byte)
+            } else {
+                *escape = Escape::None;
+                false
+            };
+            if skip {
+                self.encode_cluster()?;
+                self.position += 1;
+                return Ok(());
+            }
+        }
+
+        let combines = is_combining_mark(r#char) && !self.cluster.is_empty() && self.cluster.len() < MAX_CLUSTER;
+        if !(self.policy == Policy::Transliterate && combines) {
+            self.encode_cluster()?;
+        }
+        self.cluster.push(r#char);
+        if self.policy != Policy::Transliterate {
+            self.encode_cluster()?;
+        }
+
+        return Ok(());
+    }
+
+    /// Encode the pending characters.
+    fn encode_cluster(&mut self) -> io::Result<()> {
+        if self.cluster.is_empty() {
+            return Ok(());
+        }
+
+        let utf8 = self.cluster.drain(..).collect::<String>();
+        let encoded = self.code_page.encode_lossy(&utf8, self.policy).map_err(|mut err| {
+            err.position += self.position;
+            return io::Error::new(ErrorKind::InvalidData, err);
+        })?;
+        self.position += utf8.chars().count();
+        self.substitutions += encoded.substitutions.len();
+
+        for byte in encoded.bytes {
+            let released = match &mut self.trailer {
+                Some(trailer) => trailer.push(byte),
+                None => Some(byte),
+            };
+            if let Some(byte) = released {
+                self.encoded.push(byte);
+            }
+        }
+
+        if self.encoded.len() >= CHUNK {
+            self.inner.write_all(&self.encoded)?;
+            self.encoded.clear();
+        }
+
+        return Ok(());
+    }
+}
+
+impl<W: Write> Write for Cp437Encoder<W> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for byte in buf {
+            self.push_byte(*byte)?;
+        }
+        self.inner.write_all(&self.encoded)?;
+        self.encoded.clear();
+
+        return Ok(buf.len());
+    }
+
+    /// Write out everything that can be, and flush the inner writer.
+    ///
+    /// Characters split across writes, and the bytes held back while looking
+    /// for metadata, are kept until [`Cp437Encoder::finish`].
+    ///
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.encode_cluster()?;
+        self.inner.write_all(&self.encoded)?;
+        self.encoded.clear();
+
+        return self.inner.flush();
+    }
+}
+
+/// Where in an ANSI escape sequence a stream is.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+enum Escape {
+    /// Not in one.
+    #[default]
+    None,
+    /// Right after the ESC.
+    Started,
+    /// Inside a control sequence (i.e., after `ESC [`).
+    Csi,
+}
+
+impl Escape {
+    /// Take in the next byte, and tell whether it's part of an escape
+    /// sequence.
+    fn skip(&mut self, byte: u8) -> bool {
+        let skip;
+        (*self, skip) = match (*self, byte) {
+            (Escape::None, 0x1B) => (Escape::Started, true),
+            (Escape::None, _) => (Escape::None, false),
+            (Escape::Started, b'[') => (Escape::Csi, true),
+            (Escape::Started, _) | (Escape::Csi, 0x40..=0x7E) => (Escape::None, true),
+            (Escape::Csi, _) => (Escape::Csi, true),
+        };
+
+        return skip;
+    }
+}
+
+/// The last bytes of a stream, held back in case they turn out to be
+/// metadata.
+#[derive(Debug, Default)]
+struct Trailer {
+    /// The bytes held back, never more than [`MAX_TRAILER`].
+    bytes: VecDeque<u8>,
+}
+
+impl Trailer {
+    /// Take in the next byte, releasing the oldest one if it can't be part of
+    /// the metadata anymore.
+    fn push(&mut self, byte: u8) -> Option<u8> {
+        self.bytes.push_back(byte);
+        return if self.bytes.len() > MAX_TRAILER { self.bytes.pop_front() } else { None };
+    }
+
+    /// Release every byte held back, except for the metadata.
+    fn finish(&mut self) -> Vec<u8> {
+        let end = meta::parse(self.bytes.make_contiguous()).map_or(self.bytes.len(), |(_, range)| return range.end);
+        let mut bytes = Vec::from(mem::take(&mut self.bytes));
+        bytes.truncate(end);
+
+        return bytes;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+    use std::fs::read;
+
+    use crate::prelude::{to_cp437, to_utf8, EncodingError};
+
+    /// Decode some bytes, reading them in tiny bits to exercise the buffering.
+    fn decode(mut decoder: Cp437Decoder<&[u8]>) -> Result<String, io::Error> {
+        let mut utf8 = vec![];
+        let mut buf = [0; 3];
+        loop {
+            let count = decoder.read(&mut buf)?;
+            if count == 0 {
+                return Ok(String::from_utf8(utf8).expect("Valid UTF-8"));
+            }
+            utf8.extend_from_slice(&buf[..count]);
+        }
+    }
+
+    /// Encode a string, writing it a byte at a time to exercise the buffering.
+    fn encode(mut encoder: Cp437Encoder<Vec<u8>>, utf8: &str) -> Result<Vec<u8>, io::Error> {
+        for byte in utf8.as_bytes() {
+            encoder.write_all(&[*byte])?;
+        }
+
+        return encoder.finish();
+    }
+
+    #[test]
+    fn decoder() -> Result<(), io::Error> {
+        let bytes = (0x00..=0xFF).collect::<Vec<u8>>();
+        assert_eq!(decode(Cp437Decoder::new(&bytes[..]))?, to_utf8(&bytes));
+
+        let mut string = String::new();
+        Cp437Decoder::new(&bytes[..]).read_to_string(&mut string)?;
+        assert_eq!(string, to_utf8(&bytes));
+
+        return Ok(());
+    }
+
+    #[test]
+    fn decoder_code_page() -> Result<(), io::Error> {
+        let mut decoder = Cp437Decoder::new(&b"\x8F\xE0\xA8\xA2\xA5\xE2"[..]);
+        decoder.set_code_page(CodePage::Cp866);
+        assert_eq!(decode(decoder)?, "Привет");
+
+        return Ok(());
+    }
+
+    #[test]
+    fn decoder_escapes() -> Result<(), io::Error> {
+        let bytes = b"\x1B[1;31mA\x1B[0m\x1B7B\xDB";
+        assert_eq!(decode(Cp437Decoder::new(&bytes[..]))?, "\x1B[1;31mA\x1B[0m\x1B7B█");
+
+        let mut decoder = Cp437Decoder::new(&bytes[..]);
+        decoder.set_strip_escapes(true);
+        assert_eq!(decode(decoder)?, "AB█");
+
+        return Ok(());
+    }
+
+    #[test]
+    fn decoder_sauce() -> Result<(), io::Error> {
+        let bytes = read("res/test/meta.ans")?;
+        let (_, range) = meta::parse(&bytes).map_err(|err| return io::Error::other(err.to_string()))?;
+        assert_eq!(decode(Cp437Decoder::new(&bytes[..]))?, to_utf8(&bytes));
+
+        let mut decoder = Cp437Decoder::new(&bytes[..]);
+        decoder.set_strip_sauce(true);
+        assert_eq!(decode(decoder)?, to_utf8(&bytes[range]));
+
+        let bytes = read("res/test/simple.ans")?;
+        let mut decoder = Cp437Decoder::new(&bytes[..]);
+        decoder.set_strip_sauce(true);
+        assert_eq!(decode(decoder)?, to_utf8(&bytes));
+
+        return Ok(());
+    }
+
+    #[test]
+    fn encoder() -> Result<(), io::Error> {
+        let utf8 = to_utf8(&(0x00..=0xFF).collect::<Vec<u8>>());
+        assert_eq!(encode(Cp437Encoder::new(vec![]), &utf8)?, to_cp437(&utf8).expect("Valid CP437"));
+
+        let mut encoder = Cp437Encoder::new(vec![]);
+        encoder.write_all(utf8.as_bytes())?;
+        encoder.flush()?;
+        assert_eq!(encoder.finish()?, to_cp437(&utf8).expect("Valid CP437"));
+
+        return Ok(());
+    }
+
+    #[test]
+    fn encoder_code_page() -> Result<(), io::Error> {
+        let mut encoder = Cp437Encoder::new(vec![]);
+        encoder.set_code_page(CodePage::Cp866);
+        assert_eq!(encode(encoder, "Привет")?, b"\x8F\xE0\xA8\xA2\xA5\xE2");
+
+        return Ok(());
+    }
+
+    #[test]
+    fn encoder_policy() -> Result<(), io::Error> {
+        let err = encode(Cp437Encoder::new(vec![]), "ok “🚫”").expect_err("Not valid CP437");
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert_eq!(
+            err.into_inner().and_then(|err| return err.downcast::<EncodingError>().ok()).map(|err| return *err),
+            Some(EncodingError { r#char: '“', position: 3, code_page: CodePage::Cp437 }),
+        );
+
+        let mut encoder = Cp437Encoder::new(vec![]);
+        encoder.set_policy(Policy::Replace);
+        assert_eq!(encode(encoder, "“Cafe\u{301}”")?, b"?Cafe??");
+
+        let mut encoder = Cp437Encoder::new(vec![]);
+        encoder.set_policy(Policy::Transliterate);
+        encoder.write_all("“Cafe".as_bytes())?;
+        encoder.write_all("\u{301}”".as_bytes())?;
+        assert_eq!(encoder.substitutions(), 1);
+        assert_eq!(encoder.finish()?, b"\"Caf\x82\"");
+
+        return Ok(());
+    }
+
+    #[test]
+    fn encoder_escapes() -> Result<(), io::Error> {
+        let mut encoder = Cp437Encoder::new(vec![]);
+        encoder.set_strip_escapes(true);
+        assert_eq!(encode(encoder, "\x1B[1;31mA\x1B[0m█")?, b"A\xDB");
+
+        return Ok(());
+    }
+
+    #[test]
+    fn encoder_sauce() -> Result<(), io::Error> {
+        let bytes = read("res/test/meta.ans")?;
+        let (_, range) = meta::parse(&bytes).map_err(|err| return io::Error::other(err.to_string()))?;
+        assert_eq!(encode(Cp437Encoder::new(vec![]), &to_utf8(&bytes))?, bytes);
+
+        let mut encoder = Cp437Encoder::new(vec![]);
+        encoder.set_strip_sauce(true);
+        assert_eq!(encode(encoder, &to_utf8(&bytes))?, &bytes[range]);
+
+        return Ok(());
+    }
+
+    #[test]
+    fn encoder_invalid() {
+        let mut encoder = Cp437Encoder::new(vec![]);
+        assert_eq!(encoder.write_all(b"\xFF").map_err(|err| return err.kind()), Err(ErrorKind::InvalidData));
+
+        let mut encoder = Cp437Encoder::new(vec![]);
+        assert_eq!(encoder.write_all(&"█".as_bytes()[..1]).map_err(|err| return err.kind()), Ok(()));
+        assert_eq!(encoder.finish().map_err(|err| return err.kind()), Err(ErrorKind::InvalidData));
+    }
+}
@@ -0,0 +1,412 @@
+//! XBin images.
+//!
+//! An XBin file is a header, followed by an optional palette, an optional
+//! font, and the image itself as character/attribute pairs (optionally RLE
+//! compressed). Unlike ANSI files, there's no escape sequences involved.
+//!
+//! See <https://www.acid.org/info/xbin/x_spec.htm>
+//!
+
+use std::{
+    array::from_fn,
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+use crate::{
+    fonts::BitmapFont,
+    prelude::{vga_to_ansi, Canvas, Cell, ColourScheme, Meta},
+};
+
+/// The bytes every XBin file starts with.
+pub const MAGIC: &[u8; 5] = b"XBIN\x1A";
+
+/// Something went wrong while decoding an XBin file.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum XBinError {
+    /// The file doesn't start with [`MAGIC`].
+    NotXBin,
+    /// The file ends before the given section does.
+    Truncated(&'static str),
+    /// The font height is outside of the `[1,32]` range.
+    FontHeight(u8),
+}
+
+impl Display for XBinError {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        return match self {
+            XBinError::NotXBin => write!(f, "Not an XBin file"),
+            XBinError::Truncated(section) => write!(f, "XBin {section} is truncated"),
+            XBinError::FontHeight(height) => write!(f, "Invalid font height (expected <=32, got {height})"),
+        };
+    }
+}
+
+impl Error for XBinError {}
+
+/// The fixed size header at the start of every XBin file.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Header {
+    /// Width of the image, in characters.
+    pub width: u16,
+    /// Height of the image, in characters.
+    pub height: u16,
+    /// Height of the font, in pixels.
+    pub font_height: u8,
+    /// A bitfield of flags that define what follows the header.
+    ///
+    /// ```text
+    /// ┌───┬───┬───┬───┬───┬───┬───┬───┐
+    /// │ 7 │ 6 │ 5 │ 4 │ 3 │ 2 │ 1 │ 0 │
+    /// └───┴───┴───┴─┬─┴─┬─┴─┬─┴─┬─┴─┬─┘
+    ///               │   │   │   │   └╴Palette
+    ///               │   │   │   └╴Font
+    ///               │   │   └╴Compression
+    ///               │   └╴Non blink (iCE colours)
+    ///               └╴512 characters
+    /// ```
+    ///
+    pub flags: u8,
+}
+
+impl Header {
+    /// Size of the header, in bytes.
+    pub const SIZE: usize = 11;
+
+    /// Parse the header at the start of a file.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes`: The contents of the file.
+    ///
+    /// # Errors
+    ///
+    /// Fails when the file is not an XBin file, or when it's too short.
+    ///
+    pub fn parse(bytes: &[u8]) -> Result<Header, XBinError> {
+        if !is_xbin(bytes) {
+            return Err(XBinError::NotXBin);
+        } else if bytes.len() < Header::SIZE {
+            return Err(XBinError::Truncated("header"));
+        }
+
+        let header = Header {
+            width: u16::from_le_bytes([bytes[5], bytes[6]]),
+            height: u16::from_le_bytes([bytes[7], bytes[8]]),
+            font_height: bytes[9],
+            flags: bytes[10],
+        };
+        if header.has_font() && !(1..=32).contains(&header.font_height) {
+            return Err(XBinError::FontHeight(header.font_height));
+        }
+
+        return Ok(header);
+    }
+
+    /// Whether a palette follows the header.
+    #[inline]
+    #[must_use]
+    pub fn has_palette(&self) -> bool {
+        return self.flags & 0x01 != 0;
+    }
+
+    /// Whether a font follows the header (and the palette).
+    #[inline]
+    #[must_use]
+    pub fn has_font(&self) -> bool {
+        return self.flags & 0x02 != 0;
+    }
+
+    /// Whether the image data is RLE compressed.
+    #[inline]
+    #[must_use]
+    pub fn is_compressed(&self) -> bool {
+        return self.flags & 0x04 != 0;
+    }
+
+    /// Whether the high background bit is a bright colour instead of blink.
+    #[inline]
+    #[must_use]
+    pub fn is_non_blink(&self) -> bool {
+        return self.flags & 0x08 != 0;
+    }
+
+    /// Whether the font has 512 characters instead of 256.
+    #[inline]
+    #[must_use]
+    pub fn has_512_chars(&self) -> bool {
+        return self.flags & 0x10 != 0;
+    }
+}
+
+/// Check whether some contents look like an XBin file.
+///
+/// # Arguments
+///
+/// * `bytes`: The contents of the file.
+///
+#[inline]
+#[must_use]
+pub fn is_xbin(bytes: &[u8]) -> bool {
+    return bytes.starts_with(MAGIC);
+}
+
+/// Decode an XBin file into a canvas.
+///
+/// The canvas carries the file's own palette and font, if it has any.
+///
+/// # Arguments
+///
+/// * `bytes`: The contents of the file, sans metadata.
+///
+/// # Errors
+///
+/// Fails when the file is not an XBin file, or when it's malformed.
+///
+pub fn read(bytes: &[u8]) -> Result<Canvas, XBinError> {
+    let header = Header::parse(bytes)?;
+    let mut offset = Header::SIZE;
+
+    let palette = if header.has_palette() {
+        let palette = bytes.get(offset..offset + 48).ok_or(XBinError::Truncated("palette"))?;
+        offset += 48;
+        Some(ColourScheme::CUSTOM(from_fn(|i| {
+            let i = usize::from(vga_to_ansi(u8::try_from(i).expect("Less than 16")));
+            return from_fn(|j| return scale(palette[i * 3 + j]));
+        })))
+    } else {
+        None
+    };
+
+    let font = if header.has_font() {
+        let length = usize::from(header.font_height) * if header.has_512_chars() { 512 } else { 256 };
+        let font = bytes.get(offset..offset + length).ok_or(XBinError::Truncated("font"))?;
+        offset += length;
+        Some(
+            BitmapFont::new(header.font_height, font[..usize::from(header.font_height) * 256].to_vec())
+                .expect("Already bound checked"),
+        )
+    } else {
+        None
+    };
+
+    // Read the image before allocating the canvas, so that a bogus header
+    // can't make it allocate way more than the file could ever fill
+    let count = usize::from(header.width) * usize::from(header.height);
+    let image = if header.is_compressed() {
+        decompress(&bytes[offset..], count)?
+    } else {
+        bytes.get(offset..offset + count * 2).ok_or(XBinError::Truncated("image"))?.to_vec()
+    };
+
+    let mut canvas = Canvas::new(&Meta { width: header.width, height: header.height, ..Default::default() });
+    canvas.set_palette(palette);
+    canvas.set_font(font);
+    if header.has_font() && header.has_512_chars() {
+        canvas.warn(String::from("512 character fonts are unsupported, only the first 256 are used"));
+    }
+
+    for (pair, i) in image.chunks_exact(2).zip(0..) {
+        #[expect(clippy::integer_division, reason = "Intentional")]
+        let (x, y) = (i % usize::from(header.width), i / usize::from(header.width));
+        canvas.set(
+            u16::try_from(x).expect("Width is a u16"),
+            u16::try_from(y).expect("Height is a u16"),
+            cell(header, pair[0], pair[1]),
+        );
+    }
+
+    return Ok(canvas);
+}
+
+/// Expand RLE compressed image data into plain character/attribute pairs.
+///
+/// Every run takes at least 3 bytes and expands to at most 64 pairs, so data
+/// too short to ever reach `count` pairs is rejected before allocating.
+///
+fn decompress(data: &[u8], count: usize) -> Result<Vec<u8>, XBinError> {
+    if data.len() < count.div_ceil(64) * 3 {
+        return Err(XBinError::Truncated("image"));
+    }
+
+    let mut image = Vec::with_capacity(count * 2);
+    let mut offset = 0;
+    while image.len() < count * 2 {
+        let run = *data.get(offset).ok_or(XBinError::Truncated("image"))?;
+        let length = usize::from(run & 0x3F) + 1;
+        offset += 1;
+
+        match run >> 6 {
+            0b00 => {
+                // No compression, just pairs
+                image.extend_from_slice(data.get(offset..offset + length * 2).ok_or(XBinError::Truncated("image"))?);
+                offset += length * 2;
+            },
+            0b01 => {
+                // Repeated character, one attribute each
+                let chunk = data.get(offset..=offset + length).ok_or(XBinError::Truncated("image"))?;
+                for attribute in &chunk[1..] {
+                    image.extend_from_slice(&[chunk[0], *attribute]);
+                }
+                offset += length + 1;
+            },
+            0b10 => {
+                // Repeated attribute, one character each
+                let chunk = data.get(offset..=offset + length).ok_or(XBinError::Truncated("image"))?;
+                for byte in &chunk[1..] {
+                    image.extend_from_slice(&[*byte, chunk[0]]);
+                }
+                offset += length + 1;
+            },
+            _ => {
+                // Repeated pair
+                let chunk = data.get(offset..offset + 2).ok_or(XBinError::Truncated("image"))?;
+                for _ in 0..length {
+                    image.extend_from_slice(chunk);
+                }
+                offset += 2;
+            },
+        }
+    }
+    image.truncate(count * 2);
+
+    return Ok(image);
+}
+
+/// Build a cell out of a character/attribute pair.
+fn cell(header: Header, byte: u8, attribute: u8) -> Cell {
+    // With 512 characters the bright bit selects the font half instead
+    let attribute = if header.has_512_chars() { attribute & 0xF7 } else { attribute };
+
+    return Cell::from_attribute(byte, attribute, header.is_non_blink());
+}
+
+/// Scale a 6 bit VGA palette value up to 8 bits.
+#[inline]
+fn scale(value: u8) -> u8 {
+    let value = value & 0x3F;
+    return (value << 2) | (value >> 4);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+    use std::fs::read as read_file;
+
+    use crate::prelude::{Attributes, Colour};
+
+    #[test]
+    fn header() -> Result<(), XBinError> {
+        let bytes = read_file("res/test/xbin.xb").expect("File exists");
+        let header = Header::parse(&bytes)?;
+        assert_eq!(header, Header { width: 16, height: 4, font_height: 8, flags: 0x0B });
+        assert!(header.has_palette());
+        assert!(header.has_font());
+        assert!(!header.is_compressed());
+        assert!(header.is_non_blink());
+        assert!(!header.has_512_chars());
+
+        return Ok(());
+    }
+
+    #[test]
+    fn not_xbin() {
+        assert_eq!(read(b"Hello"), Err(XBinError::NotXBin));
+    }
+
+    #[test]
+    fn truncated() {
+        assert_eq!(read(b"XBIN\x1A\x01\x00"), Err(XBinError::Truncated("header")));
+        assert_eq!(read(b"XBIN\x1A\x01\x00\x01\x00\x10\x01"), Err(XBinError::Truncated("palette")));
+        assert_eq!(read(b"XBIN\x1A\x01\x00\x01\x00\x10\x00A"), Err(XBinError::Truncated("image")));
+        assert_eq!(read(b"XBIN\x1A\x01\x00\x01\x00\x10\x04\x00A"), Err(XBinError::Truncated("image")));
+        assert_eq!(read(b"XBIN\x1A\xFF\xFF\xFF\xFF\x10\x00AB"), Err(XBinError::Truncated("image")));
+        assert_eq!(read(b"XBIN\x1A\xFF\xFF\xFF\xFF\x10\x04\xFFA\x07"), Err(XBinError::Truncated("image")));
+    }
+
+    #[test]
+    fn font_height() {
+        assert_eq!(read(b"XBIN\x1A\x01\x00\x01\x00\x00\x02"), Err(XBinError::FontHeight(0)));
+        assert_eq!(read(b"XBIN\x1A\x01\x00\x01\x00\x21\x02"), Err(XBinError::FontHeight(33)));
+    }
+
+    #[test]
+    fn palette() -> Result<(), XBinError> {
+        let canvas = read(&read_file("res/test/xbin.xb").expect("File exists"))?;
+        let palette = canvas.palette().expect("Has a palette").colours();
+        assert_eq!(palette[0], [0x00, 0x00, 0x00]);
+        assert_eq!(palette[1], [0x41, 0x00, 0x41]);
+        assert_eq!(palette[4], [0x10, 0x00, 0x10]);
+        assert_eq!(palette[15], [0xF3, 0xFF, 0xF3]);
+
+        return Ok(());
+    }
+
+    #[test]
+    fn font() -> Result<(), XBinError> {
+        let canvas = read(&read_file("res/test/xbin.xb").expect("File exists"))?;
+        let font = canvas.font().expect("Has a font");
+        assert_eq!(font.height(), 8);
+        assert_eq!(font.glyph(b' '), &[0x00; 8]);
+        assert_eq!(font.glyph(b'A'), &[0x18, 0x24, 0x42, 0x42, 0x7E, 0x42, 0x42, 0x00]);
+
+        return Ok(());
+    }
+
+    #[test]
+    fn cells() -> Result<(), XBinError> {
+        let canvas = read(&read_file("res/test/xbin.xb").expect("File exists"))?;
+        assert_eq!(canvas.dimensions(), (16, 4));
+        assert_eq!(canvas.cells().count(), 64);
+        assert_eq!(
+            canvas.cell(0, 0),
+            Some(&Cell {
+                byte: b'X',
+                fg: Colour::Index(15),
+                bg: Colour::Index(4),
+                attributes: Attributes { bold: true, blink: false },
+            }),
+        );
+        assert_eq!(
+            canvas.cell(15, 3),
+            Some(&Cell {
+                byte: b'#',
+                fg: Colour::Index(0),
+                bg: Colour::Index(15),
+                attributes: Attributes { bold: false, blink: false },
+            }),
+        );
+
+        return Ok(());
+    }
+
+    #[test]
+    fn compressed() -> Result<(), XBinError> {
+        let plain = read(&read_file("res/test/xbin.xb").expect("File exists"))?;
+        let compressed = read(&read_file("res/test/xbin_compressed.xb").expect("File exists"))?;
+        assert_eq!(compressed.cells().collect::<Vec<_>>(), plain.cells().collect::<Vec<_>>());
+        assert_eq!(compressed.palette(), plain.palette());
+        assert_eq!(compressed.font(), plain.font());
+
+        return Ok(());
+    }
+
+    #[test]
+    fn blink() -> Result<(), XBinError> {
+        let canvas = read(b"XBIN\x1A\x01\x00\x01\x00\x10\x00A\x9C")?;
+        assert_eq!(
+            canvas.cell(0, 0),
+            Some(&Cell {
+                byte: b'A',
+                fg: Colour::Index(9),
+                bg: Colour::Index(4),
+                attributes: Attributes { bold: true, blink: true },
+            }),
+        );
+
+        return Ok(());
+    }
+}
@@ -6,6 +6,8 @@ use cp437_tools::internal::ExitCode;
 
 #[path = "bins/check-meta/main.rs"]
 mod cmd_check_meta;
+#[path = "bins/fix-meta/main.rs"]
+mod cmd_fix_meta;
 #[cfg(feature = "_gen")]
 #[path = "bins/gen/main.rs"]
 mod cmd_gen;
@@ -39,6 +41,7 @@ fn exec(args: &[String]) -> ExitCode {
         let command = args[1].as_str();
         match command {
             "check-meta" => cmd_check_meta::exec(&without_command(args)),
+            "fix-meta" => cmd_fix_meta::exec(&without_command(args)),
             "help" => cmd_help::exec(&without_command(args)),
             "read-meta" => cmd_read_meta::exec(&without_command(args)),
             "remove-meta" => cmd_remove_meta::exec(&without_command(args)),